@@ -38,6 +38,50 @@ if [[ ! -f "$MM_EXEC" ]]; then
     fi
 fi
 
+# Per-engine UCI options from the tournament file (engine_options.<engine> = ...)
+declare -A ENGINE_OPTIONS=()
+# Handling of options the engine does not advertise or accept: 'warn' (skip the option) or 'strict' (abort the game)
+OPTION_CHECK="warn"
+# Engine answer timeouts in ms, e.g. 'uciok=10000,readyok=5000,bestmove=1000' (empty: Matt-Magie defaults)
+TIMEOUTS=""
+# Keep the engine processes of a pairing alive for all its games ('true' or 'false')
+PERSISTENT_ENGINES="false"
+# Matt-Magie ponder argument: 'ponder' lets engines think on the opponent's time, empty disables it
+PONDER_ARG=""
+# Show the running games live: the board for sequential games, a dashboard for parallel ones ('true' or 'false')
+LIVE_VIEW="false"
+# Tie-breaks of the Swiss standings in order: 'buchholz' and/or 'sonneborn_berger'
+SWISS_TIEBREAKS="buchholz, sonneborn_berger"
+# Number of challengers at the start of the engine list in gauntlet mode, the other engines are their opponents
+GAUNTLET_CHALLENGERS=1
+# Knockout matches still tied after their game pairs: up to this many extra pairs, then 'sudden_death' or 'armageddon'
+KNOCKOUT_EXTRA_PAIRS=1
+KNOCKOUT_DECIDER="sudden_death"
+# NDJSON file that gets one JSON record per finished game (empty: no JSON output)
+RESULTS_JSON=""
+# Matt-Magie tags argument: optional PGN tags to add (Name) or drop (-Name) and custom tags (Name=Value), ';' separated
+PGN_TAGS=""
+# Custom PGN tags from the tournament file (pgn_tag.<Name> = value) in file order
+PGN_CUSTOM_TAGS=()
+# Log level of the games: 'error', 'warn', 'info', 'debug' (engines also get 'debug on') or 'uci' (every engine line)
+LOG_LEVEL="uci"
+# Directory that gets one log file per game, named after the round and the pairing (empty: no game logs)
+GAME_LOG_DIR=""
+# Log lines up to this level are also printed on the console (empty: none)
+CONSOLE_LOG=""
+# Parallel games only start while the 1 minute load per core is at most MAX_LOAD and at least MIN_FREE_MEMORY
+# percent of the memory is available (0 switches a check off)
+MAX_LOAD="1.5"
+MIN_FREE_MEMORY="10"
+# Directory that gets a replay script for every engine crash (empty: next to the PGN of the games)
+CRASH_DIR=""
+# Matt-Magie limits argument: own cores per engine, nice level and memory cap in MiB, e.g. "cpus=1,nice=10,memory=512"
+LIMITS=""
+# Named options of `Matt-Magie match` for the next run, see match_flags
+MATCH_FLAGS=()
+# Continue the tournament from its state file and play only the missing games ('true' or 'false')
+RESUME="false"
+
 # Banners and Header
 print_header() {
     clear
@@ -53,12 +97,22 @@ print_header() {
     echo ""
 }
 
-# Scan engines directory
+# Engine names defined in the engine registry (engines.toml or $MM_ENGINES_FILE)
+list_registry_engines() {
+    "$MM_EXEC" --list-engines 2>/dev/null
+}
+
+# Scan engine registry and engines directory
 list_engines() {
     local files=()
+    local registered
+    registered=$(list_registry_engines)
+    for name in $registered; do
+        files+=("$name")
+    done
     if [ -d "engines" ]; then
         for f in engines/*; do
-            if [[ -x "$f" && -f "$f" ]]; then
+            if [[ -x "$f" && -f "$f" ]] && ! grep -qxF "$(basename "$f")" <<< "$registered"; then
                 files+=("$(basename "$f")")
             fi
         done
@@ -66,6 +120,63 @@ list_engines() {
     echo "${files[@]}"
 }
 
+# Engine argument for Matt-Magie: registered engines by name, all others by their path in engines/
+engine_arg() {
+    local name="$1"
+    if list_registry_engines | grep -qxF "$name"; then
+        echo "$name"
+    else
+        echo "engines/$name"
+    fi
+}
+
+# Options of one engine: the shared engine_options followed by its own, so its own win on duplicate keys
+engine_options_for() {
+    local name="$1"
+    local shared="$2"
+    local own="${ENGINE_OPTIONS[$name]:-}"
+    if [[ -n "$shared" && -n "$own" ]]; then
+        echo "$shared, $own"
+    else
+        echo "${shared}${own}"
+    fi
+}
+
+# Parallel games for 'concurrency = auto': two engines per game, each on as many cores as it has threads
+# (its Threads option of the registry and the engine options, at least cpus_per_engine). Prints "games cores threads".
+auto_concurrency() {
+    local engines_str="$1"
+    local shared="$2"
+    local cpus_per_engine=""
+    if [[ "$LIMITS" =~ cpus=([0-9]+) ]]; then
+        cpus_per_engine="${BASH_REMATCH[1]}"
+    fi
+    local args=()
+    local eng engine_names
+    IFS=',' read -r -a engine_names <<< "$engines_str"
+    for eng in "${engine_names[@]}"; do
+        args+=("$(engine_arg "$eng")" "$(engine_options_for "$eng" "$shared")")
+    done
+    "$MM_EXEC" --auto-concurrency "$cpus_per_engine" "${args[@]}"
+}
+
+# Holds back the next parallel game while the machine is overloaded, as long as own games are running;
+# without them the load is not Matt-Magie's and waiting would not lower it
+wait_for_load() {
+    local reason=""
+    local paused="false"
+    while [[ "$(jobs -rp | wc -l)" -gt 0 ]] && ! reason=$("$MM_EXEC" --load-check "$MAX_LOAD" "$MIN_FREE_MEMORY"); do
+        if [[ "$paused" == "false" ]]; then
+            echo -e "${YELLOW}Pausing new games: $reason${NC}"
+            paused="true"
+        fi
+        sleep 2
+    done
+    if [[ "$paused" == "true" ]]; then
+        echo -e "${GREEN}Resuming new games${NC}"
+    fi
+}
+
 # Import Engine from suprah build
 import_engine() {
     print_header
@@ -145,8 +256,10 @@ run_single_match() {
         echo -e "${RED}Invalid choice.${NC}"
     done
 
-    local engine1="engines/${engines[$e1_idx]}"
-    local engine2="engines/${engines[$e2_idx]}"
+    local engine1
+    local engine2
+    engine1=$(engine_arg "${engines[$e1_idx]}")
+    engine2=$(engine_arg "${engines[$e2_idx]}")
 
     echo ""
     # Time Settings
@@ -225,6 +338,47 @@ run_single_match() {
     read -p "Press Enter to continue..." temp
 }
 
+# Variant argument of a game; both games of a pairing share the same Chess960 start position
+variant_arg() {
+    local variant="$1"
+    local seed="$2"
+    local pairing_num="$3"
+    if [[ "$variant" == "chess960" ]]; then
+        echo "chess960:seed:$((seed + pairing_num))"
+    else
+        echo "standard"
+    fi
+}
+
+# Named options of `Matt-Magie match` for a run of the tournament in MATCH_FLAGS, settings that are not
+# configured are left out
+match_flags() {
+    local pgn="$1"
+    local live_arg="$2"
+    local json="$3"
+
+    MATCH_FLAGS=(--timeouts "$TIMEOUTS" --unfinished save --tags "$PGN_TAGS" --crash-dir "${CRASH_DIR:-${pgn%.pgn}.crashes}" --limits "$LIMITS")
+    if [[ "$OPTION_CHECK" == "strict" ]]; then
+        MATCH_FLAGS+=(--strict-options)
+    fi
+    if [[ -n "$PONDER_ARG" ]]; then
+        MATCH_FLAGS+=(--ponder)
+    fi
+    case "$live_arg" in
+        live) MATCH_FLAGS+=(--live) ;;
+        live:*) MATCH_FLAGS+=(--live-dir "${live_arg#live:}") ;;
+    esac
+    if [[ -n "$json" ]]; then
+        MATCH_FLAGS+=(--json "$json")
+    fi
+    if [[ -n "$GAME_LOG_DIR" ]]; then
+        MATCH_FLAGS+=(--game-logs "$GAME_LOG_DIR")
+    fi
+    if [[ -n "$CONSOLE_LOG" ]]; then
+        MATCH_FLAGS+=(--console-log "$CONSOLE_LOG")
+    fi
+}
+
 # Execute Tournament Games (Non-interactive & Interactive backend)
 run_single_game_job() {
     local e1="$1"
@@ -237,33 +391,95 @@ run_single_game_job() {
     local time_control="$8"
     local time_inc="$9"
     local logging="${10}"
-    local debugging="${11}"
-    local engine_options="${12}"
+    local e1_options="${11}"
+    local e2_options="${12}"
     local total_games="${13}"
     local e1_name="${14}"
     local e2_name="${15}"
+    local game_variant="${16:-standard}"
+    local games="${17:-1}"
+    local live_arg="${18:-}"
+    local result_round="${19:-}"
 
     local tmp_pgn="${pgn}.game_${game_num}.tmp"
     local tmp_log="${logfile}.game_${game_num}.tmp"
-
-    $MM_EXEC "$e1" "$e2" "$tmp_log" "$tmp_pgn" "$event" "$site" "$game_num" "$time_control" "$time_inc" "$logging" "$debugging" "$engine_options"
+    local tmp_json
+    tmp_json=$(json_tmp_file "$tmp_pgn")
+    # Leftovers of an interrupted run must not be appended to
+    rm -f "$tmp_pgn" "$tmp_log" "$tmp_json"
+
+    match_flags "$pgn" "$live_arg" "$tmp_json"
+    "$MM_EXEC" match "$e1" "$e2" --log "$tmp_log" --pgn "$tmp_pgn" --event "$event" --site "$site" --round "$game_num" \
+        --time "$time_control" --inc "$time_inc" --log-level "$logging" --options1 "$e1_options" --options2 "$e2_options" \
+        --variant "$game_variant" --games "$games" "${MATCH_FLAGS[@]}"
+    local status=$?
+    if [[ $status -ge 128 ]]; then
+        # Killed by a signal, a resumed tournament plays the run again
+        rm -f "$tmp_pgn" "$tmp_log" "$tmp_json"
+        return
+    fi
 
     (
         flock -x 200
-        if [[ -f "$tmp_pgn" ]]; then
-            cat "$tmp_pgn" >> "$pgn"
-        fi
         if [[ -f "$tmp_log" ]]; then
             cat "$tmp_log" >> "$logfile"
         fi
-        echo -e "${YELLOW}=== Game $game_num/$total_games Finished: $e1_name vs $e2_name ===${NC}"
+        if [[ $status -ne 0 ]]; then
+            echo -e "${RED}Game $game_num failed (exit code $status), a resumed tournament plays it again${NC}"
+        elif ! record_finished_run "$tmp_pgn" "$pgn" "$game_num" "$result_round" "$e1_name" "$e2_name"; then
+            echo -e "${RED}Game $game_num could not be saved, a resumed tournament plays it again${NC}"
+        elif [[ -n "$live_arg" ]]; then
+            :  # the dashboard shows the progress
+        elif [[ "$games" -gt 1 ]]; then
+            echo -e "${YELLOW}=== Games $game_num-$((game_num + games - 1))/$total_games Finished: $e1_name vs $e2_name ===${NC}"
+        elif [[ "$game_num" == *.* ]]; then
+            echo -e "${YELLOW}=== Game $game_num Finished: $e1_name vs $e2_name ===${NC}"
+        else
+            echo -e "${YELLOW}=== Game $game_num/$total_games Finished: $e1_name vs $e2_name ===${NC}"
+        fi
         if [[ -f "$tmp_pgn" ]]; then
             tail -n 12 "$tmp_pgn"
         fi
         echo ""
     ) 200>"${pgn}.lock"
 
-    rm -f "$tmp_pgn" "$tmp_log"
+    rm -f "$tmp_pgn" "$tmp_log" "$tmp_json"
+}
+
+# JSON records of a run are written next to its temporary PGN, nothing if no JSON output is configured
+json_tmp_file() {
+    if [[ -n "$RESULTS_JSON" ]]; then
+        echo "${1%.tmp}.json.tmp"
+    fi
+}
+
+# Appends the games of a finished Matt-Magie run to the PGN and marks the run as played in the state file;
+# Swiss and knockout games also record their result for the pairing of the next games. Fails without
+# marking the run if its games could not be appended, a resumed tournament plays it again
+record_finished_run() {
+    local tmp_pgn="$1"
+    local pgn="$2"
+    local game_num="$3"
+    local result_round="${4:-}"
+    local white_name="${5:-}"
+    local black_name="${6:-}"
+
+    local result=""
+    if [[ -f "$tmp_pgn" ]]; then
+        "$MM_EXEC" --append-pgn "$tmp_pgn" "$pgn" || return 1
+        result=$(sed -n 's/^\[Result "\(.*\)"\]$/\1/p' "$tmp_pgn" | head -n 1)
+    fi
+    local tmp_json
+    tmp_json=$(json_tmp_file "$tmp_pgn")
+    if [[ -n "$tmp_json" && -f "$tmp_json" ]]; then
+        "$MM_EXEC" --append-json "$tmp_json" "$RESULTS_JSON" || return 1
+        rm -f "$tmp_json"
+    fi
+    # A run without a saved game counts as played without a result, so it is not paired again
+    if [[ -n "$result_round" ]]; then
+        printf 'result\t%s\t%s\t%s\t%s\n' "$result_round" "$white_name" "$black_name" "${result:-*}" >> "${pgn}.state"
+    fi
+    echo "done $game_num" >> "${pgn}.state"
 }
 
 execute_tournament_games() {
@@ -275,146 +491,357 @@ execute_tournament_games() {
     local engine_options="${6:-}"
     local tournament_mode="${7:-round_robin}"
     local concurrency="${8:-1}"
+    local variant="${9:-standard}"
+    local chess960_seed="${10:-}"
 
     # Convert comma-separated string back to array
     local OLD_IFS="$IFS"
     IFS=',' read -r -a engines <<< "$engines_str"
     IFS="$OLD_IFS"
 
+    local concurrency_note=""
+    if [[ "$concurrency" == "auto" ]]; then
+        local cores threads
+        IFS=$'\t' read -r concurrency cores threads < <(auto_concurrency "$engines_str" "$engine_options")
+        if [[ ! "$concurrency" =~ ^[0-9]+$ ]]; then
+            echo -e "${RED}Error: can not determine the concurrency from the cores!${NC}"
+            exit 1
+        fi
+        concurrency_note=" (auto: $cores cores, $threads threads per engine)"
+    fi
+
+    # The state file lists the finished runs by their first game number; it only fits
+    # a tournament with the same schedule, which these settings determine
+    local state_file="${pgn}.state"
+    local schedule="engines=$engines_str mode=$tournament_mode rounds=$rounds variant=$variant persistent=$PERSISTENT_ENGINES"
+    if [[ "$tournament_mode" == "knockout" ]]; then
+        schedule="$schedule extra_pairs=$KNOCKOUT_EXTRA_PAIRS decider=$KNOCKOUT_DECIDER"
+    elif [[ "$tournament_mode" == "gauntlet" && "$GAUNTLET_CHALLENGERS" -gt 1 ]]; then
+        schedule="$schedule challengers=$GAUNTLET_CHALLENGERS"
+    fi
+    declare -A finished_runs=()
+    if [[ "$RESUME" == "true" && -f "$state_file" ]]; then
+        local state_schedule state_seed
+        state_schedule=$(sed -n 's/^schedule //p' "$state_file")
+        state_seed=$(sed -n 's/^seed //p' "$state_file")
+        if [[ "$state_schedule" != "$schedule" ]]; then
+            echo -e "${RED}Error: '$state_file' belongs to a different tournament and can not be resumed!${NC}"
+            echo -e "  State file: $state_schedule"
+            echo -e "  Tournament: $schedule"
+            exit 1
+        fi
+        if [[ -n "$chess960_seed" && "$chess960_seed" != "$state_seed" ]]; then
+            echo -e "${RED}Error: 'chess960_seed' is $chess960_seed, but the tournament in '$state_file' was started with seed $state_seed!${NC}"
+            exit 1
+        fi
+        chess960_seed="$state_seed"
+        local finished_num
+        for finished_num in $(sed -n 's/^done //p' "$state_file"); do
+            finished_runs[$finished_num]=1
+        done
+    else
+        if [[ -z "$chess960_seed" ]]; then
+            chess960_seed=$(date +%s)
+        fi
+        printf 'schedule %s\nseed %s\n' "$schedule" "$chess960_seed" > "$state_file"
+    fi
+
     local num_engines=${#engines[@]}
     local match_pairs=0
     if [[ "$tournament_mode" == "gauntlet" ]]; then
-        match_pairs=$((num_engines - 1))
+        match_pairs=$((GAUNTLET_CHALLENGERS * (num_engines - GAUNTLET_CHALLENGERS)))
     else
         match_pairs=$((num_engines * (num_engines - 1) / 2))
     fi
     local total_games=$((match_pairs * 2 * rounds))
+    if [[ "$tournament_mode" == "swiss" ]]; then
+        total_games=$((num_engines / 2 * rounds))
+    elif [[ "$tournament_mode" == "knockout" ]]; then
+        total_games=$(((num_engines - 1) * 2 * rounds))  # without tie-break games
+    fi
 
     print_header
     echo -e "${GREEN}Tournament started:${NC}"
     echo -e "  Number of Engines: $num_engines"
     echo -e "  Time Control: $((time_control/1000))s + $((time_inc))ms"
     echo -e "  Rounds: $rounds"
+    if [[ "$tournament_mode" == "swiss" ]]; then
+        echo -e "  Mode: Swiss (tie-breaks: $SWISS_TIEBREAKS)"
+    elif [[ "$tournament_mode" == "gauntlet" ]]; then
+        echo -e "  Mode: Gauntlet (challengers: $(IFS=','; echo "${engines[*]:0:GAUNTLET_CHALLENGERS}" | sed 's/,/, /g'))"
+    elif [[ "$tournament_mode" == "knockout" ]]; then
+        echo -e "  Mode: Knockout ($rounds game pairs per match, up to $KNOCKOUT_EXTRA_PAIRS extra pairs, then ${KNOCKOUT_DECIDER//_/ })"
+    fi
     echo -e "  Total Games: $total_games"
-    echo -e "  Concurrency: $concurrency"
+    echo -e "  Concurrency: $concurrency$concurrency_note"
+    if [[ "$concurrency" -gt 1 ]]; then
+        echo -e "  Load Guard: max load $MAX_LOAD per core, min free memory $MIN_FREE_MEMORY% (0: off)"
+    fi
+    if [[ "$PERSISTENT_ENGINES" == "true" ]]; then
+        echo -e "  Persistent Engines: on"
+    fi
+    if [[ -n "$PONDER_ARG" ]]; then
+        echo -e "  Ponder: on"
+    fi
+    if [[ "$LIVE_VIEW" == "true" ]]; then
+        echo -e "  Live View: on"
+    fi
+    if [[ -n "$RESULTS_JSON" ]]; then
+        echo -e "  JSON Output: $RESULTS_JSON"
+    fi
+    if [[ -n "$PGN_TAGS" ]]; then
+        echo -e "  PGN Tags: $PGN_TAGS"
+    fi
+    echo -e "  Log Level: $LOG_LEVEL${CONSOLE_LOG:+ (console: $CONSOLE_LOG)}"
+    if [[ -n "$GAME_LOG_DIR" ]]; then
+        echo -e "  Game Logs: $GAME_LOG_DIR/"
+    fi
+    if [[ -n "$LIMITS" ]]; then
+        echo -e "  Engine Limits: $LIMITS"
+    fi
+    if [[ "$variant" == "chess960" ]]; then
+        echo -e "  Variant: Chess960 (seed $chess960_seed)"
+    fi
     if [[ ! -z "$engine_options" ]]; then
         echo -e "  Engine Options: $engine_options"
     fi
+    for eng in "${engines[@]}"; do
+        if [[ -n "${ENGINE_OPTIONS[$eng]:-}" ]]; then
+            echo -e "  Options $eng: ${ENGINE_OPTIONS[$eng]}"
+        fi
+    done
     echo ""
 
     local logfile="./mattmagie.log"
     local event="Suprah-Tournament"
     local site="local"
-    local logging="log_on"
-    local debuging="debug_on"
+    local logging="$LOG_LEVEL"
 
-    if [[ -f "$pgn" ]]; then
+    if [[ "$RESUME" == "true" ]]; then
+        local played_games=${#finished_runs[@]}
+        if [[ "$PERSISTENT_ENGINES" == "true" && "$tournament_mode" != "swiss" && "$tournament_mode" != "knockout" ]]; then
+            played_games=$((played_games * 2 * rounds))
+        fi
+        echo -e "${YELLOW}Resuming tournament: $played_games of $total_games games are already played and skipped.${NC}"
+        echo ""
+        touch "$pgn"
+    elif [[ -f "$pgn" ]]; then
         echo -e "${YELLOW}Note: PGN file '$(basename "$pgn")' already exists. New games will be appended!${NC}"
         echo ""
     else
         touch "$pgn"
     fi
 
+    # Live view: sequential games redraw their board, parallel games write status lines for a dashboard
+    local live_arg=""
+    local live_dir=""
+    local dashboard_pid=""
+    if [[ "$LIVE_VIEW" == "true" ]]; then
+        if [[ "$concurrency" -gt 1 ]]; then
+            live_dir="${pgn}.live"
+            rm -rf "$live_dir"
+            mkdir -p "$live_dir"
+            live_arg="live:$live_dir"
+            "$MM_EXEC" --dashboard "$live_dir" "$pgn" &
+            dashboard_pid=$!
+            disown "$dashboard_pid"
+        else
+            live_arg="live"
+        fi
+    fi
+
     if [[ "$concurrency" -gt 1 ]]; then
-        trap 'echo -e "\n${RED}Tournament interrupted! Killing all background games...${NC}"; kill $(jobs -pr) 2>/dev/null; exit 1' SIGINT SIGTERM
+        # Every running Matt-Magie gets the signal and quits its engines, the interrupted runs are not recorded
+        trap 'echo -e "\n${RED}Tournament interrupted! Stopping all background games...${NC}"; for job in $(jobs -pr); do pkill -TERM -P "$job"; done; wait; kill $dashboard_pid 2>/dev/null; echo -e "${RED}Continue it with: ./mm.sh -t <tournament_file.trn> --resume${NC}"; exit 1' SIGINT SIGTERM
     fi
 
+    # Pairings in playing order as "engine_a,engine_b,pairing_num[,round_tag]"; with persistent engines
+    # a pairing plays all its rounds in one Matt-Magie run, so its rounds are not listed separately.
+    # Swiss rounds and knockout stages are paired batch by batch from the results so far, one game per
+    # pairing; knockout games are numbered by their round tag stage.match.game instead of the game number.
+    local pairing_rounds=$rounds
+    local games_per_run=1
+    local variant_stride=1  # Matt-Magie adds the round to the seed of a persistent run
+    if [[ "$PERSISTENT_ENGINES" == "true" && "$tournament_mode" != "swiss" && "$tournament_mode" != "knockout" ]]; then
+        pairing_rounds=1
+        games_per_run=$((2 * rounds))
+        variant_stride=$rounds
+    fi
+    local pairing_num=0
     local game_num=1
-    if [[ "$concurrency" -le 1 ]]; then
-        for ((r=0; r<rounds; r++)); do
-            if [[ "$tournament_mode" == "gauntlet" ]]; then
-                local challenger_name="${engines[0]}"
-                for ((j=1; j<num_engines; j++)); do
-                    local opp_name="${engines[$j]}"
-                    local e1="engines/$challenger_name"
-                    local e2="engines/$opp_name"
-
-                    echo -e "${YELLOW}=== Game $game_num/$total_games: $challenger_name (White) vs $opp_name (Black) ===${NC}"
-                    $MM_EXEC "$e1" "$e2" "$logfile" "$pgn" "$event" "$site" "$game_num" "$time_control" "$time_inc" "$logging" "$debuging" "$engine_options"
-                    tail -n 12 "$pgn"
-                    echo ""
-                    game_num=$((game_num+1))
-
-                    echo -e "${YELLOW}=== Game $game_num/$total_games: $opp_name (White) vs $challenger_name (Black) (Colors swapped) ===${NC}"
-                    $MM_EXEC "$e2" "$e1" "$logfile" "$pgn" "$event" "$site" "$game_num" "$time_control" "$time_inc" "$logging" "$debuging" "$engine_options"
-                    tail -n 12 "$pgn"
-                    echo ""
-                    game_num=$((game_num+1))
+    local batch=0
+    local previous_pairings=""
+    while true; do
+        batch=$((batch+1))
+        local pairings=()
+        local result_round=""
+        if [[ "$tournament_mode" == "swiss" ]]; then
+            if [[ "$batch" -gt "$rounds" ]]; then
+                break
+            fi
+            result_round=$batch
+            if [[ "$concurrency" -le 1 ]]; then
+                echo -e "${CYAN}=== Swiss Round $batch/$rounds ===${NC}"
+            fi
+            local kind white black
+            while IFS=$'\t' read -r kind white black; do
+                if [[ "$kind" == "game" ]]; then
+                    pairings+=("$white,$black,$pairing_num")
+                    pairing_num=$((pairing_num+1))
+                elif [[ "$kind" == "bye" ]]; then
+                    echo -e "${YELLOW}=== Swiss Round $batch: $white has a bye (1 point) ===${NC}"
+                    if ! grep -qxF "$(printf 'bye\t%s\t%s' "$batch" "$white")" "$state_file"; then
+                        printf 'bye\t%s\t%s\n' "$batch" "$white" >> "$state_file"
+                    fi
+                fi
+            done < <("$MM_EXEC" --swiss-pairing "$state_file" "$batch" "$engines_str")
+        elif [[ "$tournament_mode" == "knockout" ]]; then
+            local kind tag white black position
+            while IFS=$'\t' read -r kind tag white black position; do
+                if [[ "$kind" == "game" ]]; then
+                    pairings+=("$white,$black,$position,$tag")
+                fi
+            done < <("$MM_EXEC" --knockout-games "$state_file" "$engines_str" "$rounds" "$KNOCKOUT_EXTRA_PAIRS" "$KNOCKOUT_DECIDER")
+            if [[ ${#pairings[@]} -eq 0 ]]; then
+                break
+            fi
+            # Unfinished games are paired again; if none of them could be finished, stop instead of looping
+            if [[ "${pairings[*]}" == "$previous_pairings" ]]; then
+                echo -e "${RED}Knockout games could not be finished! Continue the tournament with: ./mm.sh -t <tournament_file.trn> --resume${NC}"
+                break
+            fi
+            previous_pairings="${pairings[*]}"
+        else
+            if [[ "$batch" -gt 1 ]]; then
+                break
+            fi
+            for ((r=0; r<pairing_rounds; r++)); do
+                if [[ "$tournament_mode" == "gauntlet" ]]; then
+                    # All challengers meet an opponent from the same start position, so their results compare
+                    for ((j=GAUNTLET_CHALLENGERS; j<num_engines; j++)); do
+                        for ((c=0; c<GAUNTLET_CHALLENGERS; c++)); do
+                            pairings+=("${engines[$c]},${engines[$j]},$pairing_num")
+                        done
+                        pairing_num=$((pairing_num+1))
+                    done
+                else
+                    for ((i=0; i<num_engines; i++)); do
+                        for ((j=i+1; j<num_engines; j++)); do
+                            pairings+=("${engines[$i]},${engines[$j]},$pairing_num")
+                            pairing_num=$((pairing_num+1))
+                        done
+                    done
+                fi
+            done
+        fi
 
-                    sleep 1
-                done
-            else
-                for ((i=0; i<num_engines; i++)); do
-                    for ((j=i+1; j<num_engines; j++)); do
-                        local e1_name="${engines[$i]}"
-                        local e2_name="${engines[$j]}"
-                        local e1="engines/$e1_name"
-                        local e2="engines/$e2_name"
+        for pairing in "${pairings[@]}"; do
+            local a_name b_name pairing_index round_tag
+            IFS=',' read -r a_name b_name pairing_index round_tag <<< "$pairing"
 
-                        echo -e "${YELLOW}=== Game $game_num/$total_games: $e1_name (White) vs $e2_name (Black) ===${NC}"
-                        $MM_EXEC "$e1" "$e2" "$logfile" "$pgn" "$event" "$site" "$game_num" "$time_control" "$time_inc" "$logging" "$debuging" "$engine_options"
-                        tail -n 12 "$pgn"
-                        echo ""
-                        game_num=$((game_num+1))
-
-                        echo -e "${YELLOW}=== Game $game_num/$total_games: $e2_name (White) vs $e1_name (Black) (Colors swapped) ===${NC}"
-                        $MM_EXEC "$e2" "$e1" "$logfile" "$pgn" "$event" "$site" "$game_num" "$time_control" "$time_inc" "$logging" "$debuging" "$engine_options"
-                        tail -n 12 "$pgn"
-                        echo ""
-                        game_num=$((game_num+1))
-
-                        sleep 1
-                    done
-                done
+            # Both colors of a pairing share the start position; a persistent run alternates colors itself
+            local game_variant
+            game_variant=$(variant_arg "$variant" "$chess960_seed" "$((pairing_index * variant_stride))")
+            local runs=("$a_name,$b_name")
+            if [[ "$games_per_run" -eq 1 && "$tournament_mode" != "swiss" && "$tournament_mode" != "knockout" ]]; then
+                runs+=("$b_name,$a_name")
             fi
-        done
-    else
-        for ((r=0; r<rounds; r++)); do
-            if [[ "$tournament_mode" == "gauntlet" ]]; then
-                local challenger_name="${engines[0]}"
-                for ((j=1; j<num_engines; j++)); do
-                    local opp_name="${engines[$j]}"
-                    local e1="engines/$challenger_name"
-                    local e2="engines/$opp_name"
-
-                    while [ "$(jobs -rp | wc -l)" -ge "$concurrency" ]; do
-                        wait -n
-                    done
-                    run_single_game_job "$e1" "$e2" "$logfile" "$pgn" "$event" "$site" "$game_num" "$time_control" "$time_inc" "$logging" "$debuging" "$engine_options" "$total_games" "$challenger_name" "$opp_name" &
-                    game_num=$((game_num+1))
 
+            for run in "${runs[@]}"; do
+                local e1_name e2_name
+                IFS=',' read -r e1_name e2_name <<< "$run"
+                local run_num="$game_num"
+                local run_result_round="$result_round"
+                if [[ -n "$round_tag" ]]; then
+                    run_num="$round_tag"
+                    run_result_round="$round_tag"
+                fi
+                # --knockout-games leaves out the finished games, an unfinished one is played again
+                if [[ "$tournament_mode" != "knockout" && -n "${finished_runs[$run_num]:-}" ]]; then
+                    game_num=$((game_num + games_per_run))
+                    continue
+                fi
+                local e1
+                local e2
+                e1=$(engine_arg "$e1_name")
+                e2=$(engine_arg "$e2_name")
+                local e1_options
+                local e2_options
+                e1_options=$(engine_options_for "$e1_name" "$engine_options")
+                e2_options=$(engine_options_for "$e2_name" "$engine_options")
+
+                if [[ "$concurrency" -le 1 ]]; then
+                    if [[ -n "$round_tag" ]]; then
+                        echo -e "${YELLOW}=== Game $round_tag: $e1_name (White) vs $e2_name (Black) ===${NC}"
+                    elif [[ "$games_per_run" -gt 1 ]]; then
+                        echo -e "${YELLOW}=== Games $game_num-$((game_num + games_per_run - 1))/$total_games: $e1_name vs $e2_name (alternating colors) ===${NC}"
+                    elif [[ "$e1_name" == "$a_name" ]]; then
+                        echo -e "${YELLOW}=== Game $game_num/$total_games: $e1_name (White) vs $e2_name (Black) ===${NC}"
+                    else
+                        echo -e "${YELLOW}=== Game $game_num/$total_games: $e1_name (White) vs $e2_name (Black) (Colors swapped) ===${NC}"
+                    fi
+                    # Games go to the PGN only once the run is complete, an interrupted run leaves no partial games behind
+                    local tmp_pgn="${pgn}.game_${run_num}.tmp"
+                    local tmp_json
+                    tmp_json=$(json_tmp_file "$tmp_pgn")
+                    rm -f "$tmp_pgn" "$tmp_json"
+                    match_flags "$pgn" "$live_arg" "$tmp_json"
+                    "$MM_EXEC" match "$e1" "$e2" --log "$logfile" --pgn "$tmp_pgn" --event "$event" --site "$site" --round "$run_num" \
+                        --time "$time_control" --inc "$time_inc" --log-level "$logging" --options1 "$e1_options" --options2 "$e2_options" \
+                        --variant "$game_variant" --games "$games_per_run" "${MATCH_FLAGS[@]}"
+                    local status=$?
+                    if [[ $status -ge 128 ]]; then
+                        rm -f "$tmp_pgn" "$tmp_json"
+                        echo -e "${RED}Tournament interrupted! Continue it with: ./mm.sh -t <tournament_file.trn> --resume${NC}"
+                        exit 1
+                    fi
+                    if [[ $status -ne 0 ]]; then
+                        echo -e "${RED}Game $run_num failed (exit code $status), a resumed tournament plays it again${NC}"
+                    elif ! record_finished_run "$tmp_pgn" "$pgn" "$run_num" "$run_result_round" "$e1_name" "$e2_name"; then
+                        echo -e "${RED}Game $run_num could not be saved, a resumed tournament plays it again${NC}"
+                    fi
+                    if [[ -f "$tmp_pgn" ]]; then
+                        tail -n 12 "$tmp_pgn"
+                    fi
+                    rm -f "$tmp_pgn" "$tmp_json"
+                    echo ""
+                else
                     while [ "$(jobs -rp | wc -l)" -ge "$concurrency" ]; do
                         wait -n
                     done
-                    run_single_game_job "$e2" "$e1" "$logfile" "$pgn" "$event" "$site" "$game_num" "$time_control" "$time_inc" "$logging" "$debuging" "$engine_options" "$total_games" "$opp_name" "$challenger_name" &
-                    game_num=$((game_num+1))
-                done
-            else
-                for ((i=0; i<num_engines; i++)); do
-                    for ((j=i+1; j<num_engines; j++)); do
-                        local e1_name="${engines[$i]}"
-                        local e2_name="${engines[$j]}"
-                        local e1="engines/$e1_name"
-                        local e2="engines/$e2_name"
-
-                        while [ "$(jobs -rp | wc -l)" -ge "$concurrency" ]; do
-                            wait -n
-                        done
-                        run_single_game_job "$e1" "$e2" "$logfile" "$pgn" "$event" "$site" "$game_num" "$time_control" "$time_inc" "$logging" "$debuging" "$engine_options" "$total_games" "$e1_name" "$e2_name" &
-                        game_num=$((game_num+1))
+                    wait_for_load
+                    run_single_game_job "$e1" "$e2" "$logfile" "$pgn" "$event" "$site" "$run_num" "$time_control" "$time_inc" "$logging" "$e1_options" "$e2_options" "$total_games" "$e1_name" "$e2_name" "$game_variant" "$games_per_run" "$live_arg" "$run_result_round" &
+                fi
+                game_num=$((game_num + games_per_run))
+            done
 
-                        while [ "$(jobs -rp | wc -l)" -ge "$concurrency" ]; do
-                            wait -n
-                        done
-                        run_single_game_job "$e2" "$e1" "$logfile" "$pgn" "$event" "$site" "$game_num" "$time_control" "$time_inc" "$logging" "$debuging" "$engine_options" "$total_games" "$e2_name" "$e1_name" &
-                        game_num=$((game_num+1))
-                    done
-                done
+            if [[ "$concurrency" -le 1 ]]; then
+                sleep 1
             fi
         done
-        wait
+
+        # The next Swiss round or knockout batch is paired from the complete results of this one
+        if [[ "$tournament_mode" == "swiss" ]]; then
+            wait
+            echo -e "${GREEN}Standings after Swiss round $batch/$rounds:${NC}"
+            "$MM_EXEC" --swiss-standings "$state_file" "$batch" "$engines_str" "$SWISS_TIEBREAKS"
+            echo ""
+        elif [[ "$tournament_mode" == "knockout" ]]; then
+            wait
+        fi
+    done
+    wait
+
+    if [[ "$tournament_mode" == "knockout" ]]; then
+        echo -e "${GREEN}Knockout bracket:${NC}"
+        "$MM_EXEC" --knockout-bracket "$state_file" "$engines_str" "$rounds" "$KNOCKOUT_EXTRA_PAIRS" "$KNOCKOUT_DECIDER"
+        echo ""
     fi
 
+    if [[ -n "$dashboard_pid" ]]; then
+        kill "$dashboard_pid" 2>/dev/null
+        rm -rf "$live_dir"
+    fi
     if [[ "$concurrency" -gt 1 ]]; then
         trap - SIGINT SIGTERM
         rm -f "${pgn}.lock"
@@ -423,7 +850,7 @@ execute_tournament_games() {
     echo -e "${GREEN}Tournament finished! Here is the final scoreboard:${NC}"
     if [ -f "./summary.sh" ]; then
         if [[ "$tournament_mode" == "gauntlet" ]]; then
-            ./summary.sh "$pgn" --gauntlet "${engines[0]}"
+            ./summary.sh "$pgn" --gauntlet "$(IFS=','; echo "${engines[*]:0:GAUNTLET_CHALLENGERS}")"
         else
             ./summary.sh "$pgn"
         fi
@@ -451,6 +878,30 @@ run_file_tournament() {
     local options_val=""
     local mode_val=""
     local concurrency_val=""
+    local variant_val=""
+    local seed_val=""
+    local engines_file_val=""
+    local option_check_val=""
+    local timeouts_val=""
+    local persistent_val=""
+    local ponder_val=""
+    local live_view_val=""
+    local tiebreaks_val=""
+    local challengers_val=""
+    local json_output_val=""
+    local pgn_tags_val=""
+    local log_level_val=""
+    local game_logs_val=""
+    local console_log_val=""
+    local crash_dir_val=""
+    local cpus_per_engine_val=""
+    local max_load_val=""
+    local min_free_memory_val=""
+    local nice_val=""
+    local memory_limit_val=""
+    local opponents_val=""
+    local knockout_extra_pairs_val=""
+    local knockout_decider_val=""
 
     while IFS= read -r line || [[ -n "$line" ]]; do
         # Strip comments starting with #
@@ -461,11 +912,31 @@ run_file_tournament() {
         # Skip empty lines
         [[ -z "$line" ]] && continue
 
-        # Parse key = value
-        if [[ "$line" =~ ^([a-zA-Z_][a-zA-Z0-9_]*)[[:space:]]*=[[:space:]]*(.*)$ ]]; then
+        # Parse key = value or key.<engine> = value
+        if [[ "$line" =~ ^([a-zA-Z_][a-zA-Z0-9_]*)(\.([^[:space:]=]+))?[[:space:]]*=[[:space:]]*(.*)$ ]]; then
             local key="${BASH_REMATCH[1]}"
-            local val="${BASH_REMATCH[2]}"
-            
+            local key_engine="${BASH_REMATCH[3]}"
+            local val="${BASH_REMATCH[4]}"
+
+            if [[ -n "$key_engine" ]]; then
+                if [[ "$key" == "engine_options" ]]; then
+                    ENGINE_OPTIONS["$key_engine"]="$val"
+                elif [[ "$key" == "pgn_tag" ]]; then
+                    if [[ ! "$key_engine" =~ ^[A-Za-z0-9_]+$ || "$val" == *";"* ]]; then
+                        echo -e "${RED}Error: 'pgn_tag.$key_engine' needs a tag name of letters, digits and '_' and a value without ';'!${NC}"
+                        exit 1
+                    fi
+                    if [[ "$key_engine" =~ ^(Event|Site|Date|Round|White|Black|Result|Variant|SetUp|FEN|WhiteElo|BlackElo|ECO|Opening|TimeControl|Time|UTCDate|UTCTime|PlyCount|GameDuration|Termination)$ ]]; then
+                        echo -e "${RED}Error: the PGN tag '$key_engine' is set by Matt-Magie and can not be set in '$trn_file'!${NC}"
+                        exit 1
+                    fi
+                    PGN_CUSTOM_TAGS+=("$key_engine=$val")
+                else
+                    echo -e "${YELLOW}Warning: Unknown key '$key.$key_engine' in tournament file.${NC}"
+                fi
+                continue
+            fi
+
             case "$key" in
                 engines)
                     engines_val="$val"
@@ -491,6 +962,78 @@ run_file_tournament() {
                 concurrency)
                     concurrency_val="$val"
                     ;;
+                variant)
+                    variant_val="$val"
+                    ;;
+                chess960_seed)
+                    seed_val="$val"
+                    ;;
+                engines_file)
+                    engines_file_val="$val"
+                    ;;
+                option_check)
+                    option_check_val="$val"
+                    ;;
+                timeouts)
+                    timeouts_val="$val"
+                    ;;
+                persistent_engines)
+                    persistent_val="$val"
+                    ;;
+                ponder)
+                    ponder_val="$val"
+                    ;;
+                live_view)
+                    live_view_val="$val"
+                    ;;
+                json_output)
+                    json_output_val="$val"
+                    ;;
+                pgn_tags)
+                    pgn_tags_val="$val"
+                    ;;
+                log_level)
+                    log_level_val="$val"
+                    ;;
+                game_logs)
+                    game_logs_val="$val"
+                    ;;
+                console_log)
+                    console_log_val="$val"
+                    ;;
+                crash_dir)
+                    crash_dir_val="$val"
+                    ;;
+                cpus_per_engine)
+                    cpus_per_engine_val="$val"
+                    ;;
+                nice)
+                    nice_val="$val"
+                    ;;
+                memory_limit)
+                    memory_limit_val="$val"
+                    ;;
+                max_load)
+                    max_load_val="$val"
+                    ;;
+                min_free_memory)
+                    min_free_memory_val="$val"
+                    ;;
+                challengers)
+                    challengers_val="$val"
+                    ;;
+                opponents)
+                    opponents_val="$val"
+                    ;;
+                tiebreaks)
+                    tiebreaks_val="$val"
+                    ;;
+                knockout_extra_pairs)
+                    knockout_extra_pairs_val="$val"
+                    ;;
+                knockout_decider)
+                    knockout_decider_val="$val"
+                    ;;
                 *)
                     echo -e "${YELLOW}Warning: Unknown key '$key' in tournament file.${NC}"
                     ;;
@@ -498,6 +1041,25 @@ run_file_tournament() {
         fi
     done < "$trn_file"
 
+    # A gauntlet with separate challengers and opponents lists the challengers first
+    if [[ -n "$challengers_val" || -n "$opponents_val" ]]; then
+        if [[ -z "$challengers_val" || -z "$opponents_val" ]]; then
+            echo -e "${RED}Error: 'challengers' and 'opponents' must be specified together in '$trn_file'!${NC}"
+            exit 1
+        fi
+        if [[ -n "$engines_val" ]]; then
+            echo -e "${RED}Error: 'engines' can not be combined with 'challengers' and 'opponents' in '$trn_file'!${NC}"
+            exit 1
+        fi
+        if [[ -n "$mode_val" && "$mode_val" != "gauntlet" ]]; then
+            echo -e "${RED}Error: 'challengers' and 'opponents' need 'mode = gauntlet', found '$mode_val'!${NC}"
+            exit 1
+        fi
+        mode_val="gauntlet"
+        engines_val="$challengers_val,$opponents_val"
+        GAUNTLET_CHALLENGERS=$(echo "$challengers_val" | tr ',' '\n' | grep -c '[^[:space:]]')
+    fi
+
     # Validation
     if [[ -z "$engines_val" ]]; then
         echo -e "${RED}Error: 'engines' is not specified or empty in '$trn_file'!${NC}"
@@ -524,17 +1086,186 @@ run_file_tournament() {
     if [[ -z "$mode_val" ]]; then
         mode_val="round_robin"
     fi
-    if [[ "$mode_val" != "round_robin" && "$mode_val" != "gauntlet" ]]; then
-        echo -e "${RED}Error: 'mode' must be either 'round_robin' or 'gauntlet', found '$mode_val'!${NC}"
+    if [[ "$mode_val" != "round_robin" && "$mode_val" != "gauntlet" && "$mode_val" != "swiss" && "$mode_val" != "knockout" ]]; then
+        echo -e "${RED}Error: 'mode' must be 'round_robin', 'gauntlet', 'swiss' or 'knockout', found '$mode_val'!${NC}"
         exit 1
     fi
 
+    # Validate the knockout tie-break of tied matches
+    if [[ -n "$knockout_extra_pairs_val" ]]; then
+        if [[ ! "$knockout_extra_pairs_val" =~ ^[0-9]+$ ]]; then
+            echo -e "${RED}Error: 'knockout_extra_pairs' must be a non-negative integer, found '$knockout_extra_pairs_val'!${NC}"
+            exit 1
+        fi
+        KNOCKOUT_EXTRA_PAIRS="$knockout_extra_pairs_val"
+    fi
+    if [[ -n "$knockout_decider_val" ]]; then
+        if [[ "$knockout_decider_val" != "sudden_death" && "$knockout_decider_val" != "armageddon" ]]; then
+            echo -e "${RED}Error: 'knockout_decider' must be either 'sudden_death' or 'armageddon', found '$knockout_decider_val'!${NC}"
+            exit 1
+        fi
+        KNOCKOUT_DECIDER="$knockout_decider_val"
+    fi
+
+    # Validate the optional PGN tags (comma-separated, '-' drops a default tag), the custom tags follow them
+    local pgn_tags_clean="${pgn_tags_val// /}"
+    local pgn_tag_name="(WhiteElo|BlackElo|ECO|Opening|TimeControl|Time|UTCDate|UTCTime|PlyCount|GameDuration|Termination|Annotator)"
+    if [[ -n "$pgn_tags_clean" && ! "$pgn_tags_clean" =~ ^-?$pgn_tag_name(,-?$pgn_tag_name)*$ ]]; then
+        echo -e "${RED}Error: 'pgn_tags' must list optional tags like 'GameDuration' or '-Time', found '$pgn_tags_val'!${NC}"
+        exit 1
+    fi
+    PGN_TAGS="${pgn_tags_clean//,/;}"
+    local custom_tag
+    for custom_tag in "${PGN_CUSTOM_TAGS[@]}"; do
+        PGN_TAGS="${PGN_TAGS:+$PGN_TAGS;}$custom_tag"
+    done
+
+    # Validate Swiss tie-breaks (comma-separated, applied in order)
+    local tiebreaks_clean="${tiebreaks_val// /}"
+    if [[ -n "$tiebreaks_clean" ]]; then
+        if [[ ! "$tiebreaks_clean" =~ ^(buchholz|sonneborn_berger)(,(buchholz|sonneborn_berger))*$ ]]; then
+            echo -e "${RED}Error: 'tiebreaks' must list 'buchholz' and/or 'sonneborn_berger', found '$tiebreaks_val'!${NC}"
+            exit 1
+        fi
+        SWISS_TIEBREAKS="${tiebreaks_clean//,/, }"
+    fi
+
     # Set default concurrency
     if [[ -z "$concurrency_val" ]]; then
         concurrency_val="1"
     fi
-    if [[ ! "$concurrency_val" =~ ^[0-9]+$ || "$concurrency_val" -le 0 ]]; then
-        echo -e "${RED}Error: 'concurrency' must be a positive integer, found '$concurrency_val'!${NC}"
+    if [[ "$concurrency_val" != "auto" && ( ! "$concurrency_val" =~ ^[0-9]+$ || "$concurrency_val" -le 0 ) ]]; then
+        echo -e "${RED}Error: 'concurrency' must be a positive integer or 'auto', found '$concurrency_val'!${NC}"
+        exit 1
+    fi
+
+    # Validate the load guard of parallel games
+    if [[ -n "$max_load_val" ]]; then
+        if [[ ! "$max_load_val" =~ ^[0-9]+(\.[0-9]+)?$ ]]; then
+            echo -e "${RED}Error: 'max_load' must be a load per core like 1.5 (0: off), found '$max_load_val'!${NC}"
+            exit 1
+        fi
+        MAX_LOAD="$max_load_val"
+    fi
+    if [[ -n "$min_free_memory_val" ]]; then
+        if [[ ! "$min_free_memory_val" =~ ^[0-9]+$ || "$min_free_memory_val" -gt 100 ]]; then
+            echo -e "${RED}Error: 'min_free_memory' must be a percentage from 0 (off) to 100, found '$min_free_memory_val'!${NC}"
+            exit 1
+        fi
+        MIN_FREE_MEMORY="$min_free_memory_val"
+    fi
+
+    # Validate the resource limits of the engines: own cores, nice level and memory cap
+    local limits=()
+    if [[ -n "$cpus_per_engine_val" ]]; then
+        if [[ ! "$cpus_per_engine_val" =~ ^[0-9]+$ || "$cpus_per_engine_val" -le 0 ]]; then
+            echo -e "${RED}Error: 'cpus_per_engine' must be a positive integer, found '$cpus_per_engine_val'!${NC}"
+            exit 1
+        fi
+        local cores
+        cores=$(nproc)
+        if [[ $((2 * cpus_per_engine_val)) -gt "$cores" ]]; then
+            echo -e "${RED}Error: 'cpus_per_engine' = $cpus_per_engine_val needs $((2 * cpus_per_engine_val)) cores per game, there are $cores!${NC}"
+            exit 1
+        fi
+        if [[ "$concurrency_val" != "auto" && $((2 * cpus_per_engine_val * concurrency_val)) -gt "$cores" ]]; then
+            echo -e "${YELLOW}Warning: $concurrency_val parallel games with $cpus_per_engine_val cores per engine need $((2 * cpus_per_engine_val * concurrency_val)) cores, there are $cores. Games wait for free cores.${NC}"
+        fi
+        limits+=("cpus=$cpus_per_engine_val")
+    fi
+    if [[ -n "$nice_val" ]]; then
+        if [[ ! "$nice_val" =~ ^-?[0-9]+$ || "$nice_val" -lt -20 || "$nice_val" -gt 19 ]]; then
+            echo -e "${RED}Error: 'nice' must be an integer from -20 to 19, found '$nice_val'!${NC}"
+            exit 1
+        fi
+        limits+=("nice=$nice_val")
+    fi
+    if [[ -n "$memory_limit_val" ]]; then
+        if [[ ! "$memory_limit_val" =~ ^[0-9]+$ || "$memory_limit_val" -le 0 ]]; then
+            echo -e "${RED}Error: 'memory_limit' must be a positive number of MiB, found '$memory_limit_val'!${NC}"
+            exit 1
+        fi
+        limits+=("memory=$memory_limit_val")
+    fi
+    LIMITS=$(IFS=,; echo "${limits[*]}")
+
+    # Set default variant
+    if [[ -z "$variant_val" ]]; then
+        variant_val="standard"
+    fi
+    if [[ "$variant_val" != "standard" && "$variant_val" != "chess960" ]]; then
+        echo -e "${RED}Error: 'variant' must be either 'standard' or 'chess960', found '$variant_val'!${NC}"
+        exit 1
+    fi
+    if [[ -n "$seed_val" && ! "$seed_val" =~ ^[0-9]+$ ]]; then
+        echo -e "${RED}Error: 'chess960_seed' must be a non-negative integer, found '$seed_val'!${NC}"
+        exit 1
+    fi
+
+    # Set default option check
+    if [[ -z "$option_check_val" ]]; then
+        option_check_val="warn"
+    fi
+    if [[ "$option_check_val" != "warn" && "$option_check_val" != "strict" ]]; then
+        echo -e "${RED}Error: 'option_check' must be either 'warn' or 'strict', found '$option_check_val'!${NC}"
+        exit 1
+    fi
+    OPTION_CHECK="$option_check_val"
+
+    # Validate timeouts (comma-separated uciok/readyok/bestmove=<ms>)
+    local timeouts_clean="${timeouts_val// /}"
+    if [[ -n "$timeouts_clean" && ! "$timeouts_clean" =~ ^((uciok|readyok|bestmove)=[0-9]+)(,(uciok|readyok|bestmove)=[0-9]+)*$ ]]; then
+        echo -e "${RED}Error: 'timeouts' must look like 'uciok=10000, readyok=5000, bestmove=1000', found '$timeouts_val'!${NC}"
+        exit 1
+    fi
+    TIMEOUTS="$timeouts_clean"
+
+    # Validate persistent engines (true/false)
+    if [[ -z "$persistent_val" ]]; then
+        persistent_val="false"
+    fi
+    if [[ "$persistent_val" != "true" && "$persistent_val" != "false" ]]; then
+        echo -e "${RED}Error: 'persistent_engines' must be either 'true' or 'false', found '$persistent_val'!${NC}"
+        exit 1
+    fi
+    PERSISTENT_ENGINES="$persistent_val"
+
+    # Validate ponder (true/false)
+    if [[ -n "$ponder_val" && "$ponder_val" != "true" && "$ponder_val" != "false" ]]; then
+        echo -e "${RED}Error: 'ponder' must be either 'true' or 'false', found '$ponder_val'!${NC}"
+        exit 1
+    fi
+    if [[ "$ponder_val" == "true" ]]; then
+        PONDER_ARG="ponder"
+    fi
+
+    # Validate live_view (true/false)
+    if [[ -n "$live_view_val" && "$live_view_val" != "true" && "$live_view_val" != "false" ]]; then
+        echo -e "${RED}Error: 'live_view' must be either 'true' or 'false', found '$live_view_val'!${NC}"
+        exit 1
+    fi
+    if [[ "$live_view_val" == "true" ]]; then
+        LIVE_VIEW="true"
+    fi
+
+    # Validate the logging: level of the log file and the console, one log file per game
+    local level_pattern="^(error|warn|info|debug|uci)$"
+    if [[ -n "$log_level_val" ]]; then
+        if [[ ! "$log_level_val" =~ $level_pattern ]]; then
+            echo -e "${RED}Error: 'log_level' must be 'error', 'warn', 'info', 'debug' or 'uci', found '$log_level_val'!${NC}"
+            exit 1
+        fi
+        LOG_LEVEL="$log_level_val"
+    fi
+    if [[ -n "$console_log_val" ]]; then
+        if [[ ! "$console_log_val" =~ $level_pattern ]]; then
+            echo -e "${RED}Error: 'console_log' must be 'error', 'warn', 'info', 'debug' or 'uci', found '$console_log_val'!${NC}"
+            exit 1
+        fi
+        CONSOLE_LOG="$console_log_val"
+    fi
+    if [[ -n "$game_logs_val" && "$game_logs_val" != "true" && "$game_logs_val" != "false" ]]; then
+        echo -e "${RED}Error: 'game_logs' must be either 'true' or 'false', found '$game_logs_val'!${NC}"
         exit 1
     fi
 
@@ -572,12 +1303,41 @@ run_file_tournament() {
         echo -e "${RED}Error: At least 2 engines must be specified, found only ${#engines[@]}!${NC}"
         exit 1
     fi
+    local duplicate
+    duplicate=$(printf '%s\n' "${engines[@]}" | sort | uniq -d | head -n 1)
+    if [[ -n "$duplicate" ]]; then
+        echo -e "${RED}Error: Engine '$duplicate' is listed more than once!${NC}"
+        exit 1
+    fi
+
+    # Per-engine options must belong to a participating engine
+    for eng in "${!ENGINE_OPTIONS[@]}"; do
+        if ! printf '%s\n' "${engines[@]}" | grep -qxF "$eng"; then
+            echo -e "${RED}Error: 'engine_options.$eng' is set, but '$eng' is not listed in 'engines'!${NC}"
+            exit 1
+        fi
+    done
+
+    # Validate the engine registry, registered engines are started by their configured command
+    if [[ -n "$engines_file_val" ]]; then
+        export MM_ENGINES_FILE="$engines_file_val"
+    fi
+    local registry_error
+    if ! registry_error=$("$MM_EXEC" --list-engines 2>&1 >/dev/null); then
+        echo -e "${RED}Error: Engine registry could not be loaded: $registry_error${NC}"
+        exit 1
+    fi
+    local registered
+    registered=$(list_registry_engines)
 
-    # Validate that all engines exist and are executable in engines/ directory
+    # Validate that all other engines exist and are executable in engines/ directory
     for eng in "${engines[@]}"; do
+        if grep -qxF "$eng" <<< "$registered"; then
+            continue
+        fi
         local path="engines/$eng"
         if [[ ! -f "$path" ]]; then
-            echo -e "${RED}Error: Engine '$eng' not found at '$path'!${NC}"
+            echo -e "${RED}Error: Engine '$eng' is not registered and not found at '$path'!${NC}"
             exit 1
         fi
         if [[ ! -x "$path" ]]; then
@@ -591,13 +1351,20 @@ run_file_tournament() {
         pgn_val="${pgn_val}.pgn"
     fi
     local pgn_path="./$pgn_val"
+    if [[ "$game_logs_val" == "true" ]]; then
+        GAME_LOG_DIR="${pgn_path%.pgn}.logs"
+    fi
+    CRASH_DIR="${crash_dir_val:-${pgn_path%.pgn}.crashes}"
+    if [[ -n "$json_output_val" ]]; then
+        RESULTS_JSON="$json_output_val"
+    fi
 
     # Re-create comma separated list of clean engine names
     local engines_clean_str
     engines_clean_str=$(IFS=,; echo "${engines[*]}")
 
     # Run the tournament games
-    execute_tournament_games "$engines_clean_str" "$tc_val" "$inc_val" "$rounds_val" "$pgn_path" "$options_val" "$mode_val" "$concurrency_val"
+    execute_tournament_games "$engines_clean_str" "$tc_val" "$inc_val" "$rounds_val" "$pgn_path" "$options_val" "$mode_val" "$concurrency_val" "$variant_val" "$seed_val"
 }
 
 # Run Tournament (Interactive configuration)
@@ -689,9 +1456,9 @@ run_tournament() {
 
     # Concurrency Selection
     local concurrency_val="1"
-    read -p "Enter number of parallel games (concurrency) [Default: 1]: " concurrency_input
+    read -p "Enter number of parallel games (concurrency, 'auto' from the cores) [Default: 1]: " concurrency_input
     if [[ ! -z "$concurrency_input" ]]; then
-        if [[ "$concurrency_input" =~ ^[0-9]+$ && "$concurrency_input" -gt 0 ]]; then
+        if [[ "$concurrency_input" == "auto" || ( "$concurrency_input" =~ ^[0-9]+$ && "$concurrency_input" -gt 0 ) ]]; then
             concurrency_val="$concurrency_input"
         else
             echo -e "${YELLOW}Invalid concurrency input. Keeping default value of 1.${NC}"
@@ -786,11 +1553,19 @@ if [[ $# -gt 0 ]]; then
             echo -e "${RED}Error: Missing tournament file path! Usage: ./mm.sh -t <tournament_file.trn>${NC}"
             exit 1
         fi
+        if [[ -n "$3" ]]; then
+            if [[ "$3" != "--resume" ]]; then
+                echo -e "${RED}Unknown option: $3${NC}"
+                echo -e "Usage: ./mm.sh [-t <tournament_file.trn> [--resume]]"
+                exit 1
+            fi
+            RESUME="true"
+        fi
         run_file_tournament "$2"
         exit 0
     else
         echo -e "${RED}Unknown option: $1${NC}"
-        echo -e "Usage: ./mm.sh [-t <tournament_file.trn>]"
+        echo -e "Usage: ./mm.sh [-t <tournament_file.trn> [--resume]]"
         exit 1
     fi
 fi
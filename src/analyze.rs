@@ -0,0 +1,152 @@
+use std::error::Error;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::engine_config::EngineConfig;
+use crate::engine_process::{Awaiting, EngineProcess, Timeouts};
+use crate::log;
+use crate::model::UciGame;
+use crate::protocol::{EngineEvent, GameSetup, SearchInfo, SearchRequest};
+use crate::resource_limits::ProcessLimits;
+use crate::service::Service;
+use crate::uci_option;
+
+
+/// Clock the engine gets for the search, long enough that the manager's stop ends it.
+const ANALYSIS_CLOCK_MS: i32 = 24 * 60 * 60 * 1000;
+
+/// Time the engine has to answer the stop with its move.
+const STOP_GRACE: Duration = Duration::from_secs(2);
+
+
+/// The position to search as FEN, after the legal `moves` from `fen` (or the start position).
+pub fn position_fen(service: &Service, fen: Option<&str>, moves: &[String]) -> Result<String, String> {
+    let board = match fen {
+        Some(fen) => service.fen.set_fen(fen),
+        None => service.fen.set_init_board(),
+    };
+    let mut game = UciGame::new(board);
+    for played in moves {
+        let legal = service.move_gen.generate_valid_moves_list(&mut game.board);
+        if !legal.iter().any(|turn| turn.to_algebraic() == *played) {
+            return Err(format!("'{}' is not a legal move in {}", played, service.fen.get_fen(&game.board)));
+        }
+        game.do_move(played);
+    }
+    Ok(service.fen.get_fen(&game.board))
+}
+
+fn format_info(info: &SearchInfo, elapsed: Duration) -> String {
+    format!("{:>6} ms  depth {:>3}  score {:>7}  nps {}",
+        elapsed.as_millis(),
+        info.depth.map_or("-".to_string(), |depth| depth.to_string()),
+        info.score.as_deref().unwrap_or("-"),
+        info.nps.map_or("-".to_string(), |nps| nps.to_string()))
+}
+
+/// Lets the engine search the position for `time_ms` and prints its progress and best move.
+pub fn run_analysis(config: &EngineConfig, fen: Option<&str>, moves: &[String], time_ms: u64, logfile: &str) -> Result<(), Box<dyn Error>> {
+    if config.protocol == crate::engine_config::HUMAN_PROTOCOL {
+        return Err("MM analyze needs an engine, not a human player".into());
+    }
+    let service = Service::new();
+    let start_fen = position_fen(&service, fen, moves)?;
+    println!("Analyzing {} with {} for {} ms", start_fen, config.name, time_ms);
+
+    let (tx, rx) = mpsc::channel();
+    let mut engine = EngineProcess::start(0, config, &ProcessLimits::default(), &tx, logfile)?;
+    let setup = GameSetup { start_fen: Some(start_fen), chess960: false, time_ms: ANALYSIS_CLOCK_MS, inc_ms: 0, ponder: false };
+    let timeouts = Timeouts::default();
+    let mut search_started: Option<Instant> = None;
+    let mut stopped_at: Option<Instant> = None;
+
+    let outcome = loop {
+        if let Some(status) = engine.exit_status() {
+            break Err(format!("MM engine exited during the analysis: {:?}", status));
+        }
+        if engine.awaiting.is_overdue(&timeouts) {
+            break Err(format!("MM engine did not answer with {} in time", engine.awaiting.answer()));
+        }
+        if let Some(started) = search_started && stopped_at.is_none() && started.elapsed() >= Duration::from_millis(time_ms) {
+            let (commands, _) = engine.protocol.stop();
+            engine.send_all(commands, logfile);
+            stopped_at = Some(Instant::now());
+        }
+        if stopped_at.is_some_and(|stopped| stopped.elapsed() > STOP_GRACE) {
+            break Err("MM engine did not answer the stop with a move".to_string());
+        }
+
+        let (events, replies) = match rx.try_recv() {
+            Ok(message) => {
+                log::uci(&format!("{}\t->  mat\t\t{}", engine.id(), message), logfile);
+                engine.protocol.parse(&message[2..])
+            }
+            Err(mpsc::TryRecvError::Empty) if engine.protocol.handshake_lapsed() => (vec![EngineEvent::HandshakeDone], Vec::new()),
+            Err(mpsc::TryRecvError::Empty) => {
+                thread::sleep(Duration::from_millis(5));
+                continue;
+            }
+            Err(mpsc::TryRecvError::Disconnected) => break Err("MM lost the engine output".to_string()),
+        };
+        engine.send_all(replies, logfile);
+
+        for event in events {
+            match event {
+                EngineEvent::HandshakeDone => {
+                    // the registry options of the engine, the ones it does not accept are skipped
+                    let advertised = engine.advertised_options.clone();
+                    for (name, value) in config.options.split(',').filter_map(crate::parse_option) {
+                        match uci_option::checked_option(&advertised, &name, &value) {
+                            Ok((option, value)) => {
+                                let command = engine.protocol.set_option(option, &value);
+                                engine.send(&command, logfile);
+                            }
+                            Err(error) => eprintln!("MM engine rejected option '{}': {}", name, error),
+                        }
+                    }
+                    engine.new_game(&setup, logfile);
+                }
+                EngineEvent::OptionDeclared(option) => engine.advertised_options.push(option),
+                EngineEvent::IdName(name) => engine.id_name = name,
+                EngineEvent::Ready if search_started.is_none() => {
+                    engine.awaiting = Awaiting::Nothing;
+                    let request = SearchRequest { moves: &[], wtime: ANALYSIS_CLOCK_MS, btime: ANALYSIS_CLOCK_MS, winc: 0, binc: 0 };
+                    engine.go(&request, logfile);
+                    search_started = Some(Instant::now());
+                }
+                EngineEvent::Info(info) => {
+                    engine.info.update(info);
+                    let elapsed = search_started.map_or(Duration::ZERO, |started| started.elapsed());
+                    println!("{}", format_info(&engine.info, elapsed));
+                }
+                EngineEvent::BestMove { best_move, .. } if search_started.is_some() => {
+                    println!("bestmove {}", best_move);
+                    engine.searching = false;
+                    break;
+                }
+                _ => {}
+            }
+        }
+        if search_started.is_some() && !engine.searching {
+            break Ok(());
+        }
+    };
+
+    engine.shutdown(logfile);
+    outcome.map_err(|e| e.into())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position_fen() {
+        let service = Service::new();
+        let moves = vec!["e2e4".to_string(), "e7e5".to_string()];
+        assert_eq!(position_fen(&service, None, &moves), Ok("rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2".to_string()));
+        assert!(position_fen(&service, None, &["e2e5".to_string()]).unwrap_err().starts_with("'e2e5' is not a legal move"));
+    }
+}
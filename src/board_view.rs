@@ -0,0 +1,60 @@
+use crate::model::Board;
+
+
+/// Letter of a piece code, white upper case and black lower case.
+fn piece_char(piece: i32) -> char {
+    let letter = match piece % 10 {
+        0 => 'p',
+        1 => 'r',
+        2 => 'n',
+        3 => 'b',
+        4 => 'q',
+        5 => 'k',
+        _ => '?',
+    };
+    if piece / 10 == 1 { letter.to_ascii_uppercase() } else { letter }
+}
+
+/// Text lines of the board from white's view, empty squares are dots.
+pub fn render_board(board: &Board) -> Vec<String> {
+    let mut lines = vec!["   a b c d e f g h".to_string()];
+    for rank in 0..8 {
+        let squares: Vec<String> = (1..=8)
+            .map(|file| match board.field[(20 + rank * 10 + file) as usize] {
+                0 => ".".to_string(),
+                piece => piece_char(piece).to_string(),
+            })
+            .collect();
+        lines.push(format!("{}  {}  {}", 8 - rank, squares.join(" "), 8 - rank));
+    }
+    lines.push("   a b c d e f g h".to_string());
+    lines
+}
+
+/// Clock as `m:ss.t`, a fallen flag shows as 0:00.0.
+pub fn format_clock(ms: i32) -> String {
+    let tenths = ms.max(0) / 100;
+    format!("{}:{:02}.{}", tenths / 600, tenths / 10 % 60, tenths % 10)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::Service;
+
+    #[test]
+    fn test_render_board() {
+        let board = Service::new().fen.set_init_board();
+        let lines = render_board(&board);
+        assert_eq!(lines[1], "8  r n b q k b n r  8");
+        assert_eq!(lines[5], "4  . . . . . . . .  4");
+        assert_eq!(lines[8], "1  R N B Q K B N R  1");
+    }
+
+    #[test]
+    fn test_format_clock() {
+        assert_eq!(format_clock(61234), "1:01.2");
+        assert_eq!(format_clock(-50), "0:00.0");
+    }
+}
@@ -0,0 +1,379 @@
+use std::time::{Duration, Instant};
+
+use crate::protocol::{EngineEvent, GameSetup, PlayedMove, Protocol, SearchInfo, SearchRequest};
+use crate::uci_option::{OptionType, UciOption};
+
+
+/// Features the manager accepts, everything else is rejected. `san`, `sigint` and `sigterm`
+/// are only accepted when the engine switches them off: moves are sent in coordinate notation
+/// and the engines get no signals.
+const KNOWN_FEATURES: [&str; 22] = [
+    "ping", "setboard", "playother", "usermove", "time", "draw", "reuse", "analyze", "myname", "variants",
+    "colors", "ics", "name", "pause", "nps", "debug", "memory", "smp", "egt", "option", "exclude", "done",
+];
+/// Engines that send no `feature done=1` within this time after `protover 2` are done with the handshake,
+/// like protocol version 1 engines that know no features.
+const FEATURE_TIMEOUT: Duration = Duration::from_secs(2);
+
+
+/// State of the feature negotiation after `protover 2`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Features {
+    Expected(Instant),  // done after FEATURE_TIMEOUT without `done=1`
+    Pending,  // the engine sent `done=0` and takes its time until `done=1`
+    Done,
+}
+
+
+/// Chess Engine Communication Protocol (XBoard, protover 2): the engine keeps its own board,
+/// the manager sends the single moves and the clocks before each of them.
+pub struct CecpProtocol {
+    usermove: bool,
+    ping: bool,
+    pings_sent: u32,
+    chess960: bool,  // castling is sent as O-O
+    sent_moves: usize,  // moves the engine's board has
+    playing: bool,  // the engine plays the side to move and answers a move with its own
+    features: Features,
+    feature_timeout: Duration,
+}
+
+impl CecpProtocol {
+    pub fn new() -> Self {
+        CecpProtocol {
+            usermove: false,
+            ping: false,
+            pings_sent: 0,
+            chess960: false,
+            sent_moves: 0,
+            playing: false,
+            features: Features::Expected(Instant::now()),
+            feature_timeout: FEATURE_TIMEOUT,
+        }
+    }
+
+    fn move_command(&self, played: &PlayedMove) -> String {
+        // xboard writes chess960 castling as O-O, the king's target square is ambiguous there
+        let notation = match played.castling {
+            Some(castling) if self.chess960 => castling,
+            _ => played.uci.as_str(),
+        };
+        if self.usermove {
+            format!("usermove {}", notation)
+        } else {
+            notation.to_string()
+        }
+    }
+
+    /// `time` is the engine's clock and `otim` the opponent's, both in centiseconds.
+    fn clock_commands(request: &SearchRequest) -> [String; 2] {
+        let (own, opponent) = if request.white_to_move() { (request.wtime, request.btime) } else { (request.btime, request.wtime) };
+        [format!("time {}", own.max(0) / 10), format!("otim {}", opponent.max(0) / 10)]
+    }
+
+    /// Answers a `feature` line with accepted/rejected for every feature.
+    fn parse_features(&mut self, line: &str) -> (Vec<EngineEvent>, Vec<String>) {
+        let mut events = Vec::new();
+        let mut replies = Vec::new();
+        for (name, value) in feature_pairs(line) {
+            let accepted = match name.as_str() {
+                "san" | "sigint" | "sigterm" => value == "0",
+                known => KNOWN_FEATURES.contains(&known),
+            };
+            if accepted {
+                match name.as_str() {
+                    "usermove" => self.usermove = value == "1",
+                    "ping" => self.ping = value == "1",
+                    "myname" => events.push(EngineEvent::IdName(value.clone())),
+                    "option" => events.extend(parse_option_feature(&value).map(EngineEvent::OptionDeclared)),
+                    "done" if self.features != Features::Done => {
+                        if value == "1" {
+                            self.features = Features::Done;
+                            events.push(EngineEvent::HandshakeDone);
+                        } else {
+                            self.features = Features::Pending;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            replies.push(format!("{} {}", if accepted { "accepted" } else { "rejected" }, name));
+        }
+        (events, replies)
+    }
+}
+
+impl Protocol for CecpProtocol {
+    fn start(&mut self) -> Vec<String> {
+        self.features = Features::Expected(Instant::now());
+        vec!["xboard".to_string(), "protover 2".to_string()]
+    }
+
+    fn handshake_lapsed(&mut self) -> bool {
+        let lapsed = matches!(self.features, Features::Expected(since) if since.elapsed() > self.feature_timeout);
+        if lapsed {
+            self.features = Features::Done;
+        }
+        lapsed
+    }
+
+    fn parse(&mut self, line: &str) -> (Vec<EngineEvent>, Vec<String>) {
+        let mut tokens = line.split_whitespace();
+        let event = match (tokens.next(), tokens.next()) {
+            (Some("feature"), _) => return self.parse_features(line),
+            (Some("move"), Some(best_move)) => {
+                if self.playing {
+                    self.sent_moves += 1;
+                }
+                Some(EngineEvent::BestMove { best_move: best_move.to_string(), ponder_move: None })
+            }
+            (Some("pong"), Some(number)) if number == self.pings_sent.to_string() => Some(EngineEvent::Ready),
+            (Some("resign"), _) => Some(EngineEvent::Resign),
+            _ => parse_thinking(line).map(EngineEvent::Info),
+        };
+        (event.into_iter().collect(), Vec::new())
+    }
+
+    fn protocol_options(&self, _chess960: bool, _ponder: bool, _declared: &[UciOption]) -> Vec<(String, String)> {
+        // variant and pondering are commands in CECP, see new_game
+        Vec::new()
+    }
+
+    fn set_option(&self, option: &UciOption, value: &str) -> String {
+        match option.option_type {
+            OptionType::Button => format!("option {}", option.name),
+            OptionType::Check => format!("option {}={}", option.name, if value == "true" { 1 } else { 0 }),
+            _ => format!("option {}={}", option.name, value),
+        }
+    }
+
+    fn debug(&self) -> Vec<String> {
+        // CECP has no debug mode, the thinking output is always on
+        Vec::new()
+    }
+
+    fn new_game(&mut self, setup: &GameSetup) -> Vec<String> {
+        self.chess960 = setup.chess960;
+        self.sent_moves = 0;
+        self.playing = false;
+
+        let mut commands = vec!["new".to_string()];
+        if setup.chess960 {
+            commands.push("variant fischerandom".to_string());
+        }
+        commands.push("force".to_string());
+        if let Some(fen) = &setup.start_fen {
+            commands.push(format!("setboard {}", fen));
+        }
+        commands.push(level_command(setup.time_ms, setup.inc_ms));
+        commands.push("post".to_string());
+        commands.push(if setup.ponder { "hard" } else { "easy" }.to_string());
+        commands
+    }
+
+    fn ready_check(&mut self) -> Option<String> {
+        if !self.ping {
+            return None;
+        }
+        self.pings_sent += 1;
+        Some(format!("ping {}", self.pings_sent))
+    }
+
+    fn go(&mut self, request: &SearchRequest) -> Vec<String> {
+        let new_moves = &request.moves[self.sent_moves.min(request.moves.len())..];
+        let mut commands = Vec::new();
+        if self.playing && new_moves.len() == 1 {
+            // the engine answers the opponent's move by itself
+            commands.extend(CecpProtocol::clock_commands(request));
+            commands.push(self.move_command(&new_moves[0]));
+        } else {
+            if self.playing {
+                commands.push("force".to_string());
+            }
+            commands.extend(new_moves.iter().map(|played| self.move_command(played)));
+            commands.extend(CecpProtocol::clock_commands(request));
+            commands.push("go".to_string());
+            self.playing = true;
+        }
+        self.sent_moves = request.moves.len();
+        commands
+    }
+
+    fn ponder(&mut self, _request: &SearchRequest, _predicted: &str) -> Option<Vec<String>> {
+        // with `hard` the engine ponders by itself
+        None
+    }
+
+    fn ponderhit(&mut self) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn stop(&mut self) -> (Vec<String>, bool) {
+        // in force mode the engine stops thinking without moving
+        self.playing = false;
+        (vec!["force".to_string()], false)
+    }
+
+    fn game_over(&mut self, result: &str, termination: &str) -> Vec<String> {
+        self.playing = false;
+        vec![format!("result {} {{{}}}", result, termination)]
+    }
+
+    fn quit(&mut self) -> Vec<String> {
+        vec!["quit".to_string()]
+    }
+}
+
+
+/// `level 0 <base> <inc>`: base in minutes or minutes:seconds, increment in seconds.
+fn level_command(time_ms: i32, inc_ms: i32) -> String {
+    let seconds = time_ms.max(0) / 1000;
+    let base = if seconds % 60 == 0 { format!("{}", seconds / 60) } else { format!("{}:{:02}", seconds / 60, seconds % 60) };
+    let inc = if inc_ms % 1000 == 0 { format!("{}", inc_ms / 1000) } else { format!("{}", inc_ms as f64 / 1000.0) };
+    format!("level 0 {} {}", base, inc)
+}
+
+/// Thinking output `<ply> <score> <time> <nodes> <pv>` of a posting engine, time in centiseconds.
+fn parse_thinking(line: &str) -> Option<SearchInfo> {
+    let numbers: Vec<i64> = line.split_whitespace().take(4).map(|token| token.parse().ok()).collect::<Option<Vec<i64>>>()?;
+    let [ply, score, time, nodes] = numbers[..] else { return None };
+    Some(SearchInfo {
+        depth: u32::try_from(ply).ok(),
+        score: Some(SearchInfo::centipawns(score)),
+        nps: (time > 0).then(|| (nodes * 100 / time) as u64),
+    })
+}
+
+/// Name and value pairs of a `feature a=1 b="text with spaces"` line.
+fn feature_pairs(line: &str) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    let mut rest = line.trim_start().strip_prefix("feature").unwrap_or_default().trim_start();
+    while let Some((name, after)) = rest.split_once('=') {
+        let (value, after) = match after.strip_prefix('"') {
+            Some(quoted) => quoted.split_once('"').unwrap_or((quoted, "")),
+            None => after.split_once(char::is_whitespace).unwrap_or((after, "")),
+        };
+        pairs.push((name.trim().to_string(), value.to_string()));
+        rest = after.trim_start();
+    }
+    pairs
+}
+
+/// Converts an `option="Name -type ..."` feature to the option declaration UCI engines send.
+fn parse_option_feature(value: &str) -> Option<UciOption> {
+    let tokens: Vec<&str> = value.split_whitespace().collect();
+    let type_at = tokens.iter().position(|token| token.starts_with('-'))?;
+    let name = tokens[..type_at].join(" ");
+    let args = &tokens[type_at + 1..];
+    if name.is_empty() {
+        return None;
+    }
+    let (option_type, default) = match tokens[type_at] {
+        "-check" => (OptionType::Check, if args.first() == Some(&"1") { "true" } else { "false" }.to_string()),
+        "-spin" | "-slider" => {
+            let [default, min, max] = args else { return None };
+            (OptionType::Spin { min: min.parse().ok()?, max: max.parse().ok()? }, default.to_string())
+        }
+        "-combo" => {
+            let joined = args.join(" ");
+            let choices: Vec<&str> = joined.split("///").map(str::trim).collect();
+            let default = choices.iter().find_map(|choice| choice.strip_prefix('*')).or(choices.first().copied())?.to_string();
+            let vars = choices.iter().map(|choice| choice.trim_start_matches('*').to_string()).collect();
+            (OptionType::Combo(vars), default)
+        }
+        "-string" | "-file" | "-path" => (OptionType::String, args.join(" ")),
+        "-button" | "-save" | "-reset" => (OptionType::Button, String::new()),
+        _ => return None,
+    };
+    Some(UciOption { name, option_type, default })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn played(moves: &[&str]) -> Vec<PlayedMove> {
+        moves.iter().map(|uci| PlayedMove { uci: uci.to_string(), castling: None }).collect()
+    }
+
+    #[test]
+    fn test_feature_negotiation() {
+        let mut cecp = CecpProtocol::new();
+        let (events, replies) = cecp.parse("feature ping=1 setboard=1 san=1 usermove=1 myname=\"Suprah 0.9\" colors=0 wild=1 done=1");
+        assert_eq!(events, vec![EngineEvent::IdName("Suprah 0.9".to_string()), EngineEvent::HandshakeDone]);
+        assert_eq!(replies, vec!["accepted ping", "accepted setboard", "rejected san", "accepted usermove",
+            "accepted myname", "accepted colors", "rejected wild", "accepted done"]);
+        assert_eq!(cecp.ready_check(), Some("ping 1".to_string()));
+        assert_eq!(cecp.parse("pong 1").0, vec![EngineEvent::Ready]);
+        assert_eq!(cecp.parse("pong 0").0, vec![]);
+        assert_eq!(cecp.parse("resign").0, vec![EngineEvent::Resign]);
+        assert_eq!(cecp.parse("9 156 1084 48000 Nf3 Nc6").0,
+            vec![EngineEvent::Info(SearchInfo { depth: Some(9), score: Some("+1.56".to_string()), nps: Some(4428) })]);
+        assert_eq!(cecp.parse("feature option=\"Hash -spin 64 1 1024\" option=\"Ponder -check 0\"").0.len(), 2);
+    }
+
+    #[test]
+    fn test_handshake_without_features() {
+        let mut cecp = CecpProtocol::new();
+        cecp.feature_timeout = Duration::from_millis(20);
+        cecp.start();
+        assert!(!cecp.handshake_lapsed());
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(cecp.handshake_lapsed());
+        assert!(!cecp.handshake_lapsed());
+        assert_eq!(cecp.parse("feature done=1").0, vec![]);
+
+        // done=0 waits for done=1
+        cecp.start();
+        assert_eq!(cecp.parse("feature done=0").0, vec![]);
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(!cecp.handshake_lapsed());
+        assert_eq!(cecp.parse("feature myname=\"Slow\" done=1").0,
+            vec![EngineEvent::IdName("Slow".to_string()), EngineEvent::HandshakeDone]);
+        assert!(!cecp.handshake_lapsed());
+    }
+
+    #[test]
+    fn test_option_feature() {
+        assert_eq!(parse_option_feature("Hash Size -spin 64 1 1024"), Some(UciOption {
+            name: "Hash Size".to_string(),
+            option_type: OptionType::Spin { min: 1, max: 1024 },
+            default: "64".to_string(),
+        }));
+        assert_eq!(parse_option_feature("Style -combo Solid /// *Normal /// Risky"), Some(UciOption {
+            name: "Style".to_string(),
+            option_type: OptionType::Combo(vec!["Solid".to_string(), "Normal".to_string(), "Risky".to_string()]),
+            default: "Normal".to_string(),
+        }));
+        let own_book = parse_option_feature("OwnBook -check 1").unwrap();
+        assert_eq!(own_book.default, "true");
+        assert_eq!(CecpProtocol::new().set_option(&own_book, "false"), "option OwnBook=0");
+        assert_eq!(parse_option_feature("-spin 1 2 3"), None);
+    }
+
+    #[test]
+    fn test_level_command() {
+        assert_eq!(level_command(300000, 0), "level 0 5 0");
+        assert_eq!(level_command(90000, 2000), "level 0 1:30 2");
+        assert_eq!(level_command(3000, 100), "level 0 0:03 0.1");
+    }
+
+    #[test]
+    fn test_go_sequence() {
+        let mut cecp = CecpProtocol::new();
+        cecp.parse("feature usermove=1 done=1");
+        let setup = GameSetup { start_fen: None, chess960: false, time_ms: 60000, inc_ms: 0, ponder: false };
+        assert_eq!(cecp.new_game(&setup), vec!["new", "force", "level 0 1 0", "post", "easy"]);
+
+        // black engine: gets the first move and the clocks, then plays on by itself
+        let moves = played(&["e2e4"]);
+        let request = SearchRequest { moves: &moves, wtime: 59000, btime: 60000, winc: 0, binc: 0 };
+        assert_eq!(cecp.go(&request), vec!["usermove e2e4", "time 6000", "otim 5900", "go"]);
+        cecp.parse("move e7e5");
+        let moves = played(&["e2e4", "e7e5", "g1f3"]);
+        let request = SearchRequest { moves: &moves, wtime: 58000, btime: 59500, winc: 0, binc: 0 };
+        assert_eq!(cecp.go(&request), vec!["time 5950", "otim 5800", "usermove g1f3"]);
+        assert_eq!(cecp.game_over("1-0", "White mates"), vec!["result 1-0 {White mates}"]);
+    }
+}
@@ -0,0 +1,553 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::engine_process::Timeouts;
+use crate::live_view::LiveMode;
+use crate::log::Level;
+use crate::pgn::PgnTags;
+use crate::resource_limits::ResourceLimits;
+use crate::variant::Variant;
+
+
+pub const USAGE: &str = "\
+Matt-Magie - chess engine matchup manager
+
+Usage: Matt-Magie <command> [options]
+
+Commands:
+  match <engine_1> <engine_2>   Play games between two engines (or 'human')
+  tournament <file.trn>         Run a tournament file with mm.sh
+  report <pgn>                  Print the scoreboard of a PGN file
+  perft <depth>                 Count the legal move tree of a position
+  analyze <engine>              Let an engine search a position
+
+Run 'Matt-Magie <command> --help' for the options of a command.
+The positional form 'Matt-Magie <engine_1> <engine_2> <logfile> <pgn> <event> <site> <round>
+<time_ms> <inc_ms> [...]' used by mm.sh is still accepted.
+
+Internal commands of mm.sh:
+  --list-engines                Print the names of the registered engines
+  --dashboard <dir> [pgn]       Show the live status lines of the parallel games in <dir>
+  --swiss-pairing <state_file> <round> <engines>
+                                Print the pairing of the next Swiss round
+  --swiss-standings <state_file> <rounds> <engines> [tie_breaks]
+                                Print the Swiss standings
+  --knockout-games <state_file> <engines> <pairs> <extra_pairs> <sudden_death|armageddon>
+                                Print the knockout games to play next
+  --knockout-bracket <state_file> <engines> <pairs> <extra_pairs> <sudden_death|armageddon>
+                                Print the knockout bracket
+  --append-pgn <from_file> <to_file> [log_file]
+  --append-json <from_file> <to_file> [log_file]
+                                Append the games or JSON records of a run under the file lock
+  --auto-concurrency <cpus_per_engine> <engine> <options> [<engine> <options> ...]
+                                Print the parallel games, the cores and the threads per engine
+  --load-check <max_load_per_core> <min_free_memory_percent>
+                                Fail with the reason if the machine is too busy for another game";
+
+const MATCH_USAGE: &str = "\
+Usage: Matt-Magie match <engine_1> <engine_2> [options]
+
+Engines are registered names, paths to engine executables or 'human'.
+
+Options:
+  --time <ms>             Base time per game (default 60000)
+  --inc <ms>              Increment per move (default 0)
+  --games <n>             Games in a row with alternating colors (default 1)
+  --pgn <file>            PGN file the games are appended to (default games.pgn)
+  --log <file>            Log file (default mattmagie.log)
+  --event <name>          PGN Event tag (default Matt-Magie Match)
+  --site <name>           PGN Site tag (default local)
+  --round <n>             Round of the first game (default 1)
+  --options1 <list>       Options of engine 1, e.g. 'Hash=128,Threads=1'
+  --options2 <list>       Options of engine 2
+  --variant <variant>     standard, chess960, chess960:<index> or chess960:seed:<seed>
+  --strict-options        Abort on options the engine does not accept
+  --timeouts <list>       e.g. 'uciok=10000,readyok=5000,bestmove=1000'
+  --ponder                Let the engines think on the opponent's time
+  --live                  Redraw the board on the terminal
+  --live-dir <dir>        Write live status lines for --dashboard to <dir>
+  --unfinished <mode>     save or skip a game stopped by SIGINT/SIGTERM (default save)
+  --json <file>           Append a JSON record per game, '-' for stdout
+  --tags <list>           PGN tags, e.g. 'GameDuration;-Time;Annotator=Me'
+  --log-level <level>     error, warn, info (default), debug (also sends the engines 'debug on')
+                          or uci (every line to and from the engines)
+  --console-log <level>   Also print the log lines up to this level on stderr
+  --game-logs <dir>       Write the log of every game to its own file in <dir>
+  --crash-dir <dir>       Replay scripts of engine crashes (default <pgn>.crashes)
+  --limits <list>         Engine resources, e.g. 'cpus=1,nice=10,memory=512' (own cores, MiB)";
+
+const TOURNAMENT_USAGE: &str = "\
+Usage: Matt-Magie tournament <file.trn> [--resume]
+
+Runs the tournament file with ./mm.sh (or the script in MM_SCRIPT).
+
+Options:
+  --resume                Continue an interrupted tournament from its state file";
+
+const REPORT_USAGE: &str = "\
+Usage: Matt-Magie report <pgn> [--games] [--eco]
+
+Options:
+  --games                 List the single game results and openings before the scoreboard
+  --eco                   Break the results down by ECO family after the scoreboard";
+
+const PERFT_USAGE: &str = "\
+Usage: Matt-Magie perft <depth> [options]
+
+Options:
+  --fen <fen>             Position to count (default: start position)
+  --divide                Print the node count below every move";
+
+const ANALYZE_USAGE: &str = "\
+Usage: Matt-Magie analyze <engine> [options]
+
+Options:
+  --fen <fen>             Position to search (default: start position)
+  --moves <moves>         Moves from the position, e.g. 'e2e4 e7e5'
+  --time <ms>             Search time (default 5000)
+  --log <file>            Log file (default mattmagie.log)";
+
+
+/// A game setting of the `match` command or the positional form.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchArgs {
+    pub engines: [String; 2],
+    pub logfile: String,
+    pub pgn_path: String,
+    pub event: String,
+    pub site: String,
+    pub round: String,
+    pub time_ms: i32,
+    pub inc_ms: i32,
+    pub log_level: Level,
+    pub console_level: Option<Level>,
+    pub game_log_dir: Option<String>,
+    pub crash_dir: String,
+    pub limits: ResourceLimits,
+    pub options: [String; 2],
+    pub variant: String,
+    pub strict_options: bool,
+    pub timeouts: Timeouts,
+    pub games: u32,
+    pub ponder: bool,
+    pub live: LiveMode,
+    pub save_unfinished: bool,
+    pub json_path: Option<String>,
+    pub pgn_tags: PgnTags,
+}
+
+/// Helpers that mm.sh calls around the games of a tournament.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Internal {
+    ListEngines,
+    Dashboard { dir: String, pgn_path: Option<String> },
+    SwissPairing { state_file: String, round: String, engines: String },
+    SwissStandings { state_file: String, rounds: String, engines: String, tie_breaks: String },
+    KnockoutGames { state_file: String, engines: String, pairs: String, extra_pairs: String, decider: String },
+    KnockoutBracket { state_file: String, engines: String, pairs: String, extra_pairs: String, decider: String },
+    AppendPgn { from: String, to: String, logfile: String },
+    AppendJson { from: String, to: String, logfile: String },
+    AutoConcurrency { cpus_per_engine: usize, engines: Vec<(String, String)> },
+    LoadCheck { max_load_per_core: f64, min_free_memory_percent: f64 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Match(Box<MatchArgs>),
+    Internal(Internal),
+    Tournament { file: String, resume: bool },
+    Report { pgn_path: String, list_games: bool, by_eco: bool },
+    Perft { depth: u32, fen: Option<String>, divide: bool },
+    Analyze { engine: String, fen: Option<String>, moves: Vec<String>, time_ms: u64, logfile: String },
+    Help(&'static str),
+}
+
+
+/// Named options of a command and its remaining positional arguments.
+struct Flags {
+    values: HashMap<String, String>,
+    switches: HashSet<String>,
+    positional: Vec<String>,
+}
+
+impl Flags {
+    /// Accepts `--name value` and `--name=value` for `valued` options, `switches` take no value.
+    fn parse(args: &[String], valued: &[&str], switches: &[&str]) -> Result<Flags, String> {
+        let mut flags = Flags { values: HashMap::new(), switches: HashSet::new(), positional: Vec::new() };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let Some(option) = arg.strip_prefix("--") else {
+                flags.positional.push(arg.clone());
+                continue;
+            };
+            let (name, inline_value) = match option.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (option, None),
+            };
+            if valued.contains(&name) {
+                let value = inline_value.or_else(|| args.next().cloned()).ok_or_else(|| format!("option --{} needs a value", name))?;
+                flags.values.insert(name.to_string(), value);
+            } else if switches.contains(&name) && inline_value.is_none() {
+                flags.switches.insert(name.to_string());
+            } else {
+                return Err(format!("unknown option '{}'", arg));
+            }
+        }
+        Ok(flags)
+    }
+
+    fn value(&self, name: &str, default: &str) -> String {
+        self.values.get(name).cloned().unwrap_or_else(|| default.to_string())
+    }
+
+    fn number<T: std::str::FromStr>(&self, name: &str, default: T) -> Result<T, String> {
+        match self.values.get(name) {
+            Some(value) => value.trim().parse().map_err(|_| format!("--{} must be a number, found '{}'", name, value)),
+            None => Ok(default),
+        }
+    }
+
+    fn switch(&self, name: &str) -> bool {
+        self.switches.contains(name)
+    }
+
+    /// The positional arguments, exactly as many as `names` lists.
+    fn positional(&self, names: &[&str]) -> Result<Vec<String>, String> {
+        if self.positional.len() < names.len() {
+            return Err(format!("missing <{}>", names[self.positional.len()]));
+        }
+        if self.positional.len() > names.len() {
+            return Err(format!("unexpected argument '{}'", self.positional[names.len()]));
+        }
+        Ok(self.positional.clone())
+    }
+}
+
+
+/// The command of the arguments after the program name.
+pub fn parse(args: &[String]) -> Result<Command, String> {
+    let Some(first) = args.first() else {
+        return Ok(Command::Help(USAGE));
+    };
+    let rest = &args[1..];
+    let usage = match first.as_str() {
+        "help" | "--help" | "-h" => return Ok(Command::Help(USAGE)),
+        "match" => MATCH_USAGE,
+        "tournament" => TOURNAMENT_USAGE,
+        "report" => REPORT_USAGE,
+        "perft" => PERFT_USAGE,
+        "analyze" => ANALYZE_USAGE,
+        internal if internal.starts_with("--") => return Internal::parse(internal, rest).map(Command::Internal),
+        _ => return MatchArgs::from_positional(args).map(|match_args| Command::Match(Box::new(match_args))),
+    };
+    if rest.iter().any(|arg| arg == "--help" || arg == "-h") {
+        return Ok(Command::Help(usage));
+    }
+    match first.as_str() {
+        "match" => MatchArgs::from_flags(rest).map(|match_args| Command::Match(Box::new(match_args))),
+        "tournament" => {
+            let flags = Flags::parse(rest, &[], &["resume"])?;
+            let [file] = <[String; 1]>::try_from(flags.positional(&["file.trn"])?).expect("one positional argument");
+            Ok(Command::Tournament { file, resume: flags.switch("resume") })
+        }
+        "report" => {
+            let flags = Flags::parse(rest, &[], &["games", "eco"])?;
+            let [pgn_path] = <[String; 1]>::try_from(flags.positional(&["pgn"])?).expect("one positional argument");
+            Ok(Command::Report { pgn_path, list_games: flags.switch("games"), by_eco: flags.switch("eco") })
+        }
+        "perft" => {
+            let flags = Flags::parse(rest, &["fen"], &["divide"])?;
+            let [depth] = <[String; 1]>::try_from(flags.positional(&["depth"])?).expect("one positional argument");
+            let depth = depth.parse().ok().filter(|depth| *depth > 0).ok_or_else(|| format!("<depth> must be a positive number, found '{}'", depth))?;
+            Ok(Command::Perft { depth, fen: flags.values.get("fen").cloned(), divide: flags.switch("divide") })
+        }
+        _ => {
+            let flags = Flags::parse(rest, &["fen", "moves", "time", "log"], &[])?;
+            let [engine] = <[String; 1]>::try_from(flags.positional(&["engine"])?).expect("one positional argument");
+            Ok(Command::Analyze {
+                engine,
+                fen: flags.values.get("fen").cloned(),
+                moves: flags.value("moves", "").split_whitespace().map(str::to_string).collect(),
+                time_ms: flags.number("time", 5000)?,
+                logfile: flags.value("log", "mattmagie.log"),
+            })
+        }
+    }
+}
+
+/// Usage of the command whose arguments failed to parse.
+pub fn usage_of(args: &[String]) -> &'static str {
+    match args.first().map(String::as_str) {
+        Some("match") => MATCH_USAGE,
+        Some("tournament") => TOURNAMENT_USAGE,
+        Some("report") => REPORT_USAGE,
+        Some("perft") => PERFT_USAGE,
+        Some("analyze") => ANALYZE_USAGE,
+        _ => USAGE,
+    }
+}
+
+
+impl Internal {
+    /// The arguments of the internal command `name`, they are all positional.
+    fn parse(name: &str, args: &[String]) -> Result<Internal, String> {
+        let arity = |required: &[&str], optional: usize| {
+            if let Some(missing) = required.get(args.len()) {
+                return Err(format!("{} needs <{}>", name, missing));
+            }
+            match args.get(required.len() + optional) {
+                Some(unexpected) => Err(format!("unexpected argument '{}' of {}", unexpected, name)),
+                None => Ok(()),
+            }
+        };
+        let arg = |at: usize| args.get(at).cloned().unwrap_or_default();
+        let internal = match name {
+            "--list-engines" => {
+                arity(&[], 0)?;
+                Internal::ListEngines
+            }
+            "--dashboard" => {
+                arity(&["dir"], 1)?;
+                Internal::Dashboard { dir: arg(0), pgn_path: args.get(1).cloned() }
+            }
+            "--swiss-pairing" => {
+                arity(&["state_file", "round", "engines"], 0)?;
+                Internal::SwissPairing { state_file: arg(0), round: arg(1), engines: arg(2) }
+            }
+            "--swiss-standings" => {
+                arity(&["state_file", "rounds", "engines"], 1)?;
+                Internal::SwissStandings { state_file: arg(0), rounds: arg(1), engines: arg(2), tie_breaks: arg(3) }
+            }
+            "--knockout-games" | "--knockout-bracket" => {
+                arity(&["state_file", "engines", "pairs", "extra_pairs", "sudden_death|armageddon"], 0)?;
+                let (state_file, engines, pairs, extra_pairs, decider) = (arg(0), arg(1), arg(2), arg(3), arg(4));
+                if name == "--knockout-games" {
+                    Internal::KnockoutGames { state_file, engines, pairs, extra_pairs, decider }
+                } else {
+                    Internal::KnockoutBracket { state_file, engines, pairs, extra_pairs, decider }
+                }
+            }
+            "--append-pgn" | "--append-json" => {
+                arity(&["from_file", "to_file"], 1)?;
+                let (from, to, logfile) = (arg(0), arg(1), args.get(2).cloned().unwrap_or_else(|| "mattmagie.log".to_string()));
+                if name == "--append-pgn" { Internal::AppendPgn { from, to, logfile } } else { Internal::AppendJson { from, to, logfile } }
+            }
+            "--auto-concurrency" => {
+                // any number of further engines with their options
+                arity(&["cpus_per_engine", "engine", "options"], args.len().saturating_sub(3))?;
+                if args.len().is_multiple_of(2) {
+                    return Err(format!("{} needs <options> after <{}>", name, args[args.len() - 1]));
+                }
+                // an empty value leaves the threads to the engine options
+                let cpus_per_engine = match args[0].trim() {
+                    "" => 0,
+                    cpus => cpus.parse().map_err(|_| format!("<cpus_per_engine> must be a number, found '{}'", cpus))?,
+                };
+                let engines = args[1..].chunks(2).map(|engine| (engine[0].clone(), engine[1].clone())).collect();
+                Internal::AutoConcurrency { cpus_per_engine, engines }
+            }
+            "--load-check" => {
+                arity(&["max_load_per_core", "min_free_memory_percent"], 0)?;
+                let limit = |at: usize, name: &str| args[at].trim().parse::<f64>()
+                    .map_err(|_| format!("<{}> must be a number, found '{}'", name, args[at]));
+                Internal::LoadCheck { max_load_per_core: limit(0, "max_load_per_core")?, min_free_memory_percent: limit(1, "min_free_memory_percent")? }
+            }
+            _ => return Err(format!("unknown command '{}'", name)),
+        };
+        Ok(internal)
+    }
+}
+
+
+impl MatchArgs {
+    /// `<engine_1> <engine_2> <logfile> <pgn> <event> <site> <round> <time_ms> <inc_ms> [log_level] [debug_on]
+    /// [options_1] [options_2] [variant] [option_check] [timeouts] [games] [ponder] [live] [unfinished] [json]` of
+    /// older mm.sh versions. Newer settings are only options of the `match` command.
+    pub fn from_positional(args: &[String]) -> Result<MatchArgs, String> {
+        const REQUIRED: [&str; 9] = ["engine_1", "engine_2", "logfile", "pgn", "event", "site", "round", "time_ms", "inc_ms"];
+        const ARGS: usize = 21;
+        if let Some(missing) = REQUIRED.get(args.len()) {
+            return Err(format!("missing <{}> (argument {})", missing, args.len() + 1));
+        }
+        if let Some(unexpected) = args.get(ARGS) {
+            return Err(format!("unexpected argument '{}' (argument {}), use the options of the match command", unexpected, ARGS + 1));
+        }
+        let arg = |at: usize| args.get(at).map(|arg| arg.trim()).unwrap_or_default();
+        let number = |at: usize| arg(at).parse::<i32>().map_err(|_| format!("<{}> must be a number of ms, found '{}'", REQUIRED[at], arg(at)));
+        let games = match arg(16) {
+            "" => 1,
+            games => games.parse().ok().filter(|games| *games > 0).ok_or("games arg must be a positive number")?,
+        };
+        let match_args = MatchArgs {
+            engines: [args[0].clone(), args[1].clone()],
+            logfile: args[2].clone(),
+            pgn_path: args[3].clone(),
+            event: args[4].clone(),
+            site: args[5].clone(),
+            round: args[6].clone(),
+            time_ms: number(7)?,
+            inc_ms: number(8)?,
+            // `log_on` of older mm.sh versions logs everything, `debug_on` raises the level to at least debug
+            log_level: match (log_level_arg(arg(9), "log level arg")?, arg(10)) {
+                (level, "debug_on") => level.max(Level::Debug),
+                (level, _) => level,
+            },
+            console_level: None,
+            game_log_dir: None,
+            crash_dir: default_crash_dir(&args[3]),
+            limits: ResourceLimits::default(),
+            options: [args.get(11).cloned().unwrap_or_default(), args.get(12).cloned().unwrap_or_default()],
+            variant: arg(13).to_string(),
+            // unknown or invalid options are skipped with a warning, in strict mode they abort the game
+            strict_options: arg(14) == "strict",
+            timeouts: Timeouts::from_arg(arg(15)).map_err(|e| format!("can not parse timeouts arg: {}", e))?,
+            // the engines play this many games in a row with alternating colors, their processes are kept
+            games,
+            // engines that declare the Ponder option think on the opponent's time
+            ponder: arg(17) == "ponder",
+            // `live` redraws the board on the terminal, `live:<dir>` writes a status line for --dashboard
+            live: LiveMode::from_arg(arg(18)).ok_or("can not parse live arg")?,
+            // a game stopped by SIGINT or SIGTERM is saved with result `*` unless it is skipped
+            save_unfinished: unfinished_arg(arg(19))?,
+            // every saved game is also appended as one JSON line to this file, `-` is stdout
+            json_path: Some(arg(20).to_string()).filter(|path| !path.is_empty()),
+            pgn_tags: PgnTags::default(),
+        };
+        match_args.validate()
+    }
+
+    /// `<engine_1> <engine_2>` and the named options of the `match` command.
+    pub fn from_flags(args: &[String]) -> Result<MatchArgs, String> {
+        let flags = Flags::parse(args,
+            &["time", "inc", "games", "pgn", "log", "event", "site", "round", "options1", "options2",
+                "variant", "timeouts", "live-dir", "unfinished", "json", "tags",
+                "log-level", "console-log", "game-logs", "crash-dir", "limits"],
+            &["strict-options", "ponder", "live"])?;
+        let [engine_1, engine_2] = <[String; 2]>::try_from(flags.positional(&["engine_1", "engine_2"])?).expect("two positional arguments");
+        let live = match (flags.switch("live"), flags.values.get("live-dir")) {
+            (true, Some(_)) => return Err("--live and --live-dir can not be combined".to_string()),
+            (true, None) => LiveMode::Board,
+            (false, Some(dir)) => LiveMode::from_arg(&format!("live:{}", dir)).ok_or("--live-dir needs a directory")?,
+            (false, None) => LiveMode::Off,
+        };
+        let match_args = MatchArgs {
+            engines: [engine_1, engine_2],
+            logfile: flags.value("log", "mattmagie.log"),
+            pgn_path: flags.value("pgn", "games.pgn"),
+            event: flags.value("event", "Matt-Magie Match"),
+            site: flags.value("site", "local"),
+            round: flags.value("round", "1"),
+            time_ms: flags.number("time", 60000)?,
+            inc_ms: flags.number("inc", 0)?,
+            log_level: log_level_arg(&flags.value("log-level", "info"), "--log-level")?,
+            console_level: flags.values.get("console-log").map(|arg| log_level_arg(arg, "--console-log")).transpose()?,
+            game_log_dir: flags.values.get("game-logs").cloned(),
+            crash_dir: flags.values.get("crash-dir").cloned().unwrap_or_else(|| default_crash_dir(&flags.value("pgn", "games.pgn"))),
+            limits: ResourceLimits::from_arg(&flags.value("limits", "")).map_err(|e| format!("--limits: {}", e))?,
+            options: [flags.value("options1", ""), flags.value("options2", "")],
+            variant: flags.value("variant", "standard"),
+            strict_options: flags.switch("strict-options"),
+            timeouts: Timeouts::from_arg(&flags.value("timeouts", "")).map_err(|e| format!("--timeouts: {}", e))?,
+            games: flags.number("games", 1)?,
+            ponder: flags.switch("ponder"),
+            live,
+            save_unfinished: unfinished_arg(&flags.value("unfinished", "save"))?,
+            json_path: flags.values.get("json").cloned(),
+            pgn_tags: PgnTags::parse(&flags.value("tags", "")).map_err(|e| format!("--tags: {}", e))?,
+        };
+        match_args.validate()
+    }
+
+    fn validate(self) -> Result<MatchArgs, String> {
+        if Variant::from_arg(&self.variant).is_none() {
+            return Err(format!("unknown variant '{}'", self.variant));
+        }
+        if self.time_ms < 0 || self.inc_ms < 0 {
+            return Err(format!("time and increment must not be negative, found {} + {} ms", self.time_ms, self.inc_ms));
+        }
+        if self.games == 0 {
+            return Err("games must be a positive number".to_string());
+        }
+        Ok(self)
+    }
+}
+
+fn log_level_arg(arg: &str, name: &str) -> Result<Level, String> {
+    match arg {
+        "" | "log_off" => Ok(Level::Info),
+        _ => Level::from_arg(arg).ok_or(format!("{} must be error, warn, info, debug or uci, found '{}'", name, arg)),
+    }
+}
+
+/// `games.pgn` keeps its crash scripts in `games.crashes`.
+fn default_crash_dir(pgn_path: &str) -> String {
+    format!("{}.crashes", pgn_path.strip_suffix(".pgn").unwrap_or(pgn_path))
+}
+
+fn unfinished_arg(arg: &str) -> Result<bool, String> {
+    match arg {
+        "" | "save" => Ok(true),
+        "skip" => Ok(false),
+        other => Err(format!("unfinished games arg must be 'save' or 'skip', found '{}'", other)),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_positional_and_flags_agree() {
+        let positional = parse(&strings(&["a", "b", "mattmagie.log", "games.pgn", "Matt-Magie Match", "local", "1", "30000", "100",
+            "log_on", "debug_on", "Hash=16", "", "chess960:seed:7", "", "", "2", "ponder", "", "", "out.ndjson"])).unwrap();
+        let flags = parse(&strings(&["match", "a", "b", "--time", "30000", "--inc=100", "--options1", "Hash=16",
+            "--variant", "chess960:seed:7", "--games", "2", "--ponder", "--json", "out.ndjson", "--log-level", "uci"])).unwrap();
+        assert_eq!(positional, flags);
+        let Command::Match(match_args) = flags else { panic!("match expected") };
+        assert_eq!(match_args.games, 2);
+        assert!(match_args.save_unfinished);
+        assert_eq!(match_args.crash_dir, "games.crashes");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse(&strings(&["a", "b", "log", "pgn"])), Err("missing <event> (argument 5)".to_string()));
+        assert_eq!(parse(&strings(&["a", "b", "log", "pgn", "e", "s", "1", "fast", "0"])),
+            Err("<time_ms> must be a number of ms, found 'fast'".to_string()));
+        let mut positional = strings(&["a", "b", "log", "pgn", "e", "s", "1", "1000", "0"]);
+        positional.resize(21, String::new());
+        positional.push("PlyCount".to_string());
+        assert_eq!(parse(&positional), Err("unexpected argument 'PlyCount' (argument 22), use the options of the match command".to_string()));
+        assert_eq!(parse(&strings(&["match", "a"])), Err("missing <engine_2>".to_string()));
+        assert_eq!(parse(&strings(&["match", "a", "b", "--speed", "1"])), Err("unknown option '--speed'".to_string()));
+        assert_eq!(parse(&strings(&["match", "a", "b", "--time"])), Err("option --time needs a value".to_string()));
+        assert_eq!(parse(&strings(&["match", "a", "b", "--log-level", "all"])),
+            Err("--log-level must be error, warn, info, debug or uci, found 'all'".to_string()));
+        assert_eq!(parse(&strings(&["perft", "0"])), Err("<depth> must be a positive number, found '0'".to_string()));
+        assert_eq!(parse(&strings(&["report", "--help"])), Ok(Command::Help(REPORT_USAGE)));
+        assert_eq!(parse(&strings(&["tournament", "t.trn", "--resume"])), Ok(Command::Tournament { file: "t.trn".to_string(), resume: true }));
+    }
+
+    #[test]
+    fn test_internal_commands() {
+        assert_eq!(parse(&strings(&["--list-engines"])), Ok(Command::Internal(Internal::ListEngines)));
+        assert_eq!(parse(&strings(&["--swiss-standings", "t.state", "3", "a,b"])), Ok(Command::Internal(Internal::SwissStandings {
+            state_file: "t.state".to_string(), rounds: "3".to_string(), engines: "a,b".to_string(), tie_breaks: String::new() })));
+        assert_eq!(parse(&strings(&["--append-pgn", "run.pgn", "games.pgn"])), Ok(Command::Internal(Internal::AppendPgn {
+            from: "run.pgn".to_string(), to: "games.pgn".to_string(), logfile: "mattmagie.log".to_string() })));
+        assert_eq!(parse(&strings(&["--auto-concurrency", "", "a", "Threads=2", "b", ""])), Ok(Command::Internal(Internal::AutoConcurrency {
+            cpus_per_engine: 0, engines: vec![("a".to_string(), "Threads=2".to_string()), ("b".to_string(), String::new())] })));
+        assert_eq!(parse(&strings(&["--load-check", "1.5", "10"])),
+            Ok(Command::Internal(Internal::LoadCheck { max_load_per_core: 1.5, min_free_memory_percent: 10.0 })));
+
+        assert_eq!(parse(&strings(&["--knockout-games", "t.state", "a,b", "1", "0"])), Err("--knockout-games needs <sudden_death|armageddon>".to_string()));
+        assert_eq!(parse(&strings(&["--list-engines", "x"])), Err("unexpected argument 'x' of --list-engines".to_string()));
+        assert_eq!(parse(&strings(&["--auto-concurrency", "1", "a", "", "b"])), Err("--auto-concurrency needs <options> after <b>".to_string()));
+        assert_eq!(parse(&strings(&["--load-check", "high", "10"])), Err("<max_load_per_core> must be a number, found 'high'".to_string()));
+        assert_eq!(parse(&strings(&["--swiss", "t.state"])), Err("unknown command '--swiss'".to_string()));
+    }
+}
@@ -0,0 +1,98 @@
+use std::error::Error;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+
+#[derive(PartialEq)]
+pub enum TimeControl {
+    WhiteToMove,
+    BlackToMove,
+    AllStop,
+}
+
+
+/// Game clock, a thread counts down the time of the side to move every 10 ms.
+/// The increment is added when the side gets the move.
+pub struct Clock {
+    pub tx: mpsc::Sender<TimeControl>,
+    time_white: Arc<Mutex<i32>>,
+    time_black: Arc<Mutex<i32>>,
+    increments: Arc<Mutex<(i32, i32)>>,
+}
+
+impl Clock {
+    pub fn start() -> Result<Clock, Box<dyn Error>> {
+        let (tx_clock, rx_clock) = mpsc::channel::<TimeControl>();
+        let time_white = Arc::new(Mutex::new(0));
+        let time_black = Arc::new(Mutex::new(0));
+        let increments = Arc::new(Mutex::new((0, 0)));
+
+        let time_white_clone = Arc::clone(&time_white);
+        let time_black_clone = Arc::clone(&time_black);
+        let increments_clone = Arc::clone(&increments);
+
+        thread::Builder::new().name("Time_Control".to_string()).spawn(move || {
+
+            let mut to_move = TimeControl::AllStop;
+
+            loop {
+                match rx_clock.try_recv() {
+                    Ok(message) => {
+                        let (inc_white, inc_black) = *increments_clone.lock().expect("MM could not unlock increments");
+                        match message {
+                            TimeControl::WhiteToMove => {
+                                *time_white_clone.lock().expect("MM could not unlock time_white") += inc_white;
+                                to_move = TimeControl::WhiteToMove;
+                            },
+                            TimeControl::BlackToMove => {
+                                *time_black_clone.lock().expect("MM could not unlock time_white") += inc_black;
+                                to_move = TimeControl::BlackToMove;
+                            },
+                            TimeControl::AllStop => {
+                                to_move = TimeControl::AllStop;
+                            },
+
+                        }
+                    },
+                    Err(mpsc::TryRecvError::Empty) => {
+                        // do nothing proceed...
+                    },
+                    Err(mpsc::TryRecvError::Disconnected) => {
+                        // the manager is done
+                        return;
+                    }
+                }
+                if to_move == TimeControl::WhiteToMove {
+                    let mut wtime = time_white_clone.lock().expect("MM could not unlock time_white");
+                    *wtime -= 10;
+                } else if to_move == TimeControl::BlackToMove {
+                    let mut btime = time_black_clone.lock().expect("MM could not unlock time_white");
+                    *btime -= 10;
+                }
+                thread::sleep(std::time::Duration::from_millis(10));
+            }
+        })?;
+
+        Ok(Clock { tx: tx_clock, time_white, time_black, increments })
+    }
+
+    /// Sets the clocks for a new game, the clock must be stopped.
+    pub fn reset(&self, time_white: i32, time_black: i32, inc_white: i32, inc_black: i32) {
+        *self.time_white.lock().expect("MM could not unlock time_white") = time_white;
+        *self.time_black.lock().expect("MM could not unlock time_black") = time_black;
+        *self.increments.lock().expect("MM could not unlock increments") = (inc_white, inc_black);
+    }
+
+    /// Remaining time of white and black in ms.
+    pub fn remaining(&self) -> (i32, i32) {
+        (
+            *self.time_white.lock().expect("MM could not unlock time_white (remaining_time)"),
+            *self.time_black.lock().expect("MM could not unlock time_white (remaining_time)"),
+        )
+    }
+
+    pub fn increments(&self) -> (i32, i32) {
+        *self.increments.lock().expect("MM could not unlock increments")
+    }
+}
@@ -0,0 +1,106 @@
+use std::sync::OnceLock;
+
+
+/// ECO code, opening name and moves in coordinate notation, one opening per line after the header.
+const BUNDLED_TABLE: &str = include_str!("../eco.tsv");
+
+/// The five ECO volumes, named by the first letter of the code.
+pub const FAMILIES: [(char, &str); 5] = [
+    ('A', "Flank openings"),
+    ('B', "Semi-open games"),
+    ('C', "Open games and French"),
+    ('D', "Closed and semi-closed games"),
+    ('E', "Indian defences"),
+];
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Opening {
+    pub eco: String,
+    pub name: String,
+    pub moves: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct EcoTable {
+    openings: Vec<Opening>,
+}
+
+impl EcoTable {
+    /// Reads a TSV table with the columns `eco`, `name` and `moves`, the first line is the header.
+    pub fn parse(tsv: &str) -> Result<EcoTable, String> {
+        let mut openings = Vec::new();
+        for (number, line) in tsv.lines().enumerate().skip(1) {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let columns: Vec<&str> = line.split('\t').collect();
+            let [eco, name, moves] = columns[..] else {
+                return Err(format!("line {}: expected eco, name and moves, found '{}'", number + 1, line));
+            };
+            if family_of(eco) == '?' || eco.len() != 3 || !eco[1..].chars().all(|c| c.is_ascii_digit()) {
+                return Err(format!("line {}: '{}' is no ECO code", number + 1, eco));
+            }
+            openings.push(Opening {
+                eco: eco.to_string(),
+                name: name.to_string(),
+                moves: moves.split_whitespace().map(str::to_string).collect(),
+            });
+        }
+        Ok(EcoTable { openings })
+    }
+
+    /// The table compiled into Matt-Magie.
+    pub fn bundled() -> &'static EcoTable {
+        static TABLE: OnceLock<EcoTable> = OnceLock::new();
+        TABLE.get_or_init(|| EcoTable::parse(BUNDLED_TABLE).expect("MM bundled ECO table is invalid"))
+    }
+
+    /// The opening with the longest move sequence that the game started with.
+    pub fn classify(&self, moves: &[String]) -> Option<&Opening> {
+        self.openings.iter()
+            .filter(|opening| opening.moves.len() <= moves.len() && opening.moves[..] == moves[..opening.moves.len()])
+            .max_by_key(|opening| opening.moves.len())
+    }
+}
+
+/// The ECO volume letter of `eco`, e.g. `B` for the Sicilian, `?` for codes outside A00-E99.
+pub fn family_of(eco: &str) -> char {
+    eco.chars().next().filter(|letter| ('A'..='E').contains(letter)).unwrap_or('?')
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::UciGame;
+    use crate::service::Service;
+
+    fn moves(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_bundled_table_is_legal() {
+        let service = Service::new();
+        for opening in &EcoTable::bundled().openings {
+            let mut game = UciGame::new(service.fen.set_init_board());
+            for played in &opening.moves {
+                let legal = service.move_gen.generate_valid_moves_list(&mut game.board);
+                assert!(legal.iter().any(|turn| turn.to_algebraic() == *played), "{} {}: illegal move {}", opening.eco, opening.name, played);
+                game.do_move(played);
+            }
+        }
+    }
+
+    #[test]
+    fn test_classify() {
+        let table = EcoTable::bundled();
+        let najdorf = table.classify(&moves("e2e4 c7c5 g1f3 d7d6 d2d4 c5d4 f3d4 g8f6 b1c3 a7a6 c1e3 e7e5")).unwrap();
+        assert_eq!((najdorf.eco.as_str(), najdorf.name.as_str()), ("B90", "Sicilian Defense: Najdorf Variation"));
+        assert_eq!(table.classify(&moves("e2e4 e7e5 g1f3 b8c6 f1b5 a7a6")).unwrap().eco, "C60");
+        assert_eq!(table.classify(&moves("a2a3 a7a6")).unwrap().name, "Anderssen's Opening");
+        assert_eq!(table.classify(&[]), None);
+        assert!(EcoTable::parse("eco\tname\tmoves\nF00\tUnknown\te2e4\n").unwrap_err().contains("'F00' is no ECO code"));
+    }
+}
@@ -0,0 +1,216 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use serde::Deserialize;
+
+
+const DEFAULT_REGISTRY_FILE: &str = "engines.toml";
+
+/// Engine argument that lets a human play at the terminal.
+pub const HUMAN_PLAYER: &str = "human";
+pub const HUMAN_PROTOCOL: &str = "human";
+
+
+/// One engine entry of the registry file, addressed by its name.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EngineConfig {
+    pub name: String,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub working_dir: Option<String>,
+    #[serde(default)]
+    pub options: String,  // comma-separated like engine_options, e.g. "Hash=128, Threads=1"
+    #[serde(default = "default_protocol")]
+    pub protocol: String,
+    #[serde(default)]
+    pub time_control: Option<i32>,  // ms per game, overrides the match time control for this engine
+    #[serde(default)]
+    pub increment: Option<i32>,  // ms per move
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    #[serde(default)]
+    pub elo: Option<u32>,  // rating written to the WhiteElo and BlackElo tags
+    #[serde(skip)]
+    pub registered: bool,  // false for plain executables given by path
+}
+
+fn default_protocol() -> String {
+    "uci".to_string()
+}
+
+impl EngineConfig {
+    /// Config of an engine that is not in the registry, the argument is the path of the executable.
+    pub fn from_path(path: &str) -> Self {
+        EngineConfig {
+            name: path.to_string(),
+            command: path.to_string(),
+            args: Vec::new(),
+            working_dir: None,
+            options: String::new(),
+            protocol: default_protocol(),
+            time_control: None,
+            increment: None,
+            env: BTreeMap::new(),
+            elo: None,
+            registered: false,
+        }
+    }
+
+    /// The human at the terminal, named "Human" in the PGN.
+    pub fn human() -> Self {
+        EngineConfig {
+            name: "Human".to_string(),
+            command: String::new(),
+            protocol: HUMAN_PROTOCOL.to_string(),
+            registered: true,
+            ..EngineConfig::from_path("")
+        }
+    }
+
+    /// Builds the process command. Relative executable paths are resolved from the Matt-Magie directory,
+    /// not from the engine's working directory.
+    pub fn command(&self) -> Command {
+        let program = if self.command.contains('/') {
+            fs::canonicalize(&self.command).map(|path| path.display().to_string()).unwrap_or_else(|_| self.command.clone())
+        } else {
+            self.command.clone()
+        };
+        let mut command = Command::new(program);
+        command.args(&self.args).envs(&self.env);
+        if let Some(dir) = &self.working_dir {
+            command.current_dir(dir);
+        }
+        command
+    }
+}
+
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RegistryFile {
+    #[serde(default)]
+    engine: Vec<EngineConfig>,
+}
+
+pub struct EngineRegistry {
+    engines: Vec<EngineConfig>,
+}
+
+impl EngineRegistry {
+    /// Loads the registry from $MM_ENGINES_FILE or ./engines.toml, a missing default file is an empty registry.
+    pub fn load_default() -> Result<Self, Box<dyn Error>> {
+        match env::var("MM_ENGINES_FILE") {
+            Ok(path) if !path.is_empty() => EngineRegistry::load(&path),
+            _ if Path::new(DEFAULT_REGISTRY_FILE).exists() => EngineRegistry::load(DEFAULT_REGISTRY_FILE),
+            _ => Ok(EngineRegistry { engines: Vec::new() }),
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let content = fs::read_to_string(path).map_err(|e| format!("MM can not read engine registry {}: {}", path, e))?;
+        EngineRegistry::parse(&content).map_err(|e| format!("MM invalid engine registry {}: {}", path, e).into())
+    }
+
+    pub fn parse(content: &str) -> Result<Self, Box<dyn Error>> {
+        let file: RegistryFile = toml::from_str(content)?;
+        let mut engines: Vec<EngineConfig> = Vec::with_capacity(file.engine.len());
+        for mut engine in file.engine {
+            if engine.name.is_empty() || engine.name.contains(|c: char| c.is_whitespace() || c == ',') {
+                return Err(format!("engine name '{}' must not be empty or contain whitespace or commas", engine.name).into());
+            }
+            if engines.iter().any(|e| e.name == engine.name) {
+                return Err(format!("engine name '{}' is defined twice", engine.name).into());
+            }
+            if !["uci", "xboard", "cecp"].contains(&engine.protocol.as_str()) {
+                return Err(format!("engine '{}' uses unknown protocol '{}'", engine.name, engine.protocol).into());
+            }
+            engine.registered = true;
+            engines.push(engine);
+        }
+        Ok(EngineRegistry { engines })
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.engines.iter().map(|engine| engine.name.as_str()).collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&EngineConfig> {
+        self.engines.iter().find(|engine| engine.name == name)
+    }
+
+    /// A registered name resolves to its entry, `human` to the terminal player and anything else
+    /// is taken as path of an executable.
+    pub fn resolve(&self, name_or_path: &str) -> EngineConfig {
+        match self.get(name_or_path) {
+            Some(config) => config.clone(),
+            None if name_or_path == HUMAN_PLAYER => EngineConfig::human(),
+            None => EngineConfig::from_path(name_or_path),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REGISTRY: &str = r#"
+        [[engine]]
+        name = "suprah-dev"
+        command = "engines/suprah"
+        args = ["--uci"]
+        working_dir = "engines"
+        options = "Hash=128, Threads=1"
+        time_control = 10000
+        increment = 100
+        elo = 2650
+        env = { RUST_BACKTRACE = "1" }
+
+        [[engine]]
+        name = "suprah-hash16"
+        command = "engines/suprah"
+        options = "Hash=16"
+    "#;
+
+    #[test]
+    fn test_parse_registry() {
+        let registry = EngineRegistry::parse(REGISTRY).unwrap();
+        assert_eq!(registry.names(), vec!["suprah-dev", "suprah-hash16"]);
+
+        let dev = registry.get("suprah-dev").unwrap();
+        assert!(dev.registered);
+        assert_eq!(dev.args, vec!["--uci"]);
+        assert_eq!(dev.protocol, "uci");
+        assert_eq!(dev.time_control, Some(10000));
+        assert_eq!(dev.elo, Some(2650));
+        assert_eq!(dev.env.get("RUST_BACKTRACE").map(String::as_str), Some("1"));
+
+        let hash16 = registry.get("suprah-hash16").unwrap();
+        assert_eq!(hash16.time_control, None);
+        assert_eq!(hash16.elo, None);
+        assert!(hash16.working_dir.is_none());
+    }
+
+    #[test]
+    fn test_resolve_unregistered_path() {
+        let registry = EngineRegistry::parse(REGISTRY).unwrap();
+        let engine = registry.resolve("engines/suprah-0.9.3");
+        assert!(!engine.registered);
+        assert_eq!(engine.command, "engines/suprah-0.9.3");
+    }
+
+    #[test]
+    fn test_invalid_registry() {
+        assert!(EngineRegistry::parse("[[engine]]\nname = \"a b\"\ncommand = \"x\"").is_err());
+        assert!(EngineRegistry::parse("[[engine]]\nname = \"a\"\ncommand = \"x\"\n[[engine]]\nname = \"a\"\ncommand = \"y\"").is_err());
+        assert!(EngineRegistry::parse("[[engine]]\nname = \"a\"\ncommand = \"x\"\nprotocol = \"smoke\"").is_err());
+        assert!(EngineRegistry::parse("[[engine]]\nname = \"a\"\ncommand = \"x\"\nhash = 1").is_err());
+    }
+}
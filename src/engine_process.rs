@@ -0,0 +1,579 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::path::Path;
+use std::process::{Child, ExitStatus, Stdio};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::engine_config::{EngineConfig, HUMAN_PROTOCOL};
+use crate::log;
+use crate::protocol::{self, GameSetup, Protocol, SearchInfo, SearchRequest};
+use crate::resource_limits::{self, ProcessLimits};
+use crate::uci_option::UciOption;
+
+
+/// Time an engine gets to answer before it counts as stalled, in ms.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timeouts {
+    pub uciok: u64,
+    pub readyok: u64,
+    pub bestmove: u64,  // after the engine's clock ran out
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Timeouts { uciok: 10000, readyok: 5000, bestmove: 1000 }
+    }
+}
+
+impl Timeouts {
+    /// Parses `uciok=10000,readyok=5000,bestmove=1000`, missing keys keep their default.
+    pub fn from_arg(arg: &str) -> Result<Timeouts, String> {
+        let mut timeouts = Timeouts::default();
+        for part in arg.split(',').filter(|part| !part.trim().is_empty()) {
+            let (key, value) = part.split_once('=').ok_or_else(|| format!("timeout '{}' is not key=ms", part.trim()))?;
+            let value = value.trim().parse::<u64>().map_err(|_| format!("timeout '{}' is not a number of ms", part.trim()))?;
+            match key.trim() {
+                "uciok" => timeouts.uciok = value,
+                "readyok" => timeouts.readyok = value,
+                "bestmove" => timeouts.bestmove = value,
+                other => return Err(format!("unknown timeout '{}'", other)),
+            }
+        }
+        Ok(timeouts)
+    }
+}
+
+
+/// Answer the manager waits for from an engine.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Awaiting {
+    Nothing,
+    UciOk(Instant),
+    ReadyOk(Instant),
+}
+
+impl Awaiting {
+    pub fn answer(&self) -> &'static str {
+        match self {
+            Awaiting::Nothing => "nothing",
+            Awaiting::UciOk(_) => "uciok",
+            Awaiting::ReadyOk(_) => "readyok",
+        }
+    }
+
+    pub fn is_overdue(&self, timeouts: &Timeouts) -> bool {
+        match self {
+            Awaiting::Nothing => false,
+            Awaiting::UciOk(since) => since.elapsed() > Duration::from_millis(timeouts.uciok),
+            Awaiting::ReadyOk(since) => since.elapsed() > Duration::from_millis(timeouts.readyok),
+        }
+    }
+}
+
+
+/// Last lines of an engine's stderr, read on an own thread that also logs them.
+struct StderrCapture {
+    tail: Arc<Mutex<VecDeque<String>>>,
+    reader: JoinHandle<()>,
+}
+
+/// An engine that exited during a game, with its last stderr lines (e.g. a panic message) and
+/// the last lines it exchanged with Matt-Magie (`> ` sent, `< ` received).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EngineCrash {
+    pub engine: String,
+    pub pid: u32,
+    pub status: String,
+    pub exit_code: Option<i32>,
+    pub signal: Option<String>,  // e.g. "SIGSEGV"
+    pub fen: String,  // position of the game at the crash
+    pub stderr: Vec<String>,
+    pub exchange: Vec<String>,
+    pub repro: Option<String>,  // path of the replay script
+    pub memory_limit_exceeded: bool,
+}
+
+impl EngineCrash {
+    /// One line for a PGN comment, with the last `lines` lines of stderr.
+    pub fn summary(&self, lines: usize) -> String {
+        let mut summary = format!("{} (pid {}) exited with {}", self.engine, self.pid, self.status);
+        if self.memory_limit_exceeded {
+            summary.push_str(" after exceeding its memory limit");
+        }
+        if !self.stderr.is_empty() {
+            let tail = &self.stderr[self.stderr.len().saturating_sub(lines)..];
+            summary.push_str(&format!("; stderr: {}", tail.join(" | ")));
+        }
+        if let Some(repro) = &self.repro {
+            summary.push_str(&format!("; repro: {}", repro));
+        }
+        summary
+    }
+}
+
+
+/// A running engine. Its output lines are forwarded to the manager prefixed with "<index>_",
+/// the process can be kept for several games and is restarted after a crash or stall.
+pub struct EngineProcess {
+    pub index: usize,
+    pub config: EngineConfig,
+    pub child: Option<Child>,  // None for a human player
+    pub protocol: Box<dyn Protocol>,
+    pub awaiting: Awaiting,
+    pub advertised_options: Vec<UciOption>,
+    pub id_name: String,
+    pub searching: bool,  // a go was sent and its bestmove is outstanding
+    pub ponder: bool,  // pondering is on and the engine declares the Ponder option (UCI)
+    pub pondering: Option<String>,  // predicted opponent move of a running go ponder
+    pub stale_bestmoves: u32,  // answers of stopped searches that are still to come
+    pub info: SearchInfo,  // latest search progress of the game
+    stderr: Option<StderrCapture>,
+    exchange: VecDeque<String>,  // last EXCHANGE_LINES lines sent and received
+    setup: Vec<String>,  // commands before the first game: handshake, options, debug
+    game_start: Vec<String>,  // commands of the latest new game
+    last_search: Vec<String>,  // latest `position` with its `go` and `ponderhit`
+    in_game: bool,
+    limits: ProcessLimits,
+    peak_memory: u64,  // bytes of address space, sampled while a memory limit is set
+    memory_sampled: Instant,
+}
+
+impl EngineProcess {
+    /// Spawns the engine with its reader thread and starts the protocol handshake.
+    /// A human player has no process, the terminal's lines are forwarded instead.
+    pub fn start(index: usize, config: &EngineConfig, limits: &ProcessLimits, tx: &mpsc::Sender<String>, logfile: &str) -> Result<EngineProcess, Box<dyn Error>> {
+        let protocol = protocol::from_name(&config.protocol).ok_or_else(|| format!("MM unknown protocol '{}'", config.protocol))?;
+        let (child, stderr) = if config.protocol == HUMAN_PROTOCOL {
+            log::info(&format!("loaded eng{}: {} (terminal)", index, config.name), logfile);
+            forward_lines(index, BufReader::new(std::io::stdin()), tx)?;
+            (None, None)
+        } else {
+            // an own process group keeps Ctrl-C away from the engine, Matt-Magie quits it
+            let mut command = config.command();
+            limits.apply(&mut command);
+            let mut child = command
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .process_group(0)
+                .spawn()?;
+            log::info(&format!("loaded eng{} {}: {} ({})", index, child.id(), config.name, config.command), logfile);
+            let stdout = child.stdout.take().ok_or("MM Failed to retrieve stdout")?;
+            forward_lines(index, BufReader::new(stdout), tx)?;
+            let stderr = child.stderr.take().ok_or("MM Failed to retrieve stderr")?;
+            let stderr = capture_stderr(format!("{} {}", config.name, child.id()), stderr, logfile)?;
+            (Some(child), Some(stderr))
+        };
+
+        let mut engine = EngineProcess {
+            index,
+            config: config.clone(),
+            child,
+            protocol,
+            awaiting: Awaiting::Nothing,
+            advertised_options: Vec::new(),
+            id_name: String::new(),
+            searching: false,
+            ponder: false,
+            pondering: None,
+            stale_bestmoves: 0,
+            info: SearchInfo::default(),
+            stderr,
+            exchange: VecDeque::new(),
+            setup: Vec::new(),
+            game_start: Vec::new(),
+            last_search: Vec::new(),
+            in_game: false,
+            limits: limits.clone(),
+            peak_memory: 0,
+            memory_sampled: Instant::now(),
+        };
+        if !engine.is_human() {
+            let commands = engine.protocol.start();
+            engine.send_all(commands, logfile);
+            engine.awaiting = Awaiting::UciOk(Instant::now());
+        }
+        Ok(engine)
+    }
+
+    pub fn is_human(&self) -> bool {
+        self.child.is_none()
+    }
+
+    /// Process id, 0 for a human player.
+    pub fn id(&self) -> u32 {
+        self.child.as_ref().map_or(0, Child::id)
+    }
+
+    pub fn exit_status(&mut self) -> Option<ExitStatus> {
+        self.child.as_mut()?.try_wait().ok().flatten()
+    }
+
+    /// The crash of the engine that exited with `status` in the position `fen`. Its stderr is read to
+    /// the end first, unless the stderr pipe is still held open, e.g. by a child process of the engine.
+    pub fn crash(&self, status: ExitStatus, fen: String) -> EngineCrash {
+        let stderr = match &self.stderr {
+            Some(capture) => {
+                let start = Instant::now();
+                while !capture.reader.is_finished() && start.elapsed() < STDERR_GRACE {
+                    thread::sleep(Duration::from_millis(5));
+                }
+                capture.tail.lock().map(|tail| tail.iter().cloned().collect()).unwrap_or_default()
+            }
+            None => Vec::new(),
+        };
+        let memory_limit_exceeded = self.limits.is_memory_breach(self.peak_memory, &stderr);
+        EngineCrash {
+            engine: self.config.name.clone(),
+            pid: self.id(),
+            status: status.to_string(),
+            exit_code: status.code(),
+            signal: status.signal().map(signal_name),
+            fen,
+            stderr,
+            exchange: self.exchange.iter().cloned().collect(),
+            repro: None,
+            memory_limit_exceeded,
+        }
+    }
+
+    /// Notes the address space the engine uses, at most every MEMORY_SAMPLE_INTERVAL and only under a memory limit.
+    pub fn sample_memory(&mut self) {
+        if self.limits.memory_bytes.is_none() || self.memory_sampled.elapsed() < MEMORY_SAMPLE_INTERVAL {
+            return;
+        }
+        self.memory_sampled = Instant::now();
+        self.peak_memory = self.peak_memory.max(resource_limits::peak_memory(self.id()));
+    }
+
+    /// Shell script that starts the engine like Matt-Magie did and sends it the setup, the new game and
+    /// the latest search, to replay `crash` against the engine directly.
+    pub fn repro_script(&self, crash: &EngineCrash) -> String {
+        let command = self.config.command();
+        let mut script = format!("#!/bin/sh\n# Replays the crash of {} (pid {}): {}\n# FEN at the crash: {}\n",
+            crash.engine, crash.pid, crash.status, crash.fen);
+        if let Some(dir) = command.get_current_dir() {
+            script.push_str(&format!("cd {} || exit 1\n", shell_quote(&dir.to_string_lossy())));
+        }
+        let lines: Vec<String> = self.setup.iter().chain(&self.game_start).chain(&self.last_search).map(|line| shell_quote(line)).collect();
+        script.push_str(&format!("{{\n  printf '%s\\n' \\\n    {}\n  sleep {}\n}} | ", lines.join(" \\\n    "), REPRO_WAIT.as_secs()));
+        let envs: Vec<String> = command.get_envs()
+            .filter_map(|(key, value)| Some(shell_quote(&format!("{}={}", key.to_string_lossy(), value?.to_string_lossy()))))
+            .collect();
+        if !envs.is_empty() {
+            script.push_str(&format!("env {} ", envs.join(" ")));
+        }
+        let program = std::iter::once(command.get_program()).chain(command.get_args()).map(|arg| shell_quote(&arg.to_string_lossy()));
+        script.push_str(&program.collect::<Vec<String>>().join(" "));
+        script.push('\n');
+        script
+    }
+
+    /// Writes the `repro_script` of `crash` to `dir`, named after the round, the engine and its pid.
+    pub fn save_repro(&self, crash: &EngineCrash, dir: &str, round: &str) -> io::Result<String> {
+        fs::create_dir_all(dir)?;
+        let path = Path::new(dir).join(format!("{}_{}_{}.sh", log::file_name_part(round), log::file_name_part(&crash.engine), crash.pid));
+        fs::write(&path, self.repro_script(crash))?;
+        fs::set_permissions(&path, std::os::unix::fs::PermissionsExt::from_mode(0o755))?;
+        Ok(path.to_string_lossy().to_string())
+    }
+
+    /// Notes a line the engine sent, for the crash report.
+    pub fn received(&mut self, line: &str) {
+        self.record_exchange(format!("< {}", line));
+    }
+
+    fn record_exchange(&mut self, line: String) {
+        if self.exchange.len() == EXCHANGE_LINES {
+            self.exchange.pop_front();
+        }
+        self.exchange.push_back(line);
+    }
+
+    /// Keeps the commands a replay of the engine's current search needs.
+    fn record_sent(&mut self, command: &str) {
+        self.record_exchange(format!("> {}", command));
+        if !self.in_game {
+            self.setup.push(command.to_string());
+        } else if command.starts_with("position ") {
+            self.last_search = vec![command.to_string()];
+        } else if command.starts_with("go") || command == "ponderhit" {
+            self.last_search.push(command.to_string());
+        }
+    }
+
+    pub fn send(&mut self, command: &str, logfile: &str) {
+        if self.exit_status().is_some() {
+            log::warn(&format!("mat\t-x  {}\t{} (engine exited)", self.id(), command), logfile);
+            return;
+        }
+        let Some(child) = self.child.as_mut() else {
+            return;
+        };
+        let command_with_newline = format!("{}\n", command);
+        let stdin = child.stdin.as_mut().expect("Failed");
+        stdin.write_all(command_with_newline.as_bytes())
+            .unwrap_or_else(|err| {
+                eprintln!("Failed to write to stdin Command ->: {} - {}", command, err);
+            });
+        stdin.flush().unwrap();
+        log::uci(&format!("mat\t->  {}\t{}", self.id(), command), logfile);
+        self.record_sent(command);
+    }
+
+    pub fn send_all(&mut self, commands: Vec<String>, logfile: &str) {
+        for command in commands {
+            self.send(&command, logfile);
+        }
+    }
+
+    /// Prepares the engine for a new game, it is ready when it answered the ready check.
+    pub fn new_game(&mut self, setup: &GameSetup, logfile: &str) {
+        let commands = self.protocol.new_game(setup);
+        self.in_game = true;
+        self.game_start = commands.clone();
+        self.last_search.clear();
+        self.send_all(commands, logfile);
+        self.info = SearchInfo::default();
+        self.awaiting = match self.protocol.ready_check() {
+            Some(command) => {
+                self.game_start.push(command.clone());
+                self.send(&command, logfile);
+                Awaiting::ReadyOk(Instant::now())
+            }
+            None => Awaiting::Nothing,
+        };
+    }
+
+    pub fn go(&mut self, request: &SearchRequest, logfile: &str) {
+        let commands = self.protocol.go(request);
+        self.send_all(commands, logfile);
+        self.searching = true;
+    }
+
+    pub fn quit(&mut self, logfile: &str) {
+        let commands = self.protocol.quit();
+        self.send_all(commands, logfile);
+    }
+
+    /// Stops the search, quits the engine and terminates it if it does not exit within QUIT_GRACE.
+    pub fn shutdown(&mut self, logfile: &str) {
+        self.stop_search(logfile);
+        self.quit(logfile);
+        let start = Instant::now();
+        while self.child.is_some() && self.exit_status().is_none() {
+            if start.elapsed() > QUIT_GRACE {
+                log::warn(&format!("Engine {} did not quit, it is terminated", self.index), logfile);
+                self.terminate();
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    pub fn terminate(&mut self) {
+        if let Some(child) = self.child.as_mut() {
+            terminate(child, KILL_GRACE);
+        }
+        self.awaiting = Awaiting::Nothing;
+        self.searching = false;
+        self.pondering = None;
+    }
+
+    /// Stops a running search or ponder search, its bestmove is ignored.
+    pub fn stop_search(&mut self, logfile: &str) {
+        if self.searching || self.pondering.is_some() {
+            let (commands, answered) = self.protocol.stop();
+            self.send_all(commands, logfile);
+            if answered {
+                self.stale_bestmoves += 1;
+            }
+            self.searching = false;
+            self.pondering = None;
+        }
+    }
+}
+
+
+/// Forwards the lines of an engine's stdout (or the terminal) to the manager, prefixed with "<index>_".
+fn forward_lines(index: usize, reader: impl BufRead + Send + 'static, tx: &mpsc::Sender<String>) -> Result<(), Box<dyn Error>> {
+    let tx = mpsc::Sender::clone(tx);
+    thread::Builder::new().name(format!("Thread {}", index)).spawn(move || {
+        for line in reader.lines() {
+            let line = format!("{}_{}", index, line.expect("MM read engine std input failed"));
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    })?;
+    Ok(())
+}
+
+/// Logs the stderr lines of an engine prefixed with its name and pid and keeps the last STDERR_TAIL_LINES.
+fn capture_stderr(prefix: String, stderr: impl Read + Send + 'static, logfile: &str) -> Result<StderrCapture, Box<dyn Error>> {
+    let tail = Arc::new(Mutex::new(VecDeque::new()));
+    let lines = Arc::clone(&tail);
+    let logfile = logfile.to_string();
+    let reader = thread::Builder::new().name(format!("{} stderr", prefix)).spawn(move || {
+        for line in BufReader::new(stderr).split(b'\n') {
+            let Ok(line) = line else {
+                break;
+            };
+            let line = String::from_utf8_lossy(&line).trim_end().to_string();
+            log::info(&format!("{}\tstderr\t{}", prefix, line), &logfile);
+            if let Ok(mut lines) = lines.lock() {
+                if lines.len() == STDERR_TAIL_LINES {
+                    lines.pop_front();
+                }
+                lines.push_back(line);
+            }
+        }
+    })?;
+    Ok(StderrCapture { tail, reader })
+}
+
+/// `arg` in single quotes for sh.
+fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+/// Name of a signal that ended an engine, e.g. `SIGSEGV`.
+pub fn signal_name(signal: i32) -> String {
+    let name = match signal {
+        libc::SIGHUP => "SIGHUP",
+        libc::SIGINT => "SIGINT",
+        libc::SIGQUIT => "SIGQUIT",
+        libc::SIGILL => "SIGILL",
+        libc::SIGTRAP => "SIGTRAP",
+        libc::SIGABRT => "SIGABRT",
+        libc::SIGBUS => "SIGBUS",
+        libc::SIGFPE => "SIGFPE",
+        libc::SIGKILL => "SIGKILL",
+        libc::SIGSEGV => "SIGSEGV",
+        libc::SIGPIPE => "SIGPIPE",
+        libc::SIGALRM => "SIGALRM",
+        libc::SIGTERM => "SIGTERM",
+        libc::SIGXCPU => "SIGXCPU",
+        libc::SIGXFSZ => "SIGXFSZ",
+        _ => return format!("signal {}", signal),
+    };
+    name.to_string()
+}
+
+
+/// Time between two looks at the memory of an engine under a memory limit.
+const MEMORY_SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+/// Lines sent to and received from an engine kept for its crash report.
+const EXCHANGE_LINES: usize = 20;
+/// Seconds the replay script keeps the engine's stdin open, so it can finish its search.
+const REPRO_WAIT: Duration = Duration::from_secs(10);
+/// Stderr lines of an engine kept for its crash report.
+const STDERR_TAIL_LINES: usize = 20;
+/// Time the stderr of an exited engine gets to be read to the end.
+const STDERR_GRACE: Duration = Duration::from_millis(200);
+/// Time a stalled engine gets to exit after SIGTERM before it is killed.
+const KILL_GRACE: Duration = Duration::from_millis(500);
+/// Time an engine gets to exit after `quit`.
+const QUIT_GRACE: Duration = Duration::from_millis(1000);
+
+/// Stops an engine with SIGTERM and kills it with SIGKILL if it is still alive after the grace period.
+pub fn terminate(engine: &mut Child, grace: Duration) {
+    if let Ok(Some(_)) = engine.try_wait() {
+        return;
+    }
+    // SAFETY: kill only sends a signal to the engine's pid, which is not reaped before try_wait
+    unsafe {
+        libc::kill(engine.id() as libc::pid_t, libc::SIGTERM);
+    }
+    let start = Instant::now();
+    while start.elapsed() < grace {
+        if let Ok(Some(_)) = engine.try_wait() {
+            return;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    let _ = engine.kill();
+    let _ = engine.wait();
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    #[test]
+    fn test_timeouts_from_arg() {
+        assert_eq!(Timeouts::from_arg(""), Ok(Timeouts::default()));
+        assert_eq!(Timeouts::from_arg("uciok=2000, bestmove=0"), Ok(Timeouts { uciok: 2000, readyok: 5000, bestmove: 0 }));
+        assert!(Timeouts::from_arg("go=100").is_err());
+        assert!(Timeouts::from_arg("uciok=soon").is_err());
+    }
+
+    #[test]
+    fn test_terminate_ignoring_sigterm() {
+        let mut child = Command::new("sh").arg("-c").arg("trap '' TERM; sleep 10").spawn().unwrap();
+        thread::sleep(Duration::from_millis(100));
+        let start = Instant::now();
+        terminate(&mut child, Duration::from_millis(200));
+        assert!(child.try_wait().unwrap().is_some());
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_stderr_tail() {
+        let logfile = std::env::temp_dir().join(format!("mm_{}_stderr.log", std::process::id())).to_string_lossy().to_string();
+        let mut child = Command::new("sh").arg("-c").arg("for i in $(seq 1 25); do echo \"line $i\" >&2; done; exit 101")
+            .stderr(Stdio::piped()).spawn().unwrap();
+        let capture = capture_stderr("mock 1".to_string(), child.stderr.take().unwrap(), &logfile).unwrap();
+        assert!(!child.wait().unwrap().success());
+        capture.reader.join().unwrap();
+        let tail: Vec<String> = capture.tail.lock().unwrap().iter().cloned().collect();
+        assert_eq!((tail.len(), tail[0].as_str(), tail[19].as_str()), (20, "line 6", "line 25"));
+        log::flush();
+        assert!(std::fs::read_to_string(&logfile).unwrap().contains("mock 1\tstderr\tline 1\n"));
+        std::fs::remove_file(&logfile).unwrap();
+
+        let crash = EngineCrash {
+            engine: "mock".to_string(), pid: 7, status: "exit status: 101".to_string(), exit_code: Some(101), signal: None,
+            fen: String::new(), stderr: tail, exchange: Vec::new(), repro: None, memory_limit_exceeded: false,
+        };
+        assert_eq!(crash.summary(2), "mock (pid 7) exited with exit status: 101; stderr: line 24 | line 25");
+    }
+
+    #[test]
+    fn test_repro_script() {
+        let logfile = std::env::temp_dir().join(format!("mm_{}_repro.log", std::process::id())).to_string_lossy().to_string();
+        let mut config = EngineConfig::from_path("cat");
+        config.env.insert("MODE".to_string(), "it's".to_string());
+        let (tx, _rx) = mpsc::channel();
+        let mut engine = EngineProcess::start(0, &config, &ProcessLimits::default(), &tx, &logfile).unwrap();
+        engine.send("setoption name Hash value 16", &logfile);
+        engine.new_game(&GameSetup { start_fen: None, chess960: false, time_ms: 1000, inc_ms: 0, ponder: false }, &logfile);
+        let request = SearchRequest { moves: &[], wtime: 1000, btime: 1000, winc: 0, binc: 0 };
+        engine.go(&request, &logfile);
+        engine.go(&request, &logfile);
+        engine.received("info depth 1");
+        engine.terminate();
+        let status = engine.exit_status().unwrap();
+        let crash = engine.crash(status, "8/8/8/8/8/8/8/K6k w - - 0 1".to_string());
+        assert_eq!((crash.exit_code, crash.signal.as_deref()), (None, Some("SIGTERM")));
+        assert_eq!(crash.exchange.first().map(String::as_str), Some("> uci"));
+        assert_eq!(crash.exchange.last().map(String::as_str), Some("< info depth 1"));
+
+        let script = engine.repro_script(&crash);
+        assert!(script.contains("# FEN at the crash: 8/8/8/8/8/8/8/K6k w - - 0 1\n"));
+        assert!(script.contains("printf '%s\\n' \\\n    'uci' \\\n    'setoption name Hash value 16' \\\n    'ucinewgame' \\\n    'isready' \\\n    \
+            'position startpos' \\\n    'go wtime 1000 winc 0 btime 1000 binc 0'\n  sleep 10\n}"), "{}", script);
+        assert!(script.ends_with("} | env 'MODE=it'\\''s' 'cat'\n"));
+        log::flush();
+        let _ = std::fs::remove_file(&logfile);
+    }
+}
@@ -24,7 +24,7 @@ impl FenService {
         for c in board_part.chars() {
             if c == '/' {
                 index += 2; // Move to the next row
-            } else if c.is_digit(10) {
+            } else if c.is_ascii_digit() {
                 index += c.to_digit(10).unwrap() as usize; // Skip empty squares
             } else {
                 let piece = match c {
@@ -52,11 +52,31 @@ impl FenService {
         // Process whose turn it is
         let white_to_move = turn_part == "w";
 
-        // Process castling possibilities
-        let white_possible_to_castle_short = castling_part.contains('K');
-        let white_possible_to_castle_long = castling_part.contains('Q');
-        let black_possible_to_castle_short = castling_part.contains('k');
-        let black_possible_to_castle_long = castling_part.contains('q');
+        // Process castling possibilities, KQkq as well as X-FEN/Shredder-FEN rook files
+        let mut castle_rook_squares = [91, 98, 21, 28];
+        let mut castle_rights = [false; 4];
+        for c in castling_part.chars() {
+            let white = c.is_ascii_uppercase();
+            let rank = if white { 90 } else { 20 };
+            let (king, rook) = if white { (15, 11) } else { (25, 21) };
+            let Some(king_square) = (rank + 1..=rank + 8).find(|&square| field[square] == king) else {
+                continue;
+            };
+            let rook_square = match c.to_ascii_uppercase() {
+                'K' => (king_square + 1..=rank + 8).rev().find(|&square| field[square] == rook),
+                'Q' => (rank + 1..king_square).find(|&square| field[square] == rook),
+                file @ 'A'..='H' => Some(rank + 1 + (file as usize - 'A' as usize))
+                    .filter(|&square| square != king_square && field[square] == rook),
+                _ => None,
+            };
+            if let Some(rook_square) = rook_square {
+                let index = if white { 0 } else { 2 } + if rook_square > king_square { 1 } else { 0 };
+                castle_rook_squares[index] = rook_square as i32;
+                castle_rights[index] = true;
+            }
+        }
+        let [white_possible_to_castle_long, white_possible_to_castle_short,
+            black_possible_to_castle_long, black_possible_to_castle_short] = castle_rights;
 
         // Process en passant possibility
         let mut line_for_en_passante = -1;
@@ -67,7 +87,7 @@ impl FenService {
         // Process move number
         let move_number = move_number_part.parse::<i32>().unwrap_or(1);
 
-        Board::new(
+        let mut board = Board::new(
             field,
             white_possible_to_castle_long,
             white_possible_to_castle_short,
@@ -77,20 +97,59 @@ impl FenService {
             white_to_move,
             move_number,
             ZobristTable::new(),
-        )
+        );
+        board.castle_rook_squares = castle_rook_squares;
+        board
     }
 
     pub fn set_init_board(&self) -> Board {
         self.set_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")
     }
 
+    /// Sets up the Chess960 start position with the given index (0..960), castling as king-takes-rook.
+    pub fn set_chess960_board(&self, index: u32) -> Board {
+        let mut board = self.set_fen(&self.get_chess960_fen(index));
+        board.chess960 = true;
+        board
+    }
+
+    /// Generates the X-FEN of a Chess960 start position by its Scharnagl index, 518 is the standard position.
+    pub fn get_chess960_fen(&self, index: u32) -> String {
+        let mut rank: [Option<char>; 8] = [None; 8];
+        let mut n = index as usize % 960;
+
+        rank[n % 4 * 2 + 1] = Some('b'); // light squared bishop on b, d, f or h
+        n /= 4;
+        rank[n % 4 * 2] = Some('b'); // dark squared bishop on a, c, e or g
+        n /= 4;
+        self.place_on_free_square(&mut rank, n % 6, 'q');
+        n /= 6;
+
+        let knights = [(0, 1), (0, 2), (0, 3), (0, 4), (1, 2), (1, 3), (1, 4), (2, 3), (2, 4), (3, 4)][n];
+        // the second knight is placed first, so the free square index of the first stays valid
+        self.place_on_free_square(&mut rank, knights.1, 'n');
+        self.place_on_free_square(&mut rank, knights.0, 'n');
+        for piece in ['r', 'k', 'r'] {
+            self.place_on_free_square(&mut rank, 0, piece);
+        }
+
+        let black: String = rank.iter().map(|piece| piece.unwrap_or(' ')).collect();
+        format!("{}/pppppppp/8/8/8/8/PPPPPPPP/{} w KQkq - 0 1", black, black.to_ascii_uppercase())
+    }
+
+    fn place_on_free_square(&self, rank: &mut [Option<char>; 8], free_index: usize, piece: char) {
+        if let Some(square) = rank.iter_mut().filter(|square| square.is_none()).nth(free_index) {
+            *square = Some(piece);
+        }
+    }
+
     /// Clears the board by initializing all positions to -11 (out of bounds) or 0 (empty squares).
     fn clear_field(&self, field: &mut [i32; 120]) {
-        for i in 0..field.len() {
-            if i < 21 || i > 98 || i % 10 == 0 || i % 10 == 9 {
-                field[i] = -11; // Set border squares to -11
+        for (i, square) in field.iter_mut().enumerate() {
+            if !(21..=98).contains(&i) || i % 10 == 0 || i % 10 == 9 {
+                *square = -11; // Set border squares to -11
             } else {
-                field[i] = 0; // Set empty squares to 0
+                *square = 0; // Set empty squares to 0
             }
         }
     }
@@ -150,16 +209,16 @@ impl FenService {
         fen.push(' ');
         let mut castling_rights = String::new();
         if board.white_possible_to_castle_short {
-            castling_rights.push('K');
+            castling_rights.push(self.get_castling_char(board, 1, 'K'));
         }
         if board.white_possible_to_castle_long {
-            castling_rights.push('Q');
+            castling_rights.push(self.get_castling_char(board, 0, 'Q'));
         }
         if board.black_possible_to_castle_short {
-            castling_rights.push('k');
+            castling_rights.push(self.get_castling_char(board, 3, 'k'));
         }
         if board.black_possible_to_castle_long {
-            castling_rights.push('q');
+            castling_rights.push(self.get_castling_char(board, 2, 'q'));
         }
         if castling_rights.is_empty() {
             castling_rights.push('-');
@@ -181,9 +240,26 @@ impl FenService {
         fen
     }
 
+    /// X-FEN castling char: KQkq for the outermost rook, otherwise the file of the castling rook.
+    fn get_castling_char(&self, board: &Board, index: usize, symbol: char) -> char {
+        let rook_square = board.castle_rook_squares[index];
+        let rook = if index < 2 { 11 } else { 21 };
+        let outer_squares = if index % 2 == 1 {
+            rook_square + 1..=rook_square / 10 * 10 + 8
+        } else {
+            rook_square / 10 * 10 + 1..=rook_square - 1
+        };
+        if outer_squares.into_iter().any(|square| board.field[square as usize] == rook) {
+            let file = (b'a' + (rook_square % 10 - 1) as u8) as char;
+            if index < 2 { file.to_ascii_uppercase() } else { file }
+        } else {
+            symbol
+        }
+    }
+
     /// Converts a board index to a notation field (e.g., 34 -> "d6").
     pub fn get_notation_from_index(&self, index: i32) -> String {
-        if index < 21 || index > 98 || index % 10 == 0 || index % 10 == 9 {
+        if !(21..=98).contains(&index) || index % 10 == 0 || index % 10 == 9 {
             return String::from("-"); // Invalid index
         }
 
@@ -208,24 +284,71 @@ mod tests {
         let fen_service = Service::new().fen;
 
         let test_fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
-        let board = fen_service.set_fen(&test_fen);
+        let board = fen_service.set_fen(test_fen);
         let result_fen = fen_service.get_fen(&board);
         assert_eq!(test_fen, result_fen);
 
         let test_fen = "rnbq1rk1/pp2n1bp/2pppp2/6p1/3P4/2PBPP1P/PP1NN1PB/R2QK2R b KQ - 0 10";
-        let board = fen_service.set_fen(&test_fen);
+        let board = fen_service.set_fen(test_fen);
         let result_fen = fen_service.get_fen(&board);
         assert_eq!(test_fen, result_fen);
 
         let test_fen = "rnbqkbnr/ppp1pp1p/6p1/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3";
-        let board = fen_service.set_fen(&test_fen);
+        let board = fen_service.set_fen(test_fen);
         let result_fen = fen_service.get_fen(&board);
         assert_eq!(test_fen, result_fen);
 
         let test_fen = "rnbqk1nr/pp2ppbp/6p1/3pP3/1PpP4/2P2N2/P4PPP/RNBQKB1R b KQkq b3 0 6";
-        let board = fen_service.set_fen(&test_fen);
+        let board = fen_service.set_fen(test_fen);
         let result_fen = fen_service.get_fen(&board);
         assert_eq!(test_fen, result_fen);
     }
 
+    #[test]
+    fn chess960_start_position_test() {
+        let fen_service = Service::new().fen;
+
+        assert_eq!(fen_service.get_chess960_fen(518), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        assert_eq!(fen_service.get_chess960_fen(0), "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1");
+        assert_eq!(fen_service.get_chess960_fen(959), "rkrnnqbb/pppppppp/8/8/8/8/PPPPPPPP/RKRNNQBB w KQkq - 0 1");
+
+        let board = fen_service.set_chess960_board(0);
+        assert!(board.chess960);
+        assert_eq!(board.castle_rook_squares, [96, 98, 26, 28]);
+    }
+
+    #[test]
+    fn xfen_castling_test() {
+        let fen_service = Service::new().fen;
+
+        // Shredder-FEN rook files are read, X-FEN is written
+        let board = fen_service.set_fen("rk2r3/8/8/8/8/8/8/RK2R3 w EAea - 0 1");
+        assert_eq!(board.castle_rook_squares, [91, 95, 21, 25]);
+        assert_eq!(fen_service.get_fen(&board), "rk2r3/8/8/8/8/8/8/RK2R3 w KQkq - 0 1");
+
+        // an inner rook needs its file to be distinguishable from the outermost one
+        let test_fen = "1r2k1rr/8/8/8/8/8/8/1R2K1RR w Gg - 0 1";
+        let board = fen_service.set_fen(test_fen);
+        assert_eq!(board.castle_rook_squares[1], 97);
+        assert_eq!(fen_service.get_fen(&board), test_fen);
+
+        // a file without an own rook or with the king gives no castling right
+        let board = fen_service.set_fen("rk2r3/8/8/8/8/8/8/RK2R3 w CBcb - 0 1");
+        assert_eq!(fen_service.get_fen(&board), "rk2r3/8/8/8/8/8/8/RK2R3 w - - 0 1");
+        assert_eq!(board.castle_rook_squares, [91, 98, 21, 28]);
+    }
+
+    #[test]
+    fn castling_notation_test() {
+        let fen_service = Service::new().fen;
+
+        let board = fen_service.set_fen("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1");
+        assert_eq!(board.get_castling_notation("O-O"), Some("e8g8".to_string()));
+        assert_eq!(board.get_castling_notation("O-O-O"), Some("e8c8".to_string()));
+
+        let board = fen_service.set_chess960_board(0);
+        assert_eq!(board.get_castling_notation("O-O"), Some("g1h1".to_string()));
+        assert_eq!(board.get_castling_notation("0-0"), None);
+    }
+
 }
\ No newline at end of file
@@ -0,0 +1,110 @@
+use serde::Serialize;
+
+use crate::engine_process::EngineCrash;
+use crate::locked_append;
+use crate::pgn::Pgn;
+
+
+/// One engine of a finished game.
+#[derive(Debug, Serialize)]
+pub struct PlayerRecord {
+    pub name: String,
+    pub pid: u32,  // 0 for a human player
+}
+
+/// A move with the time the engine used and its last reported search result.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MoveRecord {
+    #[serde(rename = "move")]
+    pub uci: String,
+    pub time_ms: u64,
+    pub eval: Option<String>,  // from the mover's view, e.g. "+0.35" or "-M3"
+    pub depth: Option<u32>,
+}
+
+/// Machine readable result of a finished game, written as one JSON line.
+#[derive(Debug, Serialize)]
+pub struct GameRecord {
+    pub event: String,
+    pub site: String,
+    pub date: String,
+    pub time: String,
+    pub round: String,
+    pub white: PlayerRecord,
+    pub black: PlayerRecord,
+    pub result: String,
+    pub termination: String,
+    pub plies: usize,
+    pub variant: String,
+    pub time_control: String,
+    pub start_fen: String,
+    pub final_fen: String,
+    pub moves: Vec<MoveRecord>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crash: Option<EngineCrash>,
+}
+
+impl GameRecord {
+    /// Record of the saved game `pgn`, the pids are those of the white and the black engine.
+    pub fn new(pgn: &Pgn, pids: [u32; 2], start_fen: String, final_fen: String, moves: Vec<MoveRecord>) -> GameRecord {
+        GameRecord {
+            event: pgn.event.clone(),
+            site: pgn.site.clone(),
+            date: pgn.date.clone(),
+            time: pgn.time.clone(),
+            round: pgn.round.clone(),
+            white: PlayerRecord { name: pgn.white.clone(), pid: pids[0] },
+            black: PlayerRecord { name: pgn.black.clone(), pid: pids[1] },
+            result: pgn.result.clone(),
+            termination: pgn.termination.clone(),
+            plies: moves.len(),
+            variant: if pgn.variant.is_empty() { "Standard".to_string() } else { pgn.variant.clone() },
+            time_control: pgn.time_control.clone(),
+            start_fen,
+            final_fen,
+            moves,
+            crash: pgn.crash.clone(),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("MM can not serialize game record")
+    }
+
+    /// Appends the record as one line to `path`, `-` writes it to stdout.
+    pub fn save(&self, path: &str, logfile: &str) {
+        let line = format!("{}\n", self.to_json());
+        if path == "-" {
+            print!("{}", line);
+            return;
+        }
+        if let Err(e) = locked_append::append_json_lines(path, &line, logfile) {
+            println!("Error writing to file: {}", e);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_json() {
+        let mut pgn = Pgn::new("Test".to_string(), "local".to_string(), "2026.10.18".to_string(), "2.1.3".to_string(),
+            "suprah".to_string(), "stockfish".to_string(), "12:00:00".to_string(), "30+1".to_string(), String::new(), String::new());
+        pgn.set_result("0-1".to_string());
+        pgn.set_termination("BlackWinByResignation");
+        let moves = vec![
+            MoveRecord { uci: "f2f3".to_string(), time_ms: 120, eval: None, depth: None },
+            MoveRecord { uci: "e7e5".to_string(), time_ms: 85, eval: Some("+0.40".to_string()), depth: Some(14) },
+        ];
+        let record = GameRecord::new(&pgn, [101, 202], "startpos".to_string(), "final".to_string(), moves);
+        assert_eq!(record.to_json(), concat!(
+            r#"{"event":"Test","site":"local","date":"2026.10.18","time":"12:00:00","round":"2.1.3","#,
+            r#""white":{"name":"suprah","pid":101},"black":{"name":"stockfish","pid":202},"#,
+            r#""result":"0-1","termination":"BlackWinByResignation","plies":2,"variant":"Standard","time_control":"30+1","#,
+            r#""start_fen":"startpos","final_fen":"final","#,
+            r#""moves":[{"move":"f2f3","time_ms":120,"eval":null,"depth":null},{"move":"e7e5","time_ms":85,"eval":"+0.40","depth":14}]}"#));
+    }
+}
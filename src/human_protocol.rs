@@ -0,0 +1,176 @@
+use crate::board_view;
+use crate::model::{Turn, UciGame};
+use crate::notation_util::NotationUtil;
+use crate::protocol::{EngineEvent, GameSetup, Protocol, SearchRequest};
+use crate::service::Service;
+use crate::uci_option::UciOption;
+
+
+/// A human at the terminal instead of an engine: the search request shows the board and the clocks,
+/// the typed lines are checked against the legal moves and played as the human's move.
+pub struct HumanProtocol {
+    service: Service,
+    setup: Option<GameSetup>,
+    game: Option<UciGame>,  // position of the running search request
+    legal_moves: Vec<Turn>,
+}
+
+impl HumanProtocol {
+    pub fn new() -> Self {
+        HumanProtocol { service: Service::new(), setup: None, game: None, legal_moves: Vec::new() }
+    }
+
+    fn prompt(&self) {
+        if let Some(game) = &self.game {
+            let side = if game.board.white_to_move { "white" } else { "black" };
+            println!("Your move as {} (UCI or SAN, 'moves' lists the legal moves, 'resign' gives up):", side);
+        }
+    }
+
+    /// The typed move in coordinate notation, None if it is not legal.
+    fn legal_move(&self, input: &str) -> Option<String> {
+        let board = &self.game.as_ref()?.board;
+        let uci = input.to_lowercase();
+        self.legal_moves.iter()
+            .find(|turn| turn.to_algebraic() == uci)
+            .or(NotationUtil::get_turn_from_san(input, board, &self.legal_moves).as_ref())
+            .map(Turn::to_algebraic)
+    }
+}
+
+impl Protocol for HumanProtocol {
+    fn start(&mut self) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn parse(&mut self, line: &str) -> (Vec<EngineEvent>, Vec<String>) {
+        let input = line.trim();
+        if input == "resign" {
+            self.game = None;
+            return (vec![EngineEvent::Resign], Vec::new());
+        }
+        if self.game.is_none() {
+            println!("It is not your turn.");
+            return (Vec::new(), Vec::new());
+        }
+        if input == "moves" {
+            let moves: Vec<String> = self.legal_moves.iter().map(Turn::to_algebraic).collect();
+            println!("{}", moves.join(" "));
+            self.prompt();
+            return (Vec::new(), Vec::new());
+        }
+        match self.legal_move(input) {
+            Some(best_move) => {
+                self.game = None;
+                (vec![EngineEvent::BestMove { best_move, ponder_move: None }], Vec::new())
+            }
+            None => {
+                println!("Illegal move '{}'.", input);
+                self.prompt();
+                (Vec::new(), Vec::new())
+            }
+        }
+    }
+
+    fn handshake_lapsed(&mut self) -> bool {
+        false
+    }
+
+    fn protocol_options(&self, _chess960: bool, _ponder: bool, _declared: &[UciOption]) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
+    fn set_option(&self, option: &UciOption, value: &str) -> String {
+        format!("{}={}", option.name, value)
+    }
+
+    fn debug(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn new_game(&mut self, setup: &GameSetup) -> Vec<String> {
+        println!("New game, {} ms + {} ms per move.", setup.time_ms, setup.inc_ms);
+        self.setup = Some(setup.clone());
+        self.game = None;
+        Vec::new()
+    }
+
+    fn ready_check(&mut self) -> Option<String> {
+        None
+    }
+
+    fn go(&mut self, request: &SearchRequest) -> Vec<String> {
+        let Some(setup) = &self.setup else {
+            return Vec::new();
+        };
+        let mut board = match &setup.start_fen {
+            Some(fen) => self.service.fen.set_fen(fen),
+            None => self.service.fen.set_init_board(),
+        };
+        board.chess960 = setup.chess960;
+        let mut game = UciGame::new(board);
+        for played in request.moves {
+            game.do_move(&played.uci);
+        }
+
+        println!();
+        for line in board_view::render_board(&game.board) {
+            println!("{}", line);
+        }
+        if let Some(last_move) = request.moves.last() {
+            println!("Last move: {}", last_move.uci);
+        }
+        println!("White {}  Black {}", board_view::format_clock(request.wtime), board_view::format_clock(request.btime));
+
+        self.legal_moves = self.service.move_gen.generate_valid_moves_list(&mut game.board.clone());
+        self.game = Some(game);
+        self.prompt();
+        Vec::new()
+    }
+
+    fn ponder(&mut self, _request: &SearchRequest, _predicted: &str) -> Option<Vec<String>> {
+        None
+    }
+
+    fn ponderhit(&mut self) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn stop(&mut self) -> (Vec<String>, bool) {
+        self.game = None;
+        (Vec::new(), false)
+    }
+
+    fn game_over(&mut self, result: &str, termination: &str) -> Vec<String> {
+        self.game = None;
+        println!("Game over: {} ({})", result, termination);
+        Vec::new()
+    }
+
+    fn quit(&mut self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::PlayedMove;
+
+    #[test]
+    fn test_human_moves() {
+        let mut human = HumanProtocol::new();
+        human.new_game(&GameSetup { start_fen: None, chess960: false, time_ms: 60000, inc_ms: 0, ponder: false });
+        assert_eq!(human.parse("e2e4").0, vec![]);
+
+        let moves = vec![PlayedMove { uci: "e2e4".to_string(), castling: None }];
+        human.go(&SearchRequest { moves: &moves, wtime: 60000, btime: 60000, winc: 0, binc: 0 });
+        assert_eq!(human.parse("e2e4").0, vec![]);
+        assert_eq!(human.parse("Nf6").0, vec![EngineEvent::BestMove { best_move: "g8f6".to_string(), ponder_move: None }]);
+
+        human.go(&SearchRequest { moves: &moves, wtime: 60000, btime: 60000, winc: 0, binc: 0 });
+        assert_eq!(human.parse("E7E5").0, vec![EngineEvent::BestMove { best_move: "e7e5".to_string(), ponder_move: None }]);
+        assert_eq!(human.parse("resign").0, vec![EngineEvent::Resign]);
+    }
+}
@@ -0,0 +1,345 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+
+use crate::swiss;
+
+
+/// Sudden death games after which the higher seed advances.
+const SUDDEN_DEATH_GAMES: u32 = 10;
+
+
+/// How a match that is still tied after its extra game pairs is decided.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Decider {
+    SuddenDeath,  // single games with alternating colors until one is won
+    Armageddon,   // one game, the higher seed has black and advances on a draw
+}
+
+impl Decider {
+    pub fn from_name(name: &str) -> Option<Decider> {
+        match name.trim() {
+            "sudden_death" => Some(Decider::SuddenDeath),
+            "armageddon" => Some(Decider::Armageddon),
+            _ => None,
+        }
+    }
+}
+
+/// Length of the mini-matches: `pairs` game pairs, up to `extra_pairs` more while tied, then the decider.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rules {
+    pub pairs: u32,
+    pub extra_pairs: u32,
+    pub decider: Decider,
+}
+
+
+/// Game of a match as (stage, match, game), all counted from 1 like in the PGN round tag `2.1.3`.
+type GameKey = (u32, u32, u32);
+
+/// White and white's points (None if unfinished) of the knockout games in a state file:
+/// `result<TAB>stage.match.game<TAB>white<TAB>black<TAB>result`.
+fn parse_results(state: &str) -> HashMap<GameKey, (String, Option<f64>)> {
+    state.lines().filter_map(|line| {
+        let fields: Vec<&str> = line.split('\t').collect();
+        let ["result", tag, white, _black, result] = fields.as_slice() else {
+            return None;
+        };
+        let numbers: Vec<u32> = tag.split('.').map(|number| number.parse().ok()).collect::<Option<_>>()?;
+        let [stage, number, game] = numbers.as_slice() else {
+            return None;
+        };
+        Some(((*stage, *number, *game), (white.to_string(), swiss::white_score(result))))
+    }).collect()
+}
+
+
+/// State of a match of the bracket. The players are seeds (0 is the first engine), the higher seed first;
+/// a missing player is a bye.
+#[derive(Debug, PartialEq)]
+pub struct MatchState {
+    pub stage: u32,
+    pub number: u32,
+    pub players: [Option<usize>; 2],
+    pub score: [f64; 2],
+    pub winner: Option<usize>,
+    pub next_games: Vec<u32>,  // games that can be played now
+}
+
+impl MatchState {
+    /// Index in `players` of the side that has white in a game of the match: the higher seed in odd games,
+    /// the lower seed in even games and in the Armageddon game.
+    fn white_side(&self, game: u32, rules: &Rules) -> usize {
+        let armageddon_game = 2 * (rules.pairs + rules.extra_pairs) + 1;
+        if rules.decider == Decider::Armageddon && game == armageddon_game {
+            1
+        } else {
+            ((game + 1) % 2) as usize
+        }
+    }
+}
+
+/// Plays through the finished games of a match and finds its winner or the games to play next.
+fn evaluate_match(stage: u32, number: u32, players: [Option<usize>; 2], names: &[String],
+    results: &HashMap<GameKey, (String, Option<f64>)>, rules: &Rules) -> MatchState {
+
+    let mut state = MatchState { stage, number, players, score: [0.0, 0.0], winner: None, next_games: Vec::new() };
+    let (higher, lower) = match players {
+        [Some(higher), Some(lower)] => (higher, lower),
+        [player, None] | [None, player] => {
+            state.winner = player;
+            return state;
+        }
+    };
+
+    // points of both players in a game, None if it is not played yet or unfinished (`*`) and has to be played again
+    let game_points = |game: u32| -> Option<[f64; 2]> {
+        let (white, white_score) = results.get(&(stage, number, game))?;
+        let score = (*white_score)?;
+        Some(if *white == names[higher] { [score, 1.0 - score] } else { [1.0 - score, score] })
+    };
+
+    // the regulation pairs are played at once, the extra pairs one after the other while tied
+    let mut played = 0;
+    let chunks = std::iter::once(2 * rules.pairs).chain(std::iter::repeat_n(2, rules.extra_pairs as usize));
+    for chunk in chunks {
+        for game in played + 1..=played + chunk {
+            match game_points(game) {
+                Some(points) => {
+                    state.score[0] += points[0];
+                    state.score[1] += points[1];
+                }
+                None => state.next_games.push(game),
+            }
+        }
+        played += chunk;
+        if !state.next_games.is_empty() {
+            return state;
+        }
+        if state.score[0] != state.score[1] {
+            state.winner = Some(if state.score[0] > state.score[1] { higher } else { lower });
+            return state;
+        }
+    }
+
+    let deciding_games = if rules.decider == Decider::Armageddon { 1 } else { SUDDEN_DEATH_GAMES };
+    for game in played + 1..=played + deciding_games {
+        let Some(points) = game_points(game) else {
+            state.next_games.push(game);
+            return state;
+        };
+        state.score[0] += points[0];
+        state.score[1] += points[1];
+        if points[0] != points[1] || rules.decider == Decider::Armageddon {
+            // a drawn Armageddon game goes to the higher seed with black
+            state.winner = Some(if points[1] > points[0] { lower } else { higher });
+            return state;
+        }
+    }
+    state.winner = Some(higher);
+    state
+}
+
+/// Seeds of the first stage in bracket order, for 8 places 1-8, 4-5, 2-7, 3-6 (0 based).
+fn bracket_order(size: usize) -> Vec<usize> {
+    let mut order = vec![0];
+    while order.len() < size {
+        let places = order.len() * 2;
+        order = order.iter().flat_map(|seed| [*seed, places - 1 - seed]).collect();
+    }
+    order
+}
+
+/// The stages of the bracket up to the first one that is not decided yet (or the final).
+/// The top seeds get the byes if the number of engines is not a power of two.
+pub fn evaluate_bracket(players: &[String], state: &str, rules: &Rules) -> Vec<Vec<MatchState>> {
+    let results = parse_results(state);
+    let order = bracket_order(players.len().next_power_of_two());
+    let mut entrants: Vec<Option<usize>> = order.iter().map(|seed| (*seed < players.len()).then_some(*seed)).collect();
+    let mut stages: Vec<Vec<MatchState>> = Vec::new();
+    while entrants.len() >= 2 {
+        let stage = stages.len() as u32 + 1;
+        let matches: Vec<MatchState> = entrants.chunks(2).enumerate().map(|(at, pair)| {
+            let mut seeds = [pair[0], pair[1]];
+            if let [Some(a), Some(b)] = seeds && b < a {
+                seeds = [Some(b), Some(a)];
+            }
+            evaluate_match(stage, at as u32 + 1, seeds, players, &results, rules)
+        }).collect();
+        let decided = matches.iter().all(|state| state.winner.is_some());
+        entrants = matches.iter().map(|state| state.winner).collect();
+        stages.push(matches);
+        if !decided {
+            break;
+        }
+    }
+    stages
+}
+
+/// Games to play next as (round tag, white, black, start position number), empty once the bracket is decided.
+pub fn next_games(players: &[String], state: &str, rules: &Rules) -> Vec<(String, String, String, u32)> {
+    let stages = evaluate_bracket(players, state, rules);
+    let Some(stage) = stages.last() else {
+        return Vec::new();
+    };
+    let mut games = Vec::new();
+    for state in stage {
+        let [Some(higher), Some(lower)] = state.players else {
+            continue;
+        };
+        for game in &state.next_games {
+            let (white, black) = if state.white_side(*game, rules) == 0 { (higher, lower) } else { (lower, higher) };
+            // both games of a pair share the start position
+            let position = (state.stage * 100 + state.number) * 100 + (game - 1) / 2;
+            games.push((format!("{}.{}.{}", state.stage, state.number, game), players[white].clone(), players[black].clone(), position));
+        }
+    }
+    games
+}
+
+fn stage_name(matches: usize) -> String {
+    match matches {
+        1 => "Final".to_string(),
+        2 => "Semifinals".to_string(),
+        4 => "Quarterfinals".to_string(),
+        _ => format!("Round of {}", matches * 2),
+    }
+}
+
+/// The bracket with the score of every match for the terminal.
+pub fn format_bracket(players: &[String], stages: &[Vec<MatchState>]) -> Vec<String> {
+    let name = |seed: Option<usize>| seed.map_or("-".to_string(), |seed| players[seed].clone());
+    let mut lines = Vec::new();
+    for matches in stages {
+        lines.push(stage_name(matches.len()));
+        for state in matches {
+            let line = match state.players {
+                [Some(_), Some(_)] => format!("  {}.{}  {:<25} {:>4.1} - {:<4.1} {:<25}", state.stage, state.number,
+                    name(state.players[0]), state.score[0], state.score[1], name(state.players[1])),
+                _ => format!("  {}.{}  {:<25} bye", state.stage, state.number, name(state.winner)),
+            };
+            let outcome = match state.winner {
+                Some(winner) if state.players.iter().all(Option::is_some) => format!("  -> {}", players[winner]),
+                Some(_) => String::new(),
+                None => "  (running)".to_string(),
+            };
+            lines.push(format!("{}{}", line, outcome).trim_end().to_string());
+        }
+    }
+    if let Some(winner) = stages.last().filter(|matches| matches.len() == 1).and_then(|matches| matches[0].winner) {
+        lines.push(format!("Winner: {}", players[winner]));
+    }
+    lines
+}
+
+
+/// Engines, state file content and rules of the `--knockout-*` arguments.
+fn load(state_path: &str, engines: &str, pairs: &str, extra_pairs: &str, decider: &str) -> Result<(Vec<String>, String, Rules), Box<dyn Error>> {
+    let players = engines.split(',').map(str::trim).filter(|name| !name.is_empty()).map(str::to_string).collect();
+    let rules = Rules {
+        pairs: pairs.parse().ok().filter(|pairs| *pairs > 0).ok_or("MM knockout game pairs must be a positive number")?,
+        extra_pairs: extra_pairs.parse().map_err(|_| "MM knockout extra pairs must be a number")?,
+        decider: Decider::from_name(decider).ok_or_else(|| format!("MM unknown knockout decider '{}'", decider))?,
+    };
+    Ok((players, fs::read_to_string(state_path).unwrap_or_default(), rules))
+}
+
+/// Prints the games to play next as `game<TAB>stage.match.game<TAB>white<TAB>black<TAB>position` lines.
+pub fn print_next_games(state_path: &str, engines: &str, pairs: &str, extra_pairs: &str, decider: &str) -> Result<(), Box<dyn Error>> {
+    let (players, state, rules) = load(state_path, engines, pairs, extra_pairs, decider)?;
+    for (tag, white, black, position) in next_games(&players, &state, &rules) {
+        println!("game\t{}\t{}\t{}\t{}", tag, white, black, position);
+    }
+    Ok(())
+}
+
+pub fn print_bracket(state_path: &str, engines: &str, pairs: &str, extra_pairs: &str, decider: &str) -> Result<(), Box<dyn Error>> {
+    let (players, state, rules) = load(state_path, engines, pairs, extra_pairs, decider)?;
+    for line in format_bracket(&players, &evaluate_bracket(&players, &state, &rules)) {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn players(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn result(tag: &str, white: &str, black: &str, result: &str) -> String {
+        format!("result\t{}\t{}\t{}\t{}\n", tag, white, black, result)
+    }
+
+    #[test]
+    fn test_bracket_order() {
+        assert_eq!(bracket_order(1), vec![0]);
+        assert_eq!(bracket_order(4), vec![0, 3, 1, 2]);
+        assert_eq!(bracket_order(8), vec![0, 7, 3, 4, 1, 6, 2, 5]);
+    }
+
+    #[test]
+    fn test_first_stage_with_byes() {
+        let rules = Rules { pairs: 1, extra_pairs: 0, decider: Decider::SuddenDeath };
+        let players = players(&["a", "b", "c"]);
+        let games = next_games(&players, "", &rules);
+        assert_eq!(games, vec![
+            ("1.2.1".to_string(), "b".to_string(), "c".to_string(), 10200),
+            ("1.2.2".to_string(), "c".to_string(), "b".to_string(), 10200),
+        ]);
+
+        // c wins the pair, the final a - c follows
+        let state = result("1.2.1", "b", "c", "0-1") + &result("1.2.2", "c", "b", "1/2-1/2");
+        assert_eq!(next_games(&players, &state, &rules)[0], ("2.1.1".to_string(), "a".to_string(), "c".to_string(), 20100));
+        let stages = evaluate_bracket(&players, &state, &rules);
+        assert_eq!(format_bracket(&players, &stages), vec![
+            "Semifinals",
+            "  1.1  a                         bye",
+            "  1.2  b                          0.5 - 1.5  c                          -> c",
+            "Final",
+            "  2.1  a                          0.0 - 0.0  c                          (running)",
+        ]);
+    }
+
+    #[test]
+    fn test_tied_match() {
+        let players = players(&["a", "b"]);
+        let drawn_pair = result("1.1.1", "a", "b", "1/2-1/2") + &result("1.1.2", "b", "a", "1/2-1/2");
+
+        // tied after the regulation pair: one extra pair, then sudden death
+        let rules = Rules { pairs: 1, extra_pairs: 1, decider: Decider::SuddenDeath };
+        let games = next_games(&players, &drawn_pair, &rules);
+        assert_eq!(games.iter().map(|game| game.0.as_str()).collect::<Vec<_>>(), vec!["1.1.3", "1.1.4"]);
+        let state = drawn_pair.clone() + &result("1.1.3", "a", "b", "1-0") + &result("1.1.4", "b", "a", "1-0");
+        assert_eq!(next_games(&players, &state, &rules), vec![("1.1.5".to_string(), "a".to_string(), "b".to_string(), 10102)]);
+        let state = state + &result("1.1.5", "a", "b", "1/2-1/2") + &result("1.1.6", "b", "a", "1-0");
+        assert_eq!(evaluate_bracket(&players, &state, &rules)[0][0].winner, Some(1));
+
+        // Armageddon: the lower seed has white and must win
+        let rules = Rules { pairs: 1, extra_pairs: 0, decider: Decider::Armageddon };
+        assert_eq!(next_games(&players, &drawn_pair, &rules), vec![("1.1.3".to_string(), "b".to_string(), "a".to_string(), 10101)]);
+        let state = drawn_pair + &result("1.1.3", "b", "a", "1/2-1/2");
+        assert_eq!(evaluate_bracket(&players, &state, &rules)[0][0].winner, Some(0));
+        assert!(next_games(&players, &state, &rules).is_empty());
+    }
+
+    #[test]
+    fn test_unfinished_games_are_replayed() {
+        let players = players(&["a", "b"]);
+        let rules = Rules { pairs: 1, extra_pairs: 0, decider: Decider::Armageddon };
+        let state = result("1.1.1", "a", "b", "1-0") + &result("1.1.2", "b", "a", "*");
+        assert_eq!(next_games(&players, &state, &rules), vec![("1.1.2".to_string(), "b".to_string(), "a".to_string(), 10100)]);
+        assert_eq!(evaluate_bracket(&players, &state, &rules)[0][0].score, [1.0, 0.0]);
+
+        // the replay counts, also for the Armageddon game
+        let state = state + &result("1.1.2", "b", "a", "1-0");
+        let state = state + &result("1.1.3", "b", "a", "*");
+        assert_eq!(next_games(&players, &state, &rules), vec![("1.1.3".to_string(), "b".to_string(), "a".to_string(), 10101)]);
+        let state = state + &result("1.1.3", "b", "a", "1-0");
+        assert_eq!(evaluate_bracket(&players, &state, &rules)[0][0].winner, Some(1));
+    }
+}
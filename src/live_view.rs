@@ -0,0 +1,224 @@
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::board_view;
+use crate::model::Board;
+use crate::protocol::SearchInfo;
+
+
+/// Time between two redraws of a running game.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Status files that were not written for this long belong to ended runs.
+const STALE_STATUS: Duration = Duration::from_secs(10);
+
+const CLEAR_SCREEN: &str = "\x1b[H\x1b[2J";
+
+
+/// Live output of a running game: off, the board on the terminal, or one status line
+/// in a directory that `--dashboard` shows for all parallel games.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LiveMode {
+    Off,
+    Board,
+    Status(String),
+}
+
+impl LiveMode {
+    /// Parses the live argument: empty, `live` or `live:<status_dir>`.
+    pub fn from_arg(arg: &str) -> Option<LiveMode> {
+        match arg.trim() {
+            "" => Some(LiveMode::Off),
+            "live" => Some(LiveMode::Board),
+            arg => arg.strip_prefix("live:").filter(|dir| !dir.is_empty()).map(|dir| LiveMode::Status(dir.to_string())),
+        }
+    }
+}
+
+
+/// What the live view shows of a game, the pairs are white and black.
+pub struct LiveFrame<'a> {
+    pub round: &'a str,
+    pub names: [&'a str; 2],
+    pub clocks: (i32, i32),
+    pub infos: [&'a SearchInfo; 2],
+    pub board: &'a Board,
+    pub last_move: Option<&'a str>,
+    pub ply: usize,
+}
+
+pub struct LiveView {
+    mode: LiveMode,
+    last_draw: Option<Instant>,
+}
+
+impl LiveView {
+    pub fn new(mode: LiveMode) -> Self {
+        LiveView { mode, last_draw: None }
+    }
+
+    fn status_file(dir: &str) -> PathBuf {
+        Path::new(dir).join(format!("{}.status", std::process::id()))
+    }
+
+    /// Redraws the game, unforced redraws are limited to one per REDRAW_INTERVAL.
+    pub fn draw(&mut self, frame: &LiveFrame, force: bool) {
+        if self.mode == LiveMode::Off || (!force && self.last_draw.is_some_and(|last| last.elapsed() < REDRAW_INTERVAL)) {
+            return;
+        }
+        self.last_draw = Some(Instant::now());
+        match &self.mode {
+            LiveMode::Off => {}
+            LiveMode::Board => println!("{}{}", CLEAR_SCREEN, render_frame(frame).join("\n")),
+            LiveMode::Status(dir) => {
+                // written aside and renamed, the dashboard never reads half a line
+                let file = LiveView::status_file(dir);
+                let tmp = file.with_extension("tmp");
+                if fs::create_dir_all(dir).and_then(|_| fs::write(&tmp, status_line(frame))).and_then(|_| fs::rename(&tmp, &file)).is_err() {
+                    eprintln!("MM can not write live status to {}", dir);
+                }
+            }
+        }
+    }
+
+    /// Removes the status line of this run.
+    pub fn finish(&self) {
+        if let LiveMode::Status(dir) = &self.mode {
+            let _ = fs::remove_file(LiveView::status_file(dir));
+        }
+    }
+}
+
+
+fn format_info(info: &SearchInfo) -> String {
+    let mut parts = Vec::new();
+    if let Some(depth) = info.depth {
+        parts.push(format!("depth {}", depth));
+    }
+    if let Some(score) = &info.score {
+        parts.push(format!("score {}", score));
+    }
+    if let Some(nps) = info.nps {
+        parts.push(format!("nps {}", format_nps(nps)));
+    }
+    parts.join("  ")
+}
+
+fn format_nps(nps: u64) -> String {
+    match nps {
+        0..1_000 => nps.to_string(),
+        1_000..1_000_000 => format!("{}k", nps / 1_000),
+        _ => format!("{:.1}M", nps as f64 / 1_000_000.0),
+    }
+}
+
+fn move_label(frame: &LiveFrame) -> String {
+    match frame.last_move {
+        Some(last_move) => format!("{}{} {}", frame.ply.div_ceil(2), if frame.ply % 2 == 1 { "." } else { "..." }, last_move),
+        None => "-".to_string(),
+    }
+}
+
+/// Board, clocks and search infos of a game for the terminal.
+fn render_frame(frame: &LiveFrame) -> Vec<String> {
+    let mut lines = vec![format!("Round {}: {} - {}", frame.round, frame.names[0], frame.names[1]), String::new()];
+    lines.extend(board_view::render_board(frame.board));
+    lines.push(String::new());
+    let clocks = [frame.clocks.0, frame.clocks.1];
+    for (side, color) in ["White", "Black"].iter().enumerate() {
+        let to_move = frame.board.white_to_move == (side == 0);
+        let line = format!("{} {}  {:<20} {:>8}   {}",
+            if to_move { "*" } else { " " }, color, frame.names[side], board_view::format_clock(clocks[side]), format_info(frame.infos[side]));
+        lines.push(line.trim_end().to_string());
+    }
+    lines.push(format!("Last move: {}", move_label(frame)));
+    lines
+}
+
+/// Compact one line view of a game for the dashboard.
+fn status_line(frame: &LiveFrame) -> String {
+    let marker = |white: bool| if frame.board.white_to_move == white { "*" } else { " " };
+    let score = |info: &SearchInfo| info.score.clone().unwrap_or_else(|| "-".to_string());
+    format!("{:>5}  {:<16}{}{:>7}  {:<16}{}{:>7}  {:<12}  {:>6} {:>6}",
+        frame.round,
+        frame.names[0], marker(true), board_view::format_clock(frame.clocks.0),
+        frame.names[1], marker(false), board_view::format_clock(frame.clocks.1),
+        move_label(frame), score(frame.infos[0]), score(frame.infos[1]))
+}
+
+
+/// Shows the status lines of all running games in `dir` until the process is ended,
+/// with the number of finished games of the PGN file if one is given.
+pub fn run_dashboard(dir: &str, pgn_path: Option<&str>) -> Result<(), Box<dyn Error>> {
+    loop {
+        let mut lines: Vec<String> = Vec::new();
+        if let Ok(entries) = fs::read_dir(dir) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let fresh = entry.metadata().and_then(|meta| meta.modified()).ok()
+                    .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+                    .is_some_and(|age| age < STALE_STATUS);
+                if path.extension().is_some_and(|extension| extension == "status") && fresh
+                    && let Ok(line) = fs::read_to_string(&path) {
+                    lines.push(line);
+                }
+            }
+        }
+        lines.sort_by_key(|line| line.split_whitespace().next().and_then(|round| round.parse::<u32>().ok()).unwrap_or(0));
+
+        let finished = pgn_path
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|pgn| pgn.lines().filter(|line| line.starts_with("[Result ")).count());
+        print!("{}Matt-Magie live: {} running", CLEAR_SCREEN, lines.len());
+        match finished {
+            Some(finished) => println!(", {} finished\n", finished),
+            None => println!("\n"),
+        }
+        println!("{:>5}  {:<24}  {:<24}  {:<12}  {:>13}", "Round", "White", "Black", "Move", "Score W / B");
+        for line in lines {
+            println!("{}", line);
+        }
+        thread::sleep(Duration::from_millis(500));
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::Service;
+
+    #[test]
+    fn test_live_mode_from_arg() {
+        assert_eq!(LiveMode::from_arg(""), Some(LiveMode::Off));
+        assert_eq!(LiveMode::from_arg("live"), Some(LiveMode::Board));
+        assert_eq!(LiveMode::from_arg("live:games.live"), Some(LiveMode::Status("games.live".to_string())));
+        assert_eq!(LiveMode::from_arg("live:"), None);
+        assert_eq!(LiveMode::from_arg("board"), None);
+    }
+
+    #[test]
+    fn test_render_frame() {
+        let board = Service::new().fen.set_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+        let white_info = SearchInfo::default();
+        let black_info = SearchInfo { depth: Some(12), score: Some("-0.35".to_string()), nps: Some(1_250_000) };
+        let frame = LiveFrame {
+            round: "3",
+            names: ["suprah", "stockfish"],
+            clocks: (59100, 60000),
+            infos: [&white_info, &black_info],
+            board: &board,
+            last_move: Some("e2e4"),
+            ply: 1,
+        };
+        let lines = render_frame(&frame);
+        assert_eq!(lines[0], "Round 3: suprah - stockfish");
+        assert_eq!(lines[13], "  White  suprah                 0:59.1");
+        assert_eq!(lines[14], "* Black  stockfish              1:00.0   depth 12  score -0.35  nps 1.2M");
+        assert_eq!(lines[15], "Last move: 1. e2e4");
+        assert_eq!(status_line(&frame), "    3  suprah            0:59.1  stockfish       * 1:00.0  1. e2e4            -  -0.35");
+    }
+}
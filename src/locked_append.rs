@@ -0,0 +1,186 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::os::fd::AsRawFd;
+
+use crate::log;
+
+
+/// How far back from the end of a file an incomplete record is searched.
+const TAIL_BYTES: u64 = 1 << 20;
+
+/// Games end with a blank line and start with their tags, the tags may be followed by a blank line as well.
+const PGN_SEPARATOR: &str = "\n\n";
+const PGN_START: &str = "[";
+const PGN_RESULTS: [&str; 4] = ["1-0", "0-1", "1/2-1/2", "*"];
+
+
+/// Appends PGN games to `path`, see `append_record`.
+pub fn append_pgn(path: &str, games: &str, logfile: &str) -> io::Result<()> {
+    append_record(path, games, PGN_SEPARATOR, PGN_START, is_complete_game, logfile)
+}
+
+/// Appends NDJSON lines to `path`, see `append_record`.
+pub fn append_json_lines(path: &str, lines: &str, logfile: &str) -> io::Result<()> {
+    append_record(path, lines, "\n", "", |tail| serde_json::from_str::<serde_json::Value>(tail).is_ok(), logfile)
+}
+
+/// A game is complete when its movetext ends with the result outside of a comment, a write cut off in the
+/// tags (e.g. after `[Result "1-0`) or in a comment is not.
+fn is_complete_game(game: &str) -> bool {
+    let last_line = game.lines().last().unwrap_or_default();
+    !last_line.starts_with(PGN_START)
+        && game.matches('{').count() == game.matches('}').count()
+        && last_line.split_whitespace().last().is_some_and(|token| PGN_RESULTS.contains(&token))
+}
+
+/// Appends `record` to `path` while holding an exclusive `flock` on it and syncs it to disk, so parallel
+/// writers never mix their records. While it writes, a writer notes the start of its record in `<path>.appending`,
+/// so only a record that a killed writer left behind is cut off again. Any other incomplete text at the end
+/// of the file (from the last `separator` followed by `start`, rejected by `is_complete`) is not touched, the
+/// record is not appended and the problem is logged to `logfile`. A failed write is undone.
+pub fn append_record(path: &str, record: &str, separator: &str, start: &str, is_complete: fn(&str) -> bool, logfile: &str) -> io::Result<()> {
+    let mut file = OpenOptions::new().read(true).append(true).create(true).open(path)?;
+    // SAFETY: flock only locks the descriptor of `file`, which stays open until the record is written;
+    // the lock is released when the file is closed
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let journal = format!("{}.appending", path);
+    remove_interrupted(&file, path, &journal, logfile)?;
+    let prefix = separator_prefix(&mut file, path, separator, start, is_complete, logfile)?;
+    let length = file.metadata()?.len();
+    let text = format!("{}{}", prefix, record);
+    fs::write(&journal, format!("{} {}", length, text.len()))?;
+    let written = file.write_all(text.as_bytes()).and_then(|_| file.sync_data());
+    // a record that could not be undone stays in the journal for the next writer
+    if written.is_ok() || file.set_len(length).is_ok() {
+        let _ = fs::remove_file(&journal);
+    }
+    written
+}
+
+/// Cuts off the record of a writer that was killed while it wrote, as noted in its `journal`.
+fn remove_interrupted(file: &File, path: &str, journal: &str, logfile: &str) -> io::Result<()> {
+    let Ok(entry) = fs::read_to_string(journal) else {
+        return Ok(());
+    };
+    let Some((start, length)) = entry.split_once(' ').and_then(|(start, length)| Some((start.parse::<u64>().ok()?, length.trim().parse::<u64>().ok()?))) else {
+        let message = format!("MM can not read {}, remove it after checking the end of {}", journal, path);
+        log::error(&message, logfile);
+        return Err(io::Error::other(message));
+    };
+    let size = file.metadata()?.len();
+    // the record was either not started or completely written
+    if size > start && size < start + length {
+        file.set_len(start)?;
+        log::warn(&format!("MM removed an incomplete record of {} bytes that an interrupted writer left at the end of {}", size - start, path), logfile);
+    }
+    fs::remove_file(journal)
+}
+
+/// What the next record needs in front of it to be separated from the last one of `file`. A last record that
+/// is incomplete fails, it was not written by this module and is left for the user.
+fn separator_prefix(file: &mut File, path: &str, separator: &str, start_of_record: &str, is_complete: fn(&str) -> bool, logfile: &str) -> io::Result<String> {
+    let length = file.metadata()?.len();
+    let start = length.saturating_sub(TAIL_BYTES);
+    let mut tail = Vec::new();
+    file.seek(SeekFrom::Start(start))?;
+    file.read_to_end(&mut tail)?;
+
+    let separator_bytes = separator.as_bytes();
+    let boundary = format!("{}{}", separator, start_of_record).into_bytes();
+    let record_start = tail.windows(boundary.len()).rposition(|window| window == boundary)
+        .map(|at| at + separator_bytes.len());
+    let last_record = String::from_utf8_lossy(&tail[record_start.unwrap_or(0)..]);
+    // a record longer than the searched tail is left alone
+    if record_start.is_none() && start > 0 {
+        return Ok(separator.to_string());
+    }
+    if !last_record.trim().is_empty() && !is_complete(last_record.trim()) {
+        let message = format!("MM does not append to {}, it ends with an incomplete record of {} bytes", path, last_record.len());
+        log::error(&message, logfile);
+        return Err(io::Error::other(message));
+    }
+    // a complete record without its full separator, e.g. of a file edited by hand
+    let missing = (0..=separator.len()).find(|at| tail.ends_with(&separator_bytes[..separator.len() - at])).unwrap_or(0);
+    let prefix = if tail.is_empty() { "" } else { &separator[separator.len() - missing..] };
+    Ok(prefix.to_string())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::thread;
+
+    fn temp_path(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!("mm_{}_{}", std::process::id(), name));
+        let _ = fs::remove_file(&path);
+        path.to_string_lossy().to_string()
+    }
+
+    const GAME: &str = "[Event \"T\"]\n[Result \"1-0\"]\n\n1. e2e4 1-0\n\n";
+
+    #[test]
+    fn test_interrupted_record_is_removed() {
+        let path = temp_path("torn.pgn");
+        let journal = format!("{}.appending", path);
+        let log = temp_path("torn.log");
+        for torn in ["[Event \"T\"]\n[Result \"0-1\"]\n\n1. d2d4 d7", "[Event \"T\"]\n[Result \"1-0", "[Event \"T\"]\n[Result \"1-0\"]\n\n",
+            "[Event \"T\"]\n[Result \"1-0\"]\n\n1. e2e4 {engine crashed 1-0"] {
+            fs::write(&path, format!("{}{}", GAME, torn)).unwrap();
+            // the killed writer wanted to write a longer record
+            fs::write(&journal, format!("{} {}", GAME.len(), torn.len() + 10)).unwrap();
+            append_pgn(&path, GAME, &log).unwrap();
+            assert_eq!(fs::read_to_string(&path).unwrap(), format!("{}{}", GAME, GAME), "{}", torn);
+            assert!(fs::metadata(&journal).is_err());
+        }
+
+        // the journal of a record that was written completely cuts nothing
+        fs::write(&path, GAME.repeat(2)).unwrap();
+        fs::write(&journal, format!("{} {}", GAME.len(), GAME.len())).unwrap();
+        append_pgn(&path, GAME, &log).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), GAME.repeat(3));
+
+        // a complete game without the blank line is kept, also with its movetext right after the tags
+        fs::write(&path, "[Event \"T\"]\n1. e2e4 *\n").unwrap();
+        append_pgn(&path, GAME, &log).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), format!("[Event \"T\"]\n1. e2e4 *\n\n{}", GAME));
+        fs::remove_file(&path).unwrap();
+        let _ = fs::remove_file(&log);
+    }
+
+    #[test]
+    fn test_incomplete_record_is_not_cut() {
+        let path = temp_path("foreign.pgn");
+        let log = temp_path("foreign.log");
+        for foreign in ["[Event \"T\"]\n[Result \"1-0".to_string(), format!("{}[Event \"T\"]\n[Result \"1-0\"]\n\n1. d2d4 d7", GAME)] {
+            fs::write(&path, &foreign).unwrap();
+            assert!(append_pgn(&path, GAME, &log).is_err());
+            assert_eq!(fs::read_to_string(&path).unwrap(), foreign);
+        }
+
+        let path = temp_path("foreign.ndjson");
+        fs::write(&path, "{\"a\":1}\n{\"b\":").unwrap();
+        assert!(append_json_lines(&path, "{\"c\":3}\n", &log).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"a\":1}\n{\"b\":");
+        fs::remove_file(&path).unwrap();
+        assert!(fs::read_to_string(&log).unwrap().contains("incomplete record"));
+        let _ = fs::remove_file(&log);
+    }
+
+    #[test]
+    fn test_parallel_appends() {
+        let path = temp_path("parallel.pgn");
+        let log = temp_path("parallel.log");
+        let writers: Vec<_> = (0..8).map(|_| {
+            let (path, log) = (path.clone(), log.clone());
+            thread::spawn(move || (0..25).for_each(|_| append_pgn(&path, GAME, &log).unwrap()))
+        }).collect();
+        writers.into_iter().for_each(|writer| writer.join().unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), GAME.repeat(200));
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,22 +1,240 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::panic;
+use std::sync::{Mutex, TryLockError};
+use std::time::{Duration, Instant};
 
-use std::fs::OpenOptions;
 use chrono::Local;
-use std::io::Write;
-
-pub fn log(msg: &str, path: &str) {
-    let timestamp = Local::now().format("%H:%M:%S%.3f");
-    let log_entry = format!("{} {}", timestamp, msg.to_string() + "\n");
-    match OpenOptions::new()
-        .write(true)
-        .append(true)
-        .create(true)
-        .open(path) {
-            Ok(mut file) => {
-                match file.write_all(log_entry.as_bytes()) {
-                    Ok(_) => (),
-                    Err(e) => println!("Error writing to file: {}", e),
-                }
-            },
-            Err(e) => println!("Error opening file: {}", e),
+
+
+/// Buffered lines are written at the latest after this time, warnings and errors right away.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+
+/// Detail of a log line, a logger writes the lines up to its level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Uci,  // every line to and from the engines
+}
+
+impl Level {
+    /// `error`, `warn`, `info`, `debug` or `uci` (also `uci-trace`); `log_on` of older mm.sh versions is `uci`.
+    pub fn from_arg(arg: &str) -> Option<Level> {
+        match arg.trim().to_ascii_lowercase().as_str() {
+            "error" => Some(Level::Error),
+            "warn" => Some(Level::Warn),
+            "info" => Some(Level::Info),
+            "debug" => Some(Level::Debug),
+            "uci" | "uci-trace" | "log_on" => Some(Level::Uci),
+            _ => None,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Uci => "UCI",
+        }
+    }
+}
+
+struct Sink {
+    path: String,
+    writer: BufWriter<File>,
+    flushed: Instant,
+}
+
+impl Sink {
+    fn open(path: &str) -> Option<Sink> {
+        if let Some(dir) = Path::new(path).parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            let _ = fs::create_dir_all(dir);
+        }
+        match OpenOptions::new().append(true).create(true).open(path) {
+            Ok(file) => Some(Sink { path: path.to_string(), writer: BufWriter::new(file), flushed: Instant::now() }),
+            Err(e) => {
+                println!("Error opening file: {}", e);
+                None
+            }
+        }
+    }
+
+    fn write(&mut self, line: &str, level: Level) {
+        if let Err(e) = self.writer.write_all(line.as_bytes()) {
+            println!("Error writing to file: {}", e);
+        }
+        if level <= Level::Warn || self.flushed.elapsed() >= FLUSH_INTERVAL {
+            self.flush();
+        }
+    }
+
+    fn flush(&mut self) {
+        let _ = self.writer.flush();
+        self.flushed = Instant::now();
+    }
+}
+
+struct Logger {
+    level: Level,
+    console: Option<Level>,
+    files: Vec<Sink>,
+    game: Option<Sink>,
+}
+
+static LOGGER: Mutex<Logger> = Mutex::new(Logger { level: Level::Uci, console: None, files: Vec::new(), game: None });
+
+fn logger() -> std::sync::MutexGuard<'static, Logger> {
+    LOGGER.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+
+/// Lines up to `level` go to the log files, with a `console` level also to stderr. Until this is
+/// called every line is written.
+pub fn configure(level: Level, console: Option<Level>) {
+    let mut logger = logger();
+    logger.level = level;
+    logger.console = console;
+}
+
+/// The lines of the following game also go to `path`, until `end_game`.
+pub fn start_game(path: &str) {
+    let mut logger = logger();
+    logger.game = Sink::open(path);
+}
+
+/// Closes the log file of the game, all buffered lines are written.
+pub fn end_game() {
+    let mut logger = logger();
+    if let Some(mut game) = logger.game.take() {
+        game.flush();
+    }
+    logger.files.iter_mut().for_each(Sink::flush);
+}
+
+/// Writes all buffered lines, before the process exits.
+pub fn flush() {
+    flush_all(&mut logger());
+}
+
+/// Writes the buffered lines when a thread panics, they are the ones that show what led to it. The lines
+/// of a thread that panics while it writes a line stay in the buffer.
+pub fn flush_on_panic() {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        match LOGGER.try_lock() {
+            Ok(mut logger) => flush_all(&mut logger),
+            Err(TryLockError::Poisoned(poisoned)) => flush_all(&mut poisoned.into_inner()),
+            Err(TryLockError::WouldBlock) => {}
         }
+        previous(info);
+    }));
+}
+
+fn flush_all(logger: &mut Logger) {
+    logger.files.iter_mut().for_each(Sink::flush);
+    if let Some(game) = logger.game.as_mut() {
+        game.flush();
+    }
+}
+
+pub fn error(msg: &str, path: &str) {
+    write(Level::Error, msg, path);
+}
+
+pub fn warn(msg: &str, path: &str) {
+    write(Level::Warn, msg, path);
+}
+
+pub fn info(msg: &str, path: &str) {
+    write(Level::Info, msg, path);
+}
+
+pub fn debug(msg: &str, path: &str) {
+    write(Level::Debug, msg, path);
+}
+
+pub fn uci(msg: &str, path: &str) {
+    write(Level::Uci, msg, path);
+}
+
+fn write(level: Level, msg: &str, path: &str) {
+    let mut logger = logger();
+    let to_files = level <= logger.level;
+    let to_console = logger.console.is_some_and(|console| level <= console);
+    if !to_files && !to_console {
+        return;
+    }
+    let line = format!("{} {:<5} {}\n", Local::now().format("%H:%M:%S%.3f"), level.label(), msg);
+    if to_console {
+        eprint!("{}", line);
+    }
+    if !to_files {
+        return;
+    }
+    if !logger.files.iter().any(|sink| sink.path == path) {
+        let Some(sink) = Sink::open(path) else {
+            return;
+        };
+        logger.files.push(sink);
+    }
+    if let Some(sink) = logger.files.iter_mut().find(|sink| sink.path == path) {
+        sink.write(&line, level);
+    }
+    if let Some(game) = logger.game.as_mut() {
+        game.write(&line, level);
+    }
+}
+
+/// Log file of a game in `dir`, named after the round and the pairing, e.g. `3_suprah-dev_vs_stockfish.log`.
+pub fn game_log_path(dir: &str, round: &str, white: &str, black: &str) -> String {
+    Path::new(dir).join(format!("{}_{}_vs_{}.log", file_name_part(round), file_name_part(white), file_name_part(black)))
+        .to_string_lossy().to_string()
+}
+
+/// `name` with everything but letters, digits, `-` and `.` replaced by `_`, to be used in a file name.
+pub fn file_name_part(name: &str) -> String {
+    name.chars().map(|c| if c.is_ascii_alphanumeric() || "-.".contains(c) { c } else { '_' }).collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levels() {
+        assert_eq!(Level::from_arg("log_on"), Some(Level::Uci));
+        assert_eq!(Level::from_arg("Debug"), Some(Level::Debug));
+        assert_eq!(Level::from_arg("verbose"), None);
+        assert!(Level::Warn < Level::Info && Level::Debug < Level::Uci);
+        assert_eq!(game_log_path("logs", "2.1", "engines/suprah", "sf 17"), "logs/2.1_engines_suprah_vs_sf_17.log");
+    }
+
+    #[test]
+    fn test_buffered_lines_are_flushed() {
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("mm_{}_flush.log", std::process::id())).to_string_lossy().to_string();
+        let game = dir.join(format!("mm_{}_flush_game.log", std::process::id())).to_string_lossy().to_string();
+        let read = |path: &str| fs::read_to_string(path).unwrap_or_default();
+
+        start_game(&game);
+        info("last line of the game", &path);
+        assert!(!read(&path).contains("last line of the game"));
+        end_game();
+        assert!(read(&path).contains("last line of the game") && read(&game).contains("last line of the game"));
+
+        flush_on_panic();
+        info("last line before the panic", &path);
+        assert!(std::thread::spawn(|| panic!("engine thread failed")).join().is_err());
+        assert!(read(&path).contains("last line before the panic"));
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(&game);
+    }
 }
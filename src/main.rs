@@ -6,338 +6,718 @@ mod service;
 mod fen_service;
 mod move_gen_service;
 mod zobrist;
+mod variant;
+mod engine_config;
+mod uci_option;
+mod protocol;
+mod uci_protocol;
+mod cecp_protocol;
+mod human_protocol;
+mod board_view;
+mod live_view;
+mod engine_process;
+mod clock;
+mod shutdown;
+mod swiss;
+mod knockout;
+mod perft;
+mod report;
+mod game_record;
+mod cli;
+mod analyze;
+mod eco;
+mod locked_append;
+mod resource_limits;
+mod system_load;
 
 use notation_util::NotationUtil;
-use pgn::Pgn;
+use pgn::{Pgn, PgnTags};
 use service::Service;
 use std::thread;
 use std::sync::mpsc;
-use std::sync::{Arc, Mutex};
-use std::io::{BufRead, BufReader, Write};
-use std::process::{Command, Stdio};
 use std::env;
 use std::error::Error;
-use std::process::Child;
+use std::time::{Duration, Instant};
 use chrono::{Local, Datelike, Timelike};
 use model::UciGame;
 use model::GameStatus;
 use model::Board;
-
-use crate::log::log;
-
-
-#[derive(PartialEq)]
-enum TimeControl {
-    WhiteToMove,
-    BlackToMove,
-    AllStop,
+use variant::Variant;
+use engine_config::EngineRegistry;
+use uci_option::{OptionType, UciOption};
+use protocol::{EngineEvent, GameSetup, PlayedMove, SearchRequest};
+use engine_process::{Awaiting, EngineProcess, Timeouts};
+use clock::{Clock, TimeControl};
+use live_view::{LiveFrame, LiveView};
+use game_record::{GameRecord, MoveRecord};
+use cli::{Command, Internal, MatchArgs};
+use resource_limits::ResourceLimits;
+use std::os::unix::process::CommandExt;
+
+
+/// Settings shared by all games of one Matt-Magie run.
+struct MatchSettings {
+    logfile: String,
+    pgn_path: String,
+    event: String,
+    site: String,
+    round: String,
+    log_level: log::Level,
+    game_log_dir: Option<String>,
+    crash_dir: String,
+    limits: ResourceLimits,
+    engine_options: [String; 2],
+    strict_options: bool,
+    timeouts: Timeouts,
+    time_per_game: i32,
+    inc_per_move_in_ms: i32,
+    ponder: bool,
+    save_unfinished: bool,
+    json_path: Option<String>,
+    pgn_tags: PgnTags,
 }
 
 
 fn main() -> Result<(), Box<dyn Error>> {
 
     let args: Vec<String> = env::args().collect();
+    log::flush_on_panic();
+
+    let command = match cli::parse(&args[1..]) {
+        Ok(command) => command,
+        Err(error) => {
+            eprintln!("MM {}\n\n{}", error, cli::usage_of(&args[1..]));
+            log::flush();
+            std::process::exit(2);
+        }
+    };
+    match command {
+        Command::Help(usage) => {
+            println!("{}", usage);
+            Ok(())
+        }
+        Command::Internal(internal) => run_internal(internal),
+        Command::Match(match_args) => {
+            // buffered log lines of a match that ended with an error
+            let result = EngineRegistry::load_default().and_then(|registry| play_match(&registry, *match_args));
+            log::flush();
+            result
+        }
+        Command::Tournament { file, resume } => run_tournament(&file, resume),
+        Command::Report { pgn_path, list_games, by_eco } => report::print_report(&pgn_path, list_games, by_eco),
+        Command::Perft { depth, fen, divide } => {
+            perft::print_perft(fen.as_deref(), depth, divide);
+            Ok(())
+        }
+        Command::Analyze { engine, fen, moves, time_ms, logfile } =>
+            analyze::run_analysis(&EngineRegistry::load_default()?.resolve(&engine), fen.as_deref(), &moves, time_ms, &logfile),
+    }
+}
 
-    let engine_0 = args.get(1).expect("MM engine_0 not defined");
-    let engine_1 = args.get(2).expect("MM engine_1 not defined");
-    let logfile = args.get(3).expect("MM logfile path not defined").to_string();
-    let pgn_path = args.get(4).expect("MM pgn file path not defined").to_string();
-    let event = args.get(5).expect("MM pgn event not defined").to_string();
-    let site = args.get(6).expect("MM pgn site not defined").to_string();
-    let round = args.get(7).expect("MM pgn round not defined").to_string();
-    let time_per_game = args.get(8).expect("MM pgn time per game not defined").to_string();
-    let inc_per_move_in_ms = args.get(9).expect("MM Inc per move not defined").to_string();
-    let log_on: bool = if args.get(10).cloned().unwrap_or_default() == "log_on" { true } else { false };
-    let debug_on: bool = if args.get(11).cloned().unwrap_or_default() == "debug_on" { true } else { false };
-    let engine_0_options: String = args.get(12).cloned().unwrap_or_default();
-    let engine_1_options: String = args.get(13).cloned().unwrap_or_default();
-
-    let now = Local::now();
-    let date = format!("{:04}.{:02}.{:02}", now.year(), now.month(), now.day());
-    let time = format!("{:02}:{:02}:{:02}", now.hour(), now.minute(), now.second());
-
-    let mut pgn: Pgn = Pgn::new(
-        event.clone(),
-        site.clone(),
-        date,
-        round.clone(),
-        "Engine_1".to_string(),
-        "Engine_2".to_string(),
-        time,
-        format!("{}/{}", time_per_game.to_string().parse::<i32>().expect("MM can not parse time arg") / 1000, inc_per_move_in_ms),
-        "".to_string(),
-        pgn_path.to_string(),
-    );
 
+/// Runs an internal command of mm.sh, only those with engine names load the registry.
+fn run_internal(internal: Internal) -> Result<(), Box<dyn Error>> {
+    match internal {
+        Internal::ListEngines => {
+            for name in EngineRegistry::load_default()?.names() {
+                println!("{}", name);
+            }
+            Ok(())
+        }
+        // dashboard of the parallel games of a tournament, runs until it is killed
+        Internal::Dashboard { dir, pgn_path } => live_view::run_dashboard(&dir, pgn_path.as_deref()),
+        // Swiss tournaments: pairing of the next round and standings from the tournament state file
+        Internal::SwissPairing { state_file, round, engines } => swiss::print_pairing(&state_file, &round, &engines),
+        Internal::SwissStandings { state_file, rounds, engines, tie_breaks } => swiss::print_standings(&state_file, &rounds, &engines, &tie_breaks),
+        // knockout tournaments: the games to play next and the bracket from the tournament state file
+        Internal::KnockoutGames { state_file, engines, pairs, extra_pairs, decider } =>
+            knockout::print_next_games(&state_file, &engines, &pairs, &extra_pairs, &decider),
+        Internal::KnockoutBracket { state_file, engines, pairs, extra_pairs, decider } =>
+            knockout::print_bracket(&state_file, &engines, &pairs, &extra_pairs, &decider),
+        // games and JSON records of a finished run, appended to the shared files under their lock
+        Internal::AppendPgn { from, to, logfile } => append_file(&from, &to, &logfile, locked_append::append_pgn),
+        Internal::AppendJson { from, to, logfile } => append_file(&from, &to, &logfile, locked_append::append_json_lines),
+        // parallel games of `concurrency = auto` and the load guard of the tournament scheduler
+        Internal::AutoConcurrency { cpus_per_engine, engines } => {
+            system_load::print_auto_concurrency(&EngineRegistry::load_default()?, cpus_per_engine, &engines);
+            Ok(())
+        }
+        Internal::LoadCheck { max_load_per_core, min_free_memory_percent } => {
+            if !system_load::check_load(max_load_per_core, min_free_memory_percent)? {
+                log::flush();
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+    }
+}
 
-    log("Matt-Magie 1.3 started", &logfile);
+/// Appends the records of the file `from` to `to` with `append`.
+fn append_file(from: &str, to: &str, logfile: &str, append: fn(&str, &str, &str) -> std::io::Result<()>) -> Result<(), Box<dyn Error>> {
+    let records = std::fs::read_to_string(from).map_err(|e| format!("MM can not read {}: {}", from, e))?;
+    append(to, &records, logfile).map_err(|e| format!("MM can not append to {}: {}", to, e).into())
+}
 
 
-    let (tx0, rx) = mpsc::channel();
-    let tx1 = mpsc::Sender::clone(&tx0);
-    let (tx_clock, rx_clock) = mpsc::channel::<TimeControl>();
+/// Runs a tournament file with the mm.sh script, which calls this binary for the games.
+fn run_tournament(file: &str, resume: bool) -> Result<(), Box<dyn Error>> {
+    let script = env::var("MM_SCRIPT").unwrap_or_else(|_| "./mm.sh".to_string());
+    if !std::path::Path::new(&script).is_file() {
+        return Err(format!("MM tournament needs {} (set MM_SCRIPT to its path)", script).into());
+    }
+    let mut command = std::process::Command::new(&script);
+    command.arg("-t").arg(file);
+    if resume {
+        command.arg("--resume");
+    }
+    Err(format!("MM can not start {}: {}", script, command.exec()).into())
+}
 
-    let mut engine_process_0: Child = Command::new(engine_0)
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .spawn()?;
 
-    log(&format!("loaded eng0 {}: {} ", engine_process_0.id(), engine_0), &logfile);
-    let engine_0_stdout = engine_process_0.stdout.take().ok_or("MM Failed to retrieve stdout")?;
-    let id_engine_0: u32 = engine_process_0.id();
-    send(&mut engine_process_0, "uci", &logfile);    
+/// Plays the games of one match between two engines.
+fn play_match(registry: &EngineRegistry, args: MatchArgs) -> Result<(), Box<dyn Error>> {
+    let engine_configs = [registry.resolve(&args.engines[0]), registry.resolve(&args.engines[1])];
+    let logfile = args.logfile.clone();
+    let variant_arg = args.variant;
+    let games = args.games;
+    let mut live = LiveView::new(args.live);
+
+    let settings = MatchSettings {
+        logfile: logfile.clone(),
+        pgn_path: args.pgn_path,
+        event: args.event,
+        site: args.site,
+        round: args.round,
+        log_level: args.log_level,
+        game_log_dir: args.game_log_dir,
+        crash_dir: args.crash_dir,
+        limits: args.limits,
+        engine_options: [merge_options(&engine_configs[0].options, &args.options[0]), merge_options(&engine_configs[1].options, &args.options[1])],
+        strict_options: args.strict_options,
+        timeouts: args.timeouts,
+        time_per_game: args.time_ms,
+        inc_per_move_in_ms: args.inc_ms,
+        ponder: args.ponder,
+        save_unfinished: args.save_unfinished,
+        json_path: args.json_path,
+        pgn_tags: args.pgn_tags,
+    };
 
+    shutdown::install();
+    log::configure(settings.log_level, args.console_level);
+    let service = Service::new();
 
-    let mut engine_process_1: Child = Command::new(engine_1)
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .spawn()?;
+    log::info("Matt-Magie 1.3 started", &logfile);
 
-    log(&format!("loaded eng1 {}: {} ", engine_process_1.id(), engine_1), &logfile);
-    let engine_1_stdout = engine_process_1.stdout.take().ok_or("MM Failed to retrieve stdout")?;
-    let id_engine_1: u32 = engine_process_1.id();
-    send(&mut engine_process_1, "uci", &logfile);
 
+    let (tx, rx) = mpsc::channel();
+    let clock = Clock::start()?;
 
-    let _handle_0 = thread::Builder::new().name("Thread 0".to_string()).spawn(move || {
-        let reader_eng0 = BufReader::new(engine_0_stdout);
-        for line in reader_eng0.lines() {
-            tx0.send("0_".to_string() + &line.expect("MM read engine_0 std input failed")).expect("MM send engine_0 std input failed");
+    // the cores stay claimed until the run ends, restarted engines get the same ones
+    let cpu_lease = match settings.limits.cpus_per_engine {
+        0 => None,
+        cpus => Some(resource_limits::claim_cpus(2 * cpus, &logfile)?),
+    };
+    let process_limits = [settings.limits.for_engine(0, cpu_lease.as_ref()), settings.limits.for_engine(1, cpu_lease.as_ref())];
+    let mut engines = [
+        EngineProcess::start(0, &engine_configs[0], &process_limits[0], &tx, &logfile)?,
+        EngineProcess::start(1, &engine_configs[1], &process_limits[1], &tx, &logfile)?,
+    ];
+
+    for game_index in 0..games {
+        let variant = Variant::from_pairing_arg(&variant_arg, (game_index / 2) as u64).expect("MM can not parse variant arg");
+
+        // engines are kept between the games, crashed or stalled ones are restarted
+        for engine in engines.iter_mut() {
+            if let Some(status) = engine.exit_status() {
+                log::warn(&format!("Engine {} is restarted after exit: {:?}", engine.index, status), &logfile);
+                *engine = EngineProcess::start(engine.index, &engine_configs[engine.index], &process_limits[engine.index], &tx, &logfile)?;
+            }
         }
-    })?;
 
-    let _handle_1 = thread::Builder::new().name("Thread 1".to_string()).spawn(move || {
-        let reader_eng1 = BufReader::new(engine_1_stdout);
-        for line in reader_eng1.lines() {
-            tx1.send("1_".to_string() + &line.expect("MM read engine_1 std input failed")).expect("MM send engine_1 std input failed");
+        let white_index = (game_index % 2) as usize;
+        let mut pgn = new_pgn(&settings, game_index, &engines, white_index);
+        if let Variant::Chess960(index) = variant {
+            pgn.set_variant("Chess960", &service.fen.get_chess960_fen(index));
         }
-    })?;
-
-
-    let time_white = Arc::new(Mutex::new(time_per_game.to_string().parse::<i32>()
-    .expect("MM failed to parse white time_per_game")));
 
-    let time_black = Arc::new(Mutex::new(time_per_game.to_string().parse::<i32>()
-    .expect("MM failed to parse black time_per_game")));
+        // the registry may give an engine its own time control
+        let (white, black) = (&engines[white_index].config, &engines[1 - white_index].config);
+        clock.reset(
+            white.time_control.unwrap_or(settings.time_per_game),
+            black.time_control.unwrap_or(settings.time_per_game),
+            white.increment.unwrap_or(settings.inc_per_move_in_ms),
+            black.increment.unwrap_or(settings.inc_per_move_in_ms),
+        );
+
+        if let Some(dir) = &settings.game_log_dir {
+            let (white, black) = (&engines[white_index].config.name, &engines[1 - white_index].config.name);
+            log::start_game(&log::game_log_path(dir, &pgn.round, white, black));
+        }
+        let played = play_game(&settings, &mut engines, &rx, &clock, &mut pgn, &variant, &service, white_index, &mut live);
+        log::end_game();
+        played?;
+        if shutdown::requested().is_some() {
+            break;
+        }
+    }
 
-    let time_white_clone = Arc::clone(&time_white);
-    let time_black_clone = Arc::clone(&time_black);
+    for engine in engines.iter_mut() {
+        engine.shutdown(&logfile);
+    }
+    live.finish();
+    if let Some(signal) = shutdown::requested() {
+        log::warn(&format!("Matt Magie stopped by signal {}", signal), &logfile);
+        shutdown::exit_by(signal);
+    }
+    log::info("finished Matt Magie", &logfile);
+    log::flush();
+    std::process::exit(0);
+}
 
-    let inc_per_move_in_ms = inc_per_move_in_ms.to_string().parse::<i32>().expect("MM can not parse inc per move arg");
 
-    let _handle_1 = thread::Builder::new().name("Time_Control".to_string()).spawn(move || {
+/// PGN of the next game, registered engines are named by the registry, the others by their id name.
+fn new_pgn(settings: &MatchSettings, game_index: u32, engines: &[EngineProcess; 2], white_index: usize) -> Pgn {
+    let now = Local::now();
+    let date = format!("{:04}.{:02}.{:02}", now.year(), now.month(), now.day());
+    let time = format!("{:02}:{:02}:{:02}", now.hour(), now.minute(), now.second());
+    let round = match settings.round.parse::<u32>() {
+        Ok(round) => (round + game_index).to_string(),
+        Err(_) => settings.round.clone(),
+    };
+    // one TimeControl for both sides, registry overrides that differ make it unknown
+    let time_controls: Vec<String> = engines.iter()
+        .map(|engine| pgn::format_time_control(engine.config.time_control.unwrap_or(settings.time_per_game),
+            engine.config.increment.unwrap_or(settings.inc_per_move_in_ms)))
+        .collect();
+    let time_control = if time_controls[0] == time_controls[1] { time_controls[0].clone() } else { "?".to_string() };
 
-        let mut to_move = TimeControl::AllStop;
-    
-        loop {
-            match rx_clock.try_recv() {
-                Ok(message) => {
-                    match message {
-                        TimeControl::WhiteToMove => {
-                            *time_white_clone.lock().expect("MM could not unlock time_white") += inc_per_move_in_ms;
-                            to_move = TimeControl::WhiteToMove;
-                        },
-                        TimeControl::BlackToMove => {
-                            *time_black_clone.lock().expect("MM could not unlock time_white") += inc_per_move_in_ms;
-                            to_move = TimeControl::BlackToMove;
-                        },
-                        TimeControl::AllStop => {
-                            to_move = TimeControl::AllStop;
-                        },
+    let mut pgn: Pgn = Pgn::new(
+        settings.event.clone(),
+        settings.site.clone(),
+        date,
+        round,
+        "Engine_1".to_string(),
+        "Engine_2".to_string(),
+        time,
+        time_control,
+        "".to_string(),
+        settings.pgn_path.clone(),
+    );
 
-                    }
-                },
-                Err(mpsc::TryRecvError::Empty) => {
-                    // do nothing proceed...
-                },
-                Err(mpsc::TryRecvError::Disconnected) => {
-                    panic!("Disconnected clock channel for TimeControl...");
-                }
-            }
-            if to_move == TimeControl::WhiteToMove {
-                let mut wtime = time_white_clone.lock().expect("MM could not unlock time_white");
-                *wtime -= 10;
-            } else if to_move == TimeControl::BlackToMove {
-                let mut btime = time_black_clone.lock().expect("MM could not unlock time_white");
-                *btime -= 10;
-            }
-            thread::sleep(std::time::Duration::from_millis(10));
+    for (index, engine) in engines.iter().enumerate() {
+        let name = if engine.config.registered { &engine.config.name } else { &engine.id_name };
+        if name.is_empty() {
+            continue;
         }
-    })?;
-
+        if index == white_index {
+            pgn.set_white_name(name);
+        } else {
+            pgn.set_black_name(name);
+        }
+    }
+    pgn.set_elos(engines[white_index].config.elo, engines[1 - white_index].config.elo);
+    pgn.set_tags(settings.pgn_tags.clone());
+    pgn
+}
 
-    let service = Service::new();
 
-    let mut game = UciGame::new(service.fen.set_init_board());
+/// Plays one game, the engine with `white_index` has the white pieces.
+#[allow(clippy::too_many_arguments)]
+fn play_game(settings: &MatchSettings,
+    engines: &mut [EngineProcess; 2],
+    rx: &mpsc::Receiver<String>,
+    clock: &Clock,
+    pgn: &mut Pgn,
+    variant: &Variant,
+    service: &Service,
+    white_index: usize,
+    live: &mut LiveView) -> Result<(), Box<dyn Error>> {
+
+    let logfile = settings.logfile.as_str();
+    let black_index = 1 - white_index;
+
+    let mut game = UciGame::new(variant.get_start_board(service));
+    let start_fen = service.fen.get_fen(&game.board);
+    let mut moves: Vec<PlayedMove> = Vec::new();
+    let mut move_records: Vec<MoveRecord> = Vec::new();
+    let mut turn_started = Instant::now();
     let mut all_moves_long_algebraic = String::new();
-    let mut game_status = 0;
+    let mut game_started = false;
+    let mut clock_expired_at: Option<Instant> = None;
+
+    // each engine learns its own time control, engines that finished their handshake start the game right away
+    let (time_white, time_black) = clock.remaining();
+    let (inc_white, inc_black) = clock.increments();
+    let mut setups: [GameSetup; 2] = std::array::from_fn(|_| GameSetup {
+        start_fen: variant.get_start_fen(service),
+        chess960: variant.is_chess960(),
+        time_ms: time_white,
+        inc_ms: inc_white,
+        ponder: settings.ponder,
+    });
+    setups[black_index].time_ms = time_black;
+    setups[black_index].inc_ms = inc_black;
+    for engine in engines.iter_mut() {
+        if engine.awaiting == Awaiting::Nothing {
+            engine.new_game(&setups[engine.index], logfile);
+        }
+    }
 
-    let mut remaining_time_white;
-    let mut remaining_time_black;
-    
     // mainthread loop received engine inputs from all engines
-    loop {
+    'game: loop {
+
+        // SIGINT or SIGTERM end the game unfinished, a game without moves is not saved
+        if shutdown::requested().is_some() && game.board.game_status == GameStatus::Normal {
+            log::warn("Game interrupted by signal", logfile);
+            game.board.game_status = GameStatus::Unfinished;
+            if moves.is_empty() || !settings.save_unfinished {
+                clock.tx.send(TimeControl::AllStop).expect("MM could not send time data");
+                break;
+            }
+        }
 
         // Check if either engine process has exited unexpectedly
-        if let Ok(Some(status)) = engine_process_0.try_wait() {
-            log(&format!("Engine 0 exited unexpectedly: {:?}", status), &logfile);
-            game.board.game_status = GameStatus::BlackWinByTime;
-        } else if let Ok(Some(status)) = engine_process_1.try_wait() {
-            log(&format!("Engine 1 exited unexpectedly: {:?}", status), &logfile);
-            game.board.game_status = GameStatus::WhiteWinByTime;
+        if game.board.game_status == GameStatus::Normal {
+            for engine in engines.iter_mut() {
+                engine.sample_memory();
+                if let Some(status) = engine.exit_status() {
+                    let mut crash = engine.crash(status, service.fen.get_fen(&game.board));
+                    match engine.save_repro(&crash, &settings.crash_dir, &pgn.round) {
+                        Ok(path) => crash.repro = Some(path),
+                        Err(e) => log::warn(&format!("MM could not write the crash script to {}: {}", settings.crash_dir, e), logfile),
+                    }
+                    log::error(&format!("Engine {} exited unexpectedly: {}", engine.index, crash.summary(crash.stderr.len())), logfile);
+                    log::error(&format!("Engine {} crashed at {}, last exchange:", engine.index, crash.fen), logfile);
+                    for line in &crash.exchange {
+                        log::error(&format!("{}\t{}", crash.pid, line), logfile);
+                    }
+                    let white_crashed = engine.index == white_index;
+                    game.board.game_status = match (crash.memory_limit_exceeded, white_crashed) {
+                        (true, true) => GameStatus::BlackWinByMemoryLimit,
+                        (true, false) => GameStatus::WhiteWinByMemoryLimit,
+                        (false, true) => GameStatus::BlackWinByTime,
+                        (false, false) => GameStatus::WhiteWinByTime,
+                    };
+                    pgn.set_crash(crash);
+                    break;
+                }
+            }
+        }
+
+        // an engine that does not answer the handshake is stalled
+        if game.board.game_status == GameStatus::Normal {
+            for engine in engines.iter_mut() {
+                if engine.awaiting.is_overdue(&settings.timeouts) {
+                    log::error(&format!("Engine {} stalled, no {} in time", engine.index, engine.awaiting.answer()), logfile);
+                    engine.terminate();
+                    game.board.game_status = if engine.index == white_index { GameStatus::BlackWinByStall } else { GameStatus::WhiteWinByStall };
+                    break;
+                }
+            }
         }
 
-        remaining_time_white = *time_white.lock().expect("MM could not unlock time_white (remaining_time)");
-        remaining_time_black = *time_black.lock().expect("MM could not unlock time_white (remaining_time)");
-    
-        if remaining_time_white <= 0 {
-            game.board.game_status = GameStatus::BlackWinByTime;
-        } else if remaining_time_black <= 0 {
-            game.board.game_status = GameStatus::WhiteWinByTime;
+        let (remaining_time_white, remaining_time_black) = clock.remaining();
+        let (inc_white, inc_black) = clock.increments();
+
+        // a flagged engine loses on time with its late bestmove, without bestmove it is stalled
+        if (remaining_time_white <= 0 || remaining_time_black <= 0) && game.board.game_status == GameStatus::Normal {
+            let flagged_at = *clock_expired_at.get_or_insert_with(Instant::now);
+            if flagged_at.elapsed() > Duration::from_millis(settings.timeouts.bestmove) {
+                let flagged_index = if remaining_time_white <= 0 { white_index } else { black_index };
+                let flagged_white = flagged_index == white_index;
+                if engines[flagged_index].is_human() {
+                    game.board.game_status = if flagged_white { GameStatus::BlackWinByTime } else { GameStatus::WhiteWinByTime };
+                } else {
+                    log::error(&format!("Engine {} stalled, no bestmove after its time ran out", flagged_index), logfile);
+                    engines[flagged_index].terminate();
+                    game.board.game_status = if flagged_white { GameStatus::BlackWinByStall } else { GameStatus::WhiteWinByStall };
+                }
+            }
         }
 
-        if game_status == 2 {
+        if !game_started && engines.iter().all(|engine| engine.awaiting == Awaiting::Nothing) && game.board.game_status == GameStatus::Normal {
             // all Engines ready for new game
-            send(&mut engine_process_0, &format!("go wtime {} btime {}", remaining_time_white, remaining_time_black), &logfile);
-            tx_clock.send(TimeControl::WhiteToMove).expect("MM could not send time data");
-            game_status += 1;
+            let request = SearchRequest { moves: &moves, wtime: remaining_time_white, btime: remaining_time_black, winc: inc_white, binc: inc_black };
+            engines[white_index].go(&request, logfile);
+            clock.tx.send(TimeControl::WhiteToMove).expect("MM could not send time data");
+            turn_started = Instant::now();
+            game_started = true;
         }
 
-        if check_game_over(&mut game.board, &tx_clock, &logfile, &mut pgn, &all_moves_long_algebraic, &service) {
-            log(&format!("white_time {} winc {} black_time {} binc {}",
+        draw_live(live, pgn, clock, engines, white_index, &game.board, &moves, false);
+
+        if check_game_over(&mut game, &clock.tx, logfile, pgn, &all_moves_long_algebraic, service) {
+            log::debug(&format!("white_time {} winc {} black_time {} binc {}",
                 remaining_time_white,
-                inc_per_move_in_ms,
+                inc_white,
                 remaining_time_black,
-                inc_per_move_in_ms),
-                &logfile);
+                inc_black),
+                logfile);
             break;
-        } 
-
-        let result: Result<String, mpsc::RecvError>;
+        }
 
-        result = match rx.try_recv() {
-            Ok(message) => Ok(message),
-            Err(mpsc::TryRecvError::Empty) => {
-                thread::sleep(std::time::Duration::from_millis(5));
-                continue;
+        // an engine whose handshake ended without an answer gets no line, only its end
+        let (value, handshake_lapsed) = match rx.try_recv() {
+            Ok(message) => (message, false),
+            Err(mpsc::TryRecvError::Empty) => match engines.iter_mut().position(|engine| engine.protocol.handshake_lapsed()) {
+                Some(index) => (format!("{}:", index), true),
+                None => {
+                    thread::sleep(std::time::Duration::from_millis(5));
+                    continue;
+                }
             },
             Err(mpsc::TryRecvError::Disconnected) => {
-                log("disconnected from command queue", &logfile);
+                log::error("disconnected from command queue", logfile);
                 break;
             }
-        };     
-        
-        
-        match result {
-            Ok(value) => {
-                let (id_engine, msg, current_engine_process, other_engine_process, white) = if value.starts_with("0") {
-                    (id_engine_0, &value[2..], &mut engine_process_0, &mut engine_process_1, true)
-                } else {
-                    (id_engine_1, &value[2..], &mut engine_process_1, &mut engine_process_0, false)
-                };
-        
-                if msg.starts_with("log") && log_on {
-                    log(&format!("{}\t->logger\t{}", id_engine, value), &logfile);
-                } else {
-                    log(&format!("{}\t->  mat\t\t{}", id_engine, value), &logfile);
-                }
-                
-        
-                match msg {
-                    "uciok" => {
-                        let opts = if white { &engine_0_options } else { &engine_1_options };
-                        if !opts.is_empty() {
-                            for opt in opts.split(',') {
-                                if let Some((name, val)) = parse_option(opt) {
-                                    send(current_engine_process, &format!("setoption name {} value {}", name, val), &logfile);
+        };
+
+        let index = if value.starts_with("0") { 0 } else { 1 };
+        let msg = &value[2..];
+        let white = index == white_index;
+        let [engine_0, engine_1] = &mut *engines;
+        let (current_engine, other_engine) = if index == 0 { (engine_0, engine_1) } else { (engine_1, engine_0) };
+        let id_engine = current_engine.id();
+
+        let (events, replies) = if handshake_lapsed {
+            log::info(&format!("{}\tno feature done=1, handshake done", id_engine), logfile);
+            (vec![EngineEvent::HandshakeDone], Vec::new())
+        } else {
+            if msg.starts_with("log") {
+                log::info(&format!("{}\t->logger\t{}", id_engine, value), logfile);
+            } else {
+                log::uci(&format!("{}\t->  mat\t\t{}", id_engine, value), logfile);
+            }
+            current_engine.received(msg);
+            current_engine.protocol.parse(msg)
+        };
+        current_engine.send_all(replies, logfile);
+
+        for event in events {
+            match event {
+                EngineEvent::HandshakeDone => {
+                    let opts = &settings.engine_options[index];
+                    let advertised = current_engine.advertised_options.clone();
+                    let mut sent_options: Vec<(String, String)> = Vec::new();
+                    let protocol_options = current_engine.protocol.protocol_options(variant.is_chess960(), settings.ponder, &advertised);
+                    current_engine.ponder = protocol_options.iter().any(|(name, _)| name == "Ponder");
+                    for (name, val) in protocol_options {
+                        let option = UciOption { name: name.clone(), option_type: OptionType::Check, default: String::new() };
+                        let command = current_engine.protocol.set_option(&option, &val);
+                        current_engine.send(&command, logfile);
+                        sent_options.push((name, val));
+                    }
+                    if !opts.is_empty() {
+                        for opt in opts.split(',') {
+                            if let Some((name, val)) = parse_option(opt) {
+                                match uci_option::checked_option(&advertised, &name, &val) {
+                                    Ok((option, value)) => {
+                                        let command = current_engine.protocol.set_option(option, &value);
+                                        current_engine.send(&command, logfile);
+                                        sent_options.push((name, val));
+                                    }
+                                    Err(error) => {
+                                        let warning = format!("MM engine {} rejected option '{}': {}", id_engine, opt.trim(), error);
+                                        log::warn(&warning, logfile);
+                                        eprintln!("{}", warning);
+                                        if settings.strict_options {
+                                            current_engine.quit(logfile);
+                                            other_engine.quit(logfile);
+                                            return Err(warning.into());
+                                        }
+                                    }
                                 }
                             }
                         }
-                        if debug_on {
-                            send(current_engine_process, "debug on", &logfile);
-                        }
-                        send(current_engine_process, "isready", &logfile);
                     }
-                    "readyok" => {
-                        send(current_engine_process, "ucinewgame", &logfile);
-                        game_status += 1;
+                    log::info(&format!("{}\teffective options: {}", id_engine, uci_option::effective_options(&advertised, &sent_options)), logfile);
+                    if settings.log_level >= log::Level::Debug {
+                        let commands = current_engine.protocol.debug();
+                        current_engine.send_all(commands, logfile);
                     }
-                    _ if msg.starts_with("id name") => {
+                    current_engine.new_game(&setups[index], logfile);
+                }
+                EngineEvent::Ready => {
+                    current_engine.awaiting = Awaiting::Nothing;
+                }
+                EngineEvent::OptionDeclared(option) => {
+                    current_engine.advertised_options.push(option);
+                }
+                EngineEvent::IdName(name) => {
+                    if !current_engine.config.registered {
                         if white {
-                            pgn.set_white_name(&msg[8..]);
+                            pgn.set_white_name(&name);
                         } else {
-                            pgn.set_black_name(&msg[8..]);
-                        }    
+                            pgn.set_black_name(&name);
+                        }
                     }
-                    _ if msg.starts_with("bestmove") => {
-                        
-                        
-                        let best_move = if msg.len() > 13 {
-                            &msg[9..14]
-                        } else {
-                            &msg[9..13]
-                        };
-                        
-                        game.do_move(best_move);
+                    current_engine.id_name = name;
+                }
+                EngineEvent::Info(info) => {
+                    current_engine.info.update(info);
+                }
+                EngineEvent::Resign if game_started && game.board.game_status == GameStatus::Normal => {
+                    log::info(&format!("{}\tresigns", id_engine), logfile);
+                    game.board.game_status = if white { GameStatus::BlackWinByResignation } else { GameStatus::WhiteWinByResignation };
+                }
+                EngineEvent::BestMove { best_move, ponder_move } => {
 
-                        let turn = NotationUtil::get_turn_from_notation(best_move);
+                    if current_engine.stale_bestmoves > 0 {
+                        // answer to a stopped search or ponder search
+                        current_engine.stale_bestmoves -= 1;
+                        continue;
+                    }
+                    if !current_engine.searching {
+                        continue;
+                    }
+                    current_engine.searching = false;
 
-                        let long_algebraic = if turn.promotion != 0 {
-                            format!("{}{}", &msg[9..13], "=Q")
-                        } else {
-                            format!("{}", NotationUtil::get_long_algebraic(&msg[9..13], &game.board))
-                        };
-                        
-
-                        let move_number = if game.pty % 2 == 1 { format!("{}. ", game.pty / 2 + 1) } else { format!("") };
-                        all_moves_long_algebraic = format!("{} {}{}", all_moves_long_algebraic, move_number, long_algebraic);
-                        let possible_turns = service.move_gen.generate_valid_moves_list(&mut game.board);
-                        
-                        if possible_turns.is_empty() {
-                            log("found no moves", &logfile);
-                        }
+                    if clock_expired_at.is_some() {
+                        // the move came too late, the flagged engine loses on time
+                        game.board.game_status = if white { GameStatus::BlackWinByTime } else { GameStatus::WhiteWinByTime };
+                        continue;
+                    }
 
-                        let all_moves_str = game.made_moves_str.as_str();
-                
-                        if check_game_over(&mut game.board, &tx_clock, &logfile, &mut pgn, &all_moves_long_algebraic, &service) {
-                            break;
-                        }              
-
-                        let all_moves = format!("position startpos moves {}", all_moves_str);
-                        send(other_engine_process, &all_moves, &logfile);
-
-                        // inc_per_move_in_ms
-                        send(other_engine_process, &format!("go wtime {} winc {} btime {} binc {}",
-                            remaining_time_white,
-                            inc_per_move_in_ms,
-                            remaining_time_black,
-                            inc_per_move_in_ms
-                            ),
-                            &logfile);
-
-                        if !white {
-                            tx_clock.send(TimeControl::WhiteToMove).expect("MM could not send white time command");
-                        } else {
-                            tx_clock.send(TimeControl::BlackToMove).expect("MM could not send black time command");
-                        }
+                    // a malformed or illegal move loses the game
+                    let Some(best_move) = legal_move(&mut game.board, service, &best_move) else {
+                        log::error(&format!("{}\tillegal move: {}", id_engine, msg), logfile);
+                        game.board.game_status = if white { GameStatus::BlackWinByIllegalMove } else { GameStatus::WhiteWinByIllegalMove };
+                        check_game_over(&mut game, &clock.tx, logfile, pgn, &all_moves_long_algebraic, service);
+                        break 'game;
+                    };
+
+                    let turn = NotationUtil::get_turn_from_notation(&best_move);
+                    let castling = game.board.get_castling_squares(&turn)
+                        .map(|(king_to, _, _)| if king_to % 10 == 7 { "O-O" } else { "O-O-O" });
+
+                    game.do_move(&best_move);
+
+                    let long_algebraic = long_algebraic(&best_move, castling, &game.board);
+                    moves.push(PlayedMove { uci: best_move.clone(), castling });
+                    move_records.push(MoveRecord {
+                        uci: best_move.clone(),
+                        time_ms: turn_started.elapsed().as_millis() as u64,
+                        eval: current_engine.info.score.clone(),
+                        depth: current_engine.info.depth,
+                    });
+
+
+                    let move_number = if game.pty % 2 == 1 { format!("{}. ", game.pty / 2 + 1) } else { String::new() };
+                    all_moves_long_algebraic = format!("{} {}{}", all_moves_long_algebraic, move_number, long_algebraic);
+                    let possible_turns = service.move_gen.generate_valid_moves_list(&mut game.board);
+
+                    if possible_turns.is_empty() {
+                        log::info("found no moves", logfile);
+                    }
+
+                    if check_game_over(&mut game, &clock.tx, logfile, pgn, &all_moves_long_algebraic, service) {
+                        break 'game;
                     }
-                    _ => {}
+
+                    let request = SearchRequest {
+                        moves: &moves,
+                        wtime: remaining_time_white,
+                        btime: remaining_time_black,
+                        winc: inc_white,
+                        binc: inc_black,
+                    };
+
+                    // a pondering opponent continues its search on a hit, on a miss it searches anew
+                    if other_engine.pondering.as_deref() == Some(best_move.as_str()) {
+                        other_engine.pondering = None;
+                        other_engine.searching = true;
+                        let commands = other_engine.protocol.ponderhit();
+                        other_engine.send_all(commands, logfile);
+                    } else {
+                        other_engine.stop_search(logfile);
+                        other_engine.go(&request, logfile);
+                    }
+
+                    // the engine thinks on the opponent's time about its predicted reply
+                    if let Some(ponder_move) = ponder_move.filter(|mv| current_engine.ponder && is_move_notation(mv))
+                        && let Some(commands) = current_engine.protocol.ponder(&request, &ponder_move) {
+                        current_engine.send_all(commands, logfile);
+                        current_engine.pondering = Some(ponder_move);
+                    }
+
+                    if !white {
+                        clock.tx.send(TimeControl::WhiteToMove).expect("MM could not send white time command");
+                    } else {
+                        clock.tx.send(TimeControl::BlackToMove).expect("MM could not send black time command");
+                    }
+                    turn_started = Instant::now();
                 }
-            },
-            Err(_error) => {
-                panic!("cannot be here, message is OK");
+                _ => {}
             }
         }
     }
-    send(&mut engine_process_0, "stop", &logfile);
-    send(&mut engine_process_1, "stop", &logfile);
-    send(&mut engine_process_0, "quit", &logfile);
-    send(&mut engine_process_1, "quit", &logfile);
-    log("finished Matt Magie", &logfile);
-    std::process::exit(0);
+
+    draw_live(live, pgn, clock, engines, white_index, &game.board, &moves, true);
+
+    // only saved games get a record, their PGN has a result
+    if let Some(json_path) = &settings.json_path && !pgn.result.is_empty() {
+        let pids = [engines[white_index].id(), engines[black_index].id()];
+        GameRecord::new(pgn, pids, start_fen, service.fen.get_fen(&game.board), move_records).save(json_path, &settings.logfile);
+    }
+
+    // an engine still searching (its opponent crashed) or pondering must not play into the next game
+    let (result, termination) = game_result(&game.board.game_status);
+    for engine in engines.iter_mut() {
+        engine.stop_search(logfile);
+        if game.board.game_status != GameStatus::Normal {
+            let commands = engine.protocol.game_over(result, &termination);
+            engine.send_all(commands, logfile);
+        }
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_live(live: &mut LiveView, pgn: &Pgn, clock: &Clock, engines: &[EngineProcess; 2], white_index: usize, board: &Board, moves: &[PlayedMove], force: bool) {
+    live.draw(&LiveFrame {
+        round: &pgn.round,
+        names: [&pgn.white, &pgn.black],
+        clocks: clock.remaining(),
+        infos: [&engines[white_index].info, &engines[1 - white_index].info],
+        board,
+        last_move: moves.last().map(|played| played.uci.as_str()),
+        ply: moves.len(),
+    }, force);
+}
+
+/// A coordinate move like e2e4 or e7e8q, null moves are no answer.
+fn is_move_notation(mv: &str) -> bool {
+    (4..=5).contains(&mv.len()) && mv.is_ascii() && mv != "0000"
 }
 
-fn parse_option(opt: &str) -> Option<(String, String)> {
+/// The move for the PGN movetext, played on `board`: castling as O-O, a promotion with its piece like `a7a8=N`.
+fn long_algebraic(best_move: &str, castling: Option<&str>, board: &Board) -> String {
+    match (castling, best_move.chars().nth(4)) {
+        (Some(castling), _) => castling.to_string(),
+        (None, Some(piece)) => format!("{}={}", &best_move[..4], piece.to_ascii_uppercase()),
+        (None, None) => NotationUtil::get_long_algebraic(&best_move[..4], board),
+    }
+}
+
+/// The coordinate move of the engine's answer if it is legal in the position, CECP engines may write castling as O-O.
+fn legal_move(board: &mut Board, service: &Service, best_move: &str) -> Option<String> {
+    let best_move = board.get_castling_notation(best_move).unwrap_or_else(|| best_move.to_string());
+    service.move_gen.generate_valid_moves_list(board).iter()
+        .map(|turn| turn.to_algebraic())
+        .find(|legal| *legal == best_move)
+}
+
+/// Joins the registry options of an engine with the options given for the game, the latter win.
+fn merge_options(configured: &str, extra: &str) -> String {
+    [configured, extra].iter()
+        .filter(|opts| !opts.trim().is_empty())
+        .cloned()
+        .collect::<Vec<&str>>()
+        .join(",")
+}
+
+pub(crate) fn parse_option(opt: &str) -> Option<(String, String)> {
     let opt = opt.trim();
     if opt.is_empty() {
         return None;
@@ -360,6 +740,63 @@ fn parse_option(opt: &str) -> Option<(String, String)> {
     None
 }
 
+
+fn check_game_over(game: &mut UciGame,
+    tx_clock: &mpsc::Sender<TimeControl>, logfile: &str, pgn: &mut Pgn, all_moves_long_algebraic: &str, service: &Service) -> bool {
+    let plies = game.pty;
+    let made_moves = game.made_moves_str.clone();
+    let board = &mut game.board;
+
+    if board.move_count > 100 && board.game_status == GameStatus::Normal {
+        board.game_status = GameStatus::Draw;
+    }
+
+    if board.game_status != GameStatus::Normal {
+        log::debug("Game status != Normal", logfile);
+        tx_clock.send(TimeControl::AllStop).unwrap();
+        log::info(&format!("{:?} {}", board.game_status, service.fen.get_fen(board)), logfile);
+        pgn.set_moves(all_moves_long_algebraic.to_string());
+        pgn.set_ply_count(plies.to_string());
+        // Chess960 games do not start from the positions of the ECO table
+        if pgn.variant.is_empty() {
+            let moves: Vec<String> = made_moves.split_whitespace().map(str::to_string).collect();
+            if let Some(opening) = eco::EcoTable::bundled().classify(&moves) {
+                pgn.set_opening(&opening.eco, &opening.name);
+            }
+        }
+
+        let (result, termination) = game_result(&board.game_status);
+        pgn.set_termination(&termination);
+        pgn.set_result(String::from(result));
+        pgn.save(logfile);
+        true
+    } else {
+        false
+    }
+}
+
+
+/// PGN result and termination of a finished game.
+fn game_result(state: &GameStatus) -> (&'static str, String) {
+    let result = match state {
+        GameStatus::WhiteWin | GameStatus::WhiteWinByTime | GameStatus::WhiteWinByStall | GameStatus::WhiteWinByResignation
+            | GameStatus::WhiteWinByMemoryLimit | GameStatus::WhiteWinByIllegalMove => "1-0",
+        GameStatus::BlackWin | GameStatus::BlackWinByTime | GameStatus::BlackWinByStall | GameStatus::BlackWinByResignation
+            | GameStatus::BlackWinByMemoryLimit | GameStatus::BlackWinByIllegalMove => "0-1",
+        GameStatus::Unfinished => "*",
+        _ => "1/2-1/2",
+    };
+    let termination = match state {
+        GameStatus::WhiteWinByStall | GameStatus::BlackWinByStall => "stalled connection".to_string(),
+        GameStatus::WhiteWinByMemoryLimit | GameStatus::BlackWinByMemoryLimit => "memory limit exceeded".to_string(),
+        GameStatus::WhiteWinByIllegalMove | GameStatus::BlackWinByIllegalMove => "illegal move".to_string(),
+        GameStatus::Unfinished => "unterminated".to_string(),
+        _ => format!("{:?}", state),
+    };
+    (result, termination)
+}
+
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -373,47 +810,56 @@ mod tests {
         assert_eq!(parse_option("="), None);
         assert_eq!(parse_option(""), None);
     }
-}
 
+    #[test]
+    fn test_is_move_notation() {
+        assert!(is_move_notation("e2e4"));
+        assert!(is_move_notation("e7e8q"));
+        assert!(!is_move_notation("(none)"));
+        assert!(!is_move_notation("0000"));
+        assert!(!is_move_notation(""));
+    }
 
-fn check_game_over(board: &mut Board,
-    tx_clock: &mpsc::Sender<TimeControl>, logfile: &str, pgn: &mut Pgn, all_moves_long_algebraic: &str, service: &Service) -> bool {
-
-    if board.move_count > 100 {
-        board.game_status = GameStatus::Draw;
+    #[test]
+    fn test_legal_move() {
+        let service = Service::new();
+        let mut board = service.fen.set_fen("r3k2r/1P6/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        assert_eq!(legal_move(&mut board, &service, "e1g1"), Some("e1g1".to_string()));
+        assert_eq!(legal_move(&mut board, &service, "O-O-O"), Some("e1c1".to_string()));
+        assert_eq!(legal_move(&mut board, &service, "b7a8n"), Some("b7a8n".to_string()));
+
+        // malformed
+        assert_eq!(legal_move(&mut board, &service, "a9a1"), None);
+        assert_eq!(legal_move(&mut board, &service, "(none)"), None);
+        // illegal
+        assert_eq!(legal_move(&mut board, &service, "e2e5"), None);
+        assert_eq!(legal_move(&mut board, &service, "e1e3"), None);
+        assert_eq!(game_result(&GameStatus::BlackWinByIllegalMove), ("0-1", "illegal move".to_string()));
+
+        // an underpromotion is played and the game goes on
+        let mut game = UciGame::new(service.fen.set_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1"));
+        assert_eq!(legal_move(&mut game.board, &service, "a7a8r"), Some("a7a8r".to_string()));
+        game.do_move("a7a8r");
+        assert_eq!(game.board.field[21], 11);
+        assert_eq!(game.board.game_status, GameStatus::Normal);
+        assert!(!service.move_gen.generate_valid_moves_list(&mut game.board).is_empty());
     }
 
-    if board.game_status != GameStatus::Normal {
-        log("Game status != Normal", logfile);
-        tx_clock.send(TimeControl::AllStop).unwrap();
-        log(&format!("{:?} {}", board.game_status, service.fen.get_fen(&board)), logfile);
-        pgn.set_moves(all_moves_long_algebraic.to_string());
-        pgn.set_ply_count(format!("{}", board.move_count));
+    #[test]
+    fn test_promotion_keeps_its_piece() {
+        let service = Service::new();
+        let mut game = UciGame::new(service.fen.set_fen("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1"));
+        game.do_move("a7b8n");
+        assert_eq!(long_algebraic("a7b8n", None, &game.board), "a7b8=N");
+        game.do_move("e8d7");
+        game.do_move("b8c6");
+        assert_eq!(long_algebraic("b8c6", None, &game.board), "Nb8c6");
+    }
 
-        let state = board.game_status.clone();
-        let result = match state {
-            GameStatus::WhiteWin | GameStatus::WhiteWinByTime => "1-0",
-            GameStatus::BlackWin | GameStatus::BlackWinByTime => "0-1",
-            _ => "1/2-1/2",
-        };
-        pgn.set_termination(&format!("{:?}", state));
-        pgn.set_result(String::from(result));
-        pgn.save();
-        true
-    } else {
-        false
+    #[test]
+    fn test_merge_options() {
+        assert_eq!(merge_options("Hash=128, Threads=1", "Hash=16"), "Hash=128, Threads=1,Hash=16");
+        assert_eq!(merge_options("", "Hash=16"), "Hash=16");
+        assert_eq!(merge_options("Hash=128", " "), "Hash=128");
     }
 }
-
-
-
-fn send(engine: &mut Child, command: &str, logfile: &str) {
-    let command_with_newline = format!("{}\n", command);
-    let stdin = engine.stdin.as_mut().expect("Failed");
-    stdin.write_all(&command_with_newline.as_bytes())
-        .unwrap_or_else(|err| {
-            eprintln!("Failed to write to stdin Command ->: {} - {}", command, err);
-        });
-    stdin.flush().unwrap();
-    log(&format!("mat\t->  {}\t{}", engine.id(), command), logfile);
-}
\ No newline at end of file
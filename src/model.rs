@@ -10,7 +10,16 @@ pub enum GameStatus {
     WhiteWin,
     BlackWin,
     WhiteWinByTime,
-    BlackWinByTime
+    BlackWinByTime,
+    WhiteWinByStall,
+    BlackWinByStall,
+    WhiteWinByResignation,
+    BlackWinByResignation,
+    WhiteWinByMemoryLimit,
+    BlackWinByMemoryLimit,
+    WhiteWinByIllegalMove,
+    BlackWinByIllegalMove,
+    Unfinished,  // stopped by SIGINT or SIGTERM
 }
 
 
@@ -90,7 +99,6 @@ impl Turn {
     }
 
     // Constructor with only 'from' and 'to' fields
-    #[allow(dead_code)]
     pub fn from_to(from: i32, to: i32) -> Self {
         Turn {
             from,
@@ -114,7 +122,6 @@ impl Turn {
         self
     }
 
-    #[allow(dead_code)]
     pub fn to_algebraic(&self) -> String {
         let column_from = (self.from % 10 + 96) as u8;
         let row_from = (10 - (self.from / 10) + 48) as u8;
@@ -122,7 +129,12 @@ impl Turn {
         let row_to = (10 - (self.to / 10) + 48) as u8;
         let mut promotional_lit = "";
         if self.promotion != 0 {
-            promotional_lit = if self.promotion % 10 == 4 { "q" } else { "k" };
+            promotional_lit = match self.promotion % 10 {
+                1 => "r",
+                2 => "n",
+                3 => "b",
+                _ => "q",
+            };
         }
         format!(
             "{}{}{}{}{}",
@@ -138,15 +150,17 @@ pub struct MoveInformation {
     pub castle_information: CastleInformation,
     pub hash: u64,
     pub en_passante: i32,
+    pub castle_rook: i32,  // origin of the castling rook, 0 if the move was no castling
 }
 
 impl MoveInformation {
     // Constructor
-    pub fn new(castle_information: CastleInformation, hash: u64, en_passante: i32) -> Self {
+    pub fn new(castle_information: CastleInformation, hash: u64, en_passante: i32, castle_rook: i32) -> Self {
         MoveInformation {
             castle_information,
             hash,
             en_passante,
+            castle_rook,
         }
     }
 }
@@ -188,10 +202,13 @@ pub struct Board {
     pub game_status: GameStatus,
     pub move_repetition_map: HashMap<u64, i32>,
     pub zobrist: ZobristTable,
+    pub chess960: bool,  // castling is encoded as king-takes-rook (UCI_Chess960)
+    pub castle_rook_squares: [i32; 4],  // rook origins: white long, white short, black long, black short
 }
 
 impl Board {
     // Constructor
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         field: [i32; 120],
         white_possible_to_castle_long: bool,
@@ -215,6 +232,8 @@ impl Board {
             game_status: GameStatus::Normal,
             move_repetition_map: HashMap::new(),
             zobrist,
+            chess960: false,
+            castle_rook_squares: [91, 98, 21, 28],
         }
     }
 
@@ -229,6 +248,7 @@ impl Board {
         
         let old_castle_information = self.get_castle_information();
         let old_field_for_en_passante = self.field_for_en_passante;
+        let castling = self.get_castling_squares(turn);
 
         // Handling en passante information
         self.field_for_en_passante = -1;
@@ -239,60 +259,49 @@ impl Board {
             self.field_for_en_passante = base + (turn.from % 10);
         }
 
-        // Handling castling for white and black
-        if self.field[turn.from as usize] == 15 || self.field[turn.from as usize] == 25 {
-            match (turn.from, turn.to) {
-                (25, 27) => {
-                    self.field[28] = 0;
-                    self.field[26] = 21;
-                }
-                (25, 23) => {
-                    self.field[21] = 0;
-                    self.field[24] = 21;
-                }
-                (95, 97) => {
-                    self.field[98] = 0;
-                    self.field[96] = 11;
-                }
-                (95, 93) => {
-                    self.field[91] = 0;
-                    self.field[94] = 11;
-                }
-                _ => {}
+        // Update castling rights, a king move or a move from/to a castling rook square loses them
+        match self.field[turn.from as usize] {
+            15 => {
+                self.white_possible_to_castle_long = false;
+                self.white_possible_to_castle_short = false;
             }
-        }
-
-        // Update castling rights
-        match turn.from {
-            21 => self.black_possible_to_castle_long = false,
-            28 => self.black_possible_to_castle_short = false,
             25 => {
                 self.black_possible_to_castle_long = false;
                 self.black_possible_to_castle_short = false;
             }
-            91 => self.white_possible_to_castle_long = false,
-            98 => self.white_possible_to_castle_short = false,
-            95 => {
-                self.white_possible_to_castle_long = false;
-                self.white_possible_to_castle_short = false;
-            }
             _ => {}
         }
+        for square in [turn.from, turn.to] {
+            if square == self.castle_rook_squares[0] { self.white_possible_to_castle_long = false; }
+            if square == self.castle_rook_squares[1] { self.white_possible_to_castle_short = false; }
+            if square == self.castle_rook_squares[2] { self.black_possible_to_castle_long = false; }
+            if square == self.castle_rook_squares[3] { self.black_possible_to_castle_short = false; }
+        }
 
-        // Handle promotion
-        if turn.is_promotion() {
-            self.field[turn.to as usize] = turn.promotion;
+        if let Some((king_to, rook_from, rook_to)) = castling {
+            // Handling castling for white and black, king and rook may land on each others origin
+            let king = self.field[turn.from as usize];
+            let rook = self.field[rook_from as usize];
+            self.field[turn.from as usize] = 0;
+            self.field[rook_from as usize] = 0;
+            self.field[rook_to as usize] = rook;
+            self.field[king_to as usize] = king;
         } else {
-            self.field[turn.to as usize] = self.field[turn.from as usize];
-        }
+            // Handle promotion
+            if turn.is_promotion() {
+                self.field[turn.to as usize] = turn.promotion;
+            } else {
+                self.field[turn.to as usize] = self.field[turn.from as usize];
+            }
 
-        self.field[turn.from as usize] = 0;
+            self.field[turn.from as usize] = 0;
 
-        // Handle en passante
-        if old_field_for_en_passante == turn.to && self.field[turn.to as usize] == 10 {
-            self.field[(turn.to + 10) as usize] = 0;
-        } else if old_field_for_en_passante == turn.to && self.field[turn.to as usize] == 20 {
-            self.field[(turn.to - 10) as usize] = 0;
+            // Handle en passante
+            if old_field_for_en_passante == turn.to && self.field[turn.to as usize] == 10 {
+                self.field[(turn.to + 10) as usize] = 0;
+            } else if old_field_for_en_passante == turn.to && self.field[turn.to as usize] == 20 {
+                self.field[(turn.to - 10) as usize] = 0;
+            }
         }
 
         // Increment move count if it's black's turn
@@ -309,12 +318,12 @@ impl Board {
             .or_insert(1);
 
         // Check for 3-move repetition
-        if let Some(&count) = self.move_repetition_map.get(&board_hash) {
-            if count == 3 {
-                self.game_status = GameStatus::Draw;
-            }
+        if let Some(&count) = self.move_repetition_map.get(&board_hash)
+            && count == 3 {
+            self.game_status = GameStatus::Draw;
         }
-        MoveInformation::new(old_castle_information, board_hash, old_field_for_en_passante)
+        let castle_rook = castling.map_or(0, |(_, rook_from, _)| rook_from);
+        MoveInformation::new(old_castle_information, board_hash, old_field_for_en_passante, castle_rook)
     }
 
 
@@ -322,39 +331,52 @@ impl Board {
     pub fn undo_move(&mut self, turn: &Turn, move_information: MoveInformation) {
 
         // validation
-        if self.field[turn.to as usize] == 0 {
+        if move_information.castle_rook == 0 && self.field[turn.to as usize] == 0 {
             panic!("undo_move(): Field on turn.to is 0\n{:?}", turn);
         }
 
         self.game_status = GameStatus::Normal;
 
         let castle_information = move_information.castle_information;
-        let mut is_en_passante_move = false;
-
-        // Handle en passante undo
-        if turn.to == move_information.en_passante && self.field[turn.to as usize] == 10 {
-            self.field[(turn.to + 10) as usize] = 20;
-            is_en_passante_move = true;
-        } else if turn.to == move_information.en_passante && self.field[turn.to as usize] == 20 {
-            self.field[(turn.to - 10) as usize] = 10;
-            is_en_passante_move = true;
-        }
 
-        // Handle promotion undo
-        if turn.is_promotion() {
-            self.field[turn.from as usize] = 10; // Reset to pawn
-            if self.white_to_move {
-                self.field[turn.from as usize] += 10; // Black pawn for black promotion
-            }
+        if move_information.castle_rook != 0 {
+            // Handle castling undo
+            let rook_from = move_information.castle_rook;
+            let (king_to, rook_to) = Board::get_castling_targets(turn.from, rook_from);
+            let king = self.field[king_to as usize];
+            let rook = self.field[rook_to as usize];
+            self.field[king_to as usize] = 0;
+            self.field[rook_to as usize] = 0;
+            self.field[rook_from as usize] = rook;
+            self.field[turn.from as usize] = king;
         } else {
-            self.field[turn.from as usize] = self.field[turn.to as usize];
-        }
+            let mut is_en_passante_move = false;
+
+            // Handle en passante undo
+            if turn.to == move_information.en_passante && self.field[turn.to as usize] == 10 {
+                self.field[(turn.to + 10) as usize] = 20;
+                is_en_passante_move = true;
+            } else if turn.to == move_information.en_passante && self.field[turn.to as usize] == 20 {
+                self.field[(turn.to - 10) as usize] = 10;
+                is_en_passante_move = true;
+            }
 
-        if is_en_passante_move {
-            self.field[turn.to as usize] = 0;
-        } else {
-            self.field[turn.to as usize] = turn.capture.max(0);
-        }        
+            // Handle promotion undo
+            if turn.is_promotion() {
+                self.field[turn.from as usize] = 10; // Reset to pawn
+                if self.white_to_move {
+                    self.field[turn.from as usize] += 10; // Black pawn for black promotion
+                }
+            } else {
+                self.field[turn.from as usize] = self.field[turn.to as usize];
+            }
+
+            if is_en_passante_move {
+                self.field[turn.to as usize] = 0;
+            } else {
+                self.field[turn.to as usize] = turn.capture.max(0);
+            }
+        }
 
         // Restore castling rights and en passante information
         self.white_possible_to_castle_long = castle_information.white_possible_to_castle_long;
@@ -363,29 +385,6 @@ impl Board {
         self.black_possible_to_castle_short = castle_information.black_possible_to_castle_short;
         self.field_for_en_passante = move_information.en_passante;
 
-        // Handle castling undo
-        if self.field[turn.from as usize] == 15 || self.field[turn.from as usize] == 25 {
-            match (turn.from, turn.to) {
-                (25, 27) => {
-                    self.field[28] = 21;
-                    self.field[26] = 0;
-                }
-                (25, 23) => {
-                    self.field[21] = 21;
-                    self.field[24] = 0;
-                }
-                (95, 97) => {
-                    self.field[98] = 11;
-                    self.field[96] = 0;
-                }
-                (95, 93) => {
-                    self.field[91] = 11;
-                    self.field[94] = 0;
-                }
-                _ => {}
-            }
-        }
-
         // Decrement move count if it was white's move
         if self.white_to_move {
             self.move_count -= 1;
@@ -412,9 +411,53 @@ impl Board {
         }
     }
 
+    /// Returns (king target, rook origin, rook target) if the turn is a castling move.
+    /// Castling is a king move onto an own rook, standard boards also accept the two-square king move.
+    pub fn get_castling_squares(&self, turn: &Turn) -> Option<(i32, i32, i32)> {
+        let (own_rook, long_rook, short_rook) = match self.field[turn.from as usize] {
+            15 => (11, self.castle_rook_squares[0], self.castle_rook_squares[1]),
+            25 => (21, self.castle_rook_squares[2], self.castle_rook_squares[3]),
+            _ => return None,
+        };
+        if turn.from / 10 != turn.to / 10 {
+            return None;
+        }
+
+        let rook_from = if self.field[turn.to as usize] == own_rook {
+            turn.to
+        } else if !self.chess960 && (turn.to - turn.from).abs() == 2 {
+            if turn.to < turn.from { long_rook } else { short_rook }
+        } else {
+            return None;
+        };
+        let (king_to, rook_to) = Board::get_castling_targets(turn.from, rook_from);
+        Some((king_to, rook_from, rook_to))
+    }
+
+    /// Target squares (king, rook) of a castling move, the king ends on the g- or c-file like in standard chess.
+    pub fn get_castling_targets(king_from: i32, rook_from: i32) -> (i32, i32) {
+        let rank = king_from / 10 * 10;
+        if rook_from > king_from { (rank + 7, rank + 6) } else { (rank + 3, rank + 4) }
+    }
+
+    /// Coordinate notation of an `O-O` or `O-O-O` of the side to move, chess960 castling is written king takes rook.
+    pub fn get_castling_notation(&self, castling: &str) -> Option<String> {
+        let (king, rook_index) = match (self.white_to_move, castling) {
+            (true, "O-O-O") => (15, 0),
+            (true, "O-O") => (15, 1),
+            (false, "O-O-O") => (25, 2),
+            (false, "O-O") => (25, 3),
+            _ => return None,
+        };
+        let king_from = (21..99).find(|square| self.field[*square as usize] == king)?;
+        let rook_from = self.castle_rook_squares[rook_index];
+        let to = if self.chess960 { rook_from } else { Board::get_castling_targets(king_from, rook_from).0 };
+        Some(Turn::from_to(king_from, to).to_algebraic())
+    }
+
     // Hash function for the board (used for 3-move repetition)
     pub fn hash(&self) -> u64 {
-        self.zobrist.gen_hash(&self)
+        self.zobrist.gen_hash(self)
     }
 }
 
@@ -431,6 +474,7 @@ impl PartialEq for Board {
             self.move_count == other.move_count &&
             self.game_status == other.game_status &&
             self.field == other.field &&  // Direct comparison of arrays (fixed-size arrays implement PartialEq)
+            self.castle_rook_squares == other.castle_rook_squares &&
             self.move_repetition_map == other.move_repetition_map  // HashMap comparison
     }
 }
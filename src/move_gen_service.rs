@@ -31,10 +31,12 @@ impl MoveGenService {
             let mut move_turn = Turn::new(idx0, idx1, board.field[idx1 as usize], 0, 0, false);
     
             // Check for castling
-            if board.field[idx0 as usize] == king_value && (idx1 == idx0 + 2 || idx1 == idx0 - 2) {
-                if !self.is_valid_castling(board, white_turn, idx1) {
+            if board.field[idx0 as usize] == king_value
+                && let Some((_, rook_from, _)) = board.get_castling_squares(&move_turn) {
+                if !self.is_valid_castling(board, white_turn, idx0, rook_from) {
                     continue;
                 }
+                move_turn.capture = 0;
             }
     
             // Check for promotion
@@ -55,14 +57,14 @@ impl MoveGenService {
         }
     
         if white_turn {
-            valid_moves.sort_unstable_by(|a, b| b.eval.cmp(&a.eval));
+            valid_moves.sort_unstable_by_key(|a| std::cmp::Reverse(a.eval));
         } else {
-            valid_moves.sort_unstable_by(|a, b| a.eval.cmp(&b.eval));
+            valid_moves.sort_unstable_by_key(|a| a.eval);
         }
     
         // check Gamestatus
         if valid_moves.is_empty() {
-            if self.get_check_idx_list(&board.field, board.white_to_move).len() > 0 {
+            if !self.get_check_idx_list(&board.field, board.white_to_move).is_empty() {
                 board.game_status = if board.white_to_move { GameStatus::BlackWin } else { GameStatus::WhiteWin }
             } else {
                 board.game_status = GameStatus::Draw;
@@ -101,7 +103,7 @@ impl MoveGenService {
         if valid {
     
             // check if the move gives opponent check
-            if self.get_check_idx_list(&board.field, !white_turn).len() > 0 {
+            if !self.get_check_idx_list(&board.field, !white_turn).is_empty() {
                 turn.gives_check = true;
             }
             valid_moves.push(turn.clone());
@@ -110,7 +112,7 @@ impl MoveGenService {
     }
     
     fn validate_and_add_promotion_moves(&self, board: &mut Board, turn: &mut Turn, valid_moves: &mut Vec<Turn>, white_turn: bool) {
-        let promotion_types = if white_turn { [12, 14] } else { [22, 24] }; // Knight and Queen promotions for white and black
+        let promotion_types = if white_turn { [11, 12, 13, 14] } else { [21, 22, 23, 24] }; // Rook, Knight, Bishop and Queen promotions for white and black
         for &promotion in &promotion_types {
             turn.promotion = promotion;
             self.validate_and_add_move(board, turn, valid_moves, white_turn);
@@ -119,44 +121,46 @@ impl MoveGenService {
 
     
 
-    fn is_valid_castling(&self, board: &Board, white_turn: bool, target: i32) -> bool {
-        let check_squares = if white_turn {
-            if target == 97 { vec![96, 97] } else { vec![94, 93] }
-        } else {
-            if target == 27 { vec![26, 27] } else { vec![24, 23] }
+    fn is_valid_castling(&self, board: &Board, white_turn: bool, king_from: i32, rook_from: i32) -> bool {
+        let short = rook_from > king_from;
+
+        // Check if castling is allowed
+        let (possible, castle_rook) = match (white_turn, short) {
+            (true, true) => (board.white_possible_to_castle_short, board.castle_rook_squares[1]),
+            (true, false) => (board.white_possible_to_castle_long, board.castle_rook_squares[0]),
+            (false, true) => (board.black_possible_to_castle_short, board.castle_rook_squares[3]),
+            (false, false) => (board.black_possible_to_castle_long, board.castle_rook_squares[2]),
         };
+        if !possible || castle_rook != rook_from {
+            return false;
+        }
 
         // Check if the king is currently in check
         if !self.get_check_idx_list(&board.field, white_turn).is_empty() {
             return false;
         }
 
-        // Check if the king would pass through check squares
-        for &square in &check_squares {
+        // Check if the king would pass through check squares (the target is checked after the move)
+        let (king_to, _) = Board::get_castling_targets(king_from, rook_from);
+        let step = if king_to > king_from { 1 } else { -1 };
+        let mut square = king_from;
+        while square != king_to {
+            square += step;
             if !self.get_attack_idx_list(&board.field, white_turn, square).is_empty() {
                 return false;
             }
         }
-
-        // Check if castling is allowed
-        if white_turn {
-            if target == 97 && !board.white_possible_to_castle_short {
-                return false;
-            }
-            if target == 93 && !board.white_possible_to_castle_long {
-                return false;
-            }
-        } else {
-            if target == 27 && !board.black_possible_to_castle_short {
-                return false;
-            }
-            if target == 23 && !board.black_possible_to_castle_long {
-                return false;
-            }
-        }
         true
     }
 
+    /// All squares between king, rook and their targets have to be empty, besides king and rook themselves.
+    fn is_castling_path_free(&self, field: &[i32], king_from: i32, rook_from: i32) -> bool {
+        let (king_to, rook_to) = Board::get_castling_targets(king_from, rook_from);
+        let first = king_from.min(king_to).min(rook_from).min(rook_to);
+        let last = king_from.max(king_to).max(rook_from).max(rook_to);
+        (first..=last).all(|square| square == king_from || square == rook_from || field[square as usize] == 0)
+    }
+
     fn get_promotion_move(&self, board: &Board, white_turn: bool, idx0: i32, idx1: i32) -> Option<Turn> {
         if white_turn && idx0 / 10 == 3 && board.field[idx0 as usize] == 10 {
             Some(Turn {
@@ -236,23 +240,19 @@ impl MoveGenService {
                     }
                 }
 
-                // Castling moves for White and Black
-                if field[i as usize] == king_value {
-                    if i == 95 && field[96] == 0 && field[97] == 0 && field[98] == 11 {
-                        moves.push(95);
-                        moves.push(97);
-                    }
-                    if i == 25 && field[26] == 0 && field[27] == 0 && field[28] == 21 {
-                        moves.push(25);
-                        moves.push(27);
-                    }
-                    if i == 95 && field[94] == 0 && field[93] == 0 && field[92] == 0 && field[91] == 11 {
-                        moves.push(95);
-                        moves.push(93);
-                    }
-                    if i == 25 && field[24] == 0 && field[23] == 0 && field[22] == 0 && field[21] == 21 {
-                        moves.push(25);
-                        moves.push(23);
+                // Castling moves for White and Black, encoded as king-takes-rook on chess960 boards
+                let (long_rook, short_rook) = if white {
+                    (board.castle_rook_squares[0], board.castle_rook_squares[1])
+                } else {
+                    (board.castle_rook_squares[2], board.castle_rook_squares[3])
+                };
+                for rook_from in [short_rook, long_rook] {
+                    let (king_to, _) = Board::get_castling_targets(i, rook_from);
+                    if field[rook_from as usize] == rook_value && rook_from / 10 == i / 10
+                        && (board.chess960 || (king_to - i).abs() == 2)
+                        && self.is_castling_path_free(&field, i, rook_from) {
+                        moves.push(i);
+                        moves.push(if board.chess960 { rook_from } else { king_to });
                     }
                 }
             }
@@ -263,7 +263,7 @@ impl MoveGenService {
                     if field[(i - 10) as usize] == 0 {
                         moves.push(i);
                         moves.push(i - 10);
-                        if i >= 81 && i <= 88 && field[(i - 20) as usize] == 0 {
+                        if (81..=88).contains(&i) && field[(i - 20) as usize] == 0 {
                             moves.push(i);
                             moves.push(i - 20);
                         }
@@ -280,7 +280,7 @@ impl MoveGenService {
                     if field[(i + 10) as usize] == 0 {
                         moves.push(i);
                         moves.push(i + 10);
-                        if i >= 31 && i <= 38 && field[(i + 20) as usize] == 0 {
+                        if (31..=38).contains(&i) && field[(i + 20) as usize] == 0 {
                             moves.push(i);
                             moves.push(i + 20);
                         }
@@ -438,11 +438,11 @@ impl MoveGenService {
         let mut white_king_pos = -1;
         let mut black_king_pos = -1;
 
-        for i in 21..99 {
-            if field[i] == 15 {
+        for (i, &piece) in field.iter().enumerate().take(99).skip(21) {
+            if piece == 15 {
                 white_king_pos = i as i32;
             }
-            if field[i] == 25 {
+            if piece == 25 {
                 black_king_pos = i as i32;
             }
         }
@@ -543,4 +543,60 @@ mod tests {
 
         assert_eq!(board.game_status, GameStatus::Draw);
     }
+
+    #[test]
+    fn test_chess960_castling() {
+        let service = Service::new();
+        // king on b1 with rooks on a1 and e1, castling long keeps the king on c1 and the rook on d1
+        let mut board = service.fen.set_fen("1r2k1r1/pppppppp/8/8/8/8/PPPPPPPP/RK2R3 w KQ - 0 1");
+        board.chess960 = true;
+
+        let moves = service.move_gen.generate_valid_moves_list(&mut board);
+        let short = NotationUtil::get_turn_from_list(&moves, "b1e1");
+        let long = NotationUtil::get_turn_from_list(&moves, "b1a1");
+
+        let board_before = board.clone();
+        let move_info = board.do_move(&short);
+        assert_eq!(board.field[97], 15);
+        assert_eq!(board.field[96], 11);
+        assert_eq!(board.field[92], 0);
+        assert_eq!(board.field[95], 0);
+        assert!(!board.white_possible_to_castle_long && !board.white_possible_to_castle_short);
+        board.undo_move(&short, move_info);
+        assert_eq!(board, board_before);
+
+        let move_info = board.do_move(&long);
+        assert_eq!(board.field[93], 15);
+        assert_eq!(board.field[94], 11);
+        assert_eq!(board.field[91], 0);
+        board.undo_move(&long, move_info);
+        assert_eq!(board, board_before);
+    }
+
+    #[test]
+    fn test_chess960_castling_through_check() {
+        let service = Service::new();
+        // the black rook on f8 attacks f1, which the king has to pass for castling short
+        let mut board = service.fen.set_fen("5r1k/8/8/8/8/8/8/RK2R3 w KQ - 0 1");
+        board.chess960 = true;
+
+        let moves = service.move_gen.generate_valid_moves_list(&mut board);
+        assert!(!moves.iter().any(|turn| turn.from == 92 && turn.to == 95));
+        assert!(moves.iter().any(|turn| turn.from == 92 && turn.to == 91));
+    }
+
+    #[test]
+    fn test_standard_castling_notation() {
+        let service = Service::new();
+        let mut board = service.fen.set_fen("r3k2r/pppppppp/8/8/8/8/PPPPPPPP/R3K2R w KQkq - 0 1");
+
+        let moves = service.move_gen.generate_valid_moves_list(&mut board);
+        let short = NotationUtil::get_turn_from_list(&moves, "e1g1");
+        NotationUtil::get_turn_from_list(&moves, "e1c1");
+
+        board.do_move(&short);
+        assert_eq!(board.field[97], 15);
+        assert_eq!(board.field[96], 11);
+        assert_eq!(service.fen.get_fen(&board), "r3k2r/pppppppp/8/8/8/8/PPPPPPPP/R4RK1 b kq - 0 1");
+    }
 }
@@ -36,22 +36,26 @@ impl NotationUtil {
 
         // Promotion logic for white
         if notation_move.len() == 5 && notation_move.chars().nth(3) == Some('8') {
-            promotion = match notation_move.chars().nth(4) {
-                Some('n') => 12,
-                _ => 14, // default to queen
-            };
+            promotion = 10 + NotationUtil::promotion_piece(notation_move.chars().nth(4));
         }
 
         // Promotion logic for black
         if notation_move.len() == 5 && notation_move.chars().nth(3) == Some('1') {
-            promotion = match notation_move.chars().nth(4) {
-                Some('n') => 22,
-                _ => 24, // default to queen
-            };
+            promotion = 20 + NotationUtil::promotion_piece(notation_move.chars().nth(4));
         }
         Turn::new(from, to, 0, promotion, 0, false)
     }
 
+    /// Piece of a promotion letter without its color, a queen for anything else.
+    fn promotion_piece(letter: Option<char>) -> i32 {
+        match letter.map(|letter| letter.to_ascii_lowercase()) {
+            Some('r') => 1,
+            Some('n') => 2,
+            Some('b') => 3,
+            _ => 4,
+        }
+    }
+
     /// Finds a specific move in the move list based on the notation.
     #[allow(dead_code)]
     pub fn get_turn_from_list(move_list: &Vec<Turn>, notation: &str) -> Turn {
@@ -60,15 +64,13 @@ impl NotationUtil {
         // Handle promotion
         if notation.len() == 5 {
             match notation.chars().nth(4) {
-                Some('q') => target_turn.promotion = 14,
-                Some('n') => target_turn.promotion = 12,
-                Some('Q') => target_turn.promotion = 14,
-                Some('N') => target_turn.promotion = 12,
+                Some('q' | 'n' | 'b' | 'r' | 'Q' | 'N' | 'B' | 'R') =>
+                    target_turn.promotion = 10 + NotationUtil::promotion_piece(notation.chars().nth(4)),
                 _ => panic!("Invalid promotion"),
             }
 
             if target_turn.to / 90 == 1 {
-                target_turn.promotion = target_turn.promotion + 10; // for black promotion
+                target_turn.promotion += 10; // for black promotion
             }
         }
 
@@ -97,6 +99,55 @@ impl NotationUtil {
         };
         format!("{}{}", figure_str, move_notation)
     }
+
+    /// Finds the legal move of a SAN move like `Nbd7`, `exd5`, `e8=Q+` or `O-O`, None if it is ambiguous.
+    pub fn get_turn_from_san(san: &str, board: &Board, legal_moves: &[Turn]) -> Option<Turn> {
+        let san = san.trim_end_matches(['+', '#', '!', '?']);
+        if let Some(castling) = board.get_castling_notation(&san.replace('0', "O")) {
+            return legal_moves.iter().find(|turn| turn.to_algebraic() == castling).cloned();
+        }
+
+        let (san, promotion) = match san.split_once('=') {
+            Some((san, piece)) => (san, Some(piece)),
+            None => (san, None),
+        };
+        let piece = match san.chars().next()? {
+            'R' => 1,
+            'N' => 2,
+            'B' => 3,
+            'Q' => 4,
+            'K' => 5,
+            _ => 0,
+        };
+        let squares: String = san.chars().skip(if piece == 0 { 0 } else { 1 }).filter(|c| *c != 'x').collect();
+        if squares.len() < 2 || !squares.is_ascii() {
+            return None;
+        }
+        let (disambiguation, target) = squares.split_at(squares.len() - 2);
+        let to = NotationUtil::get_index_from_notation_field(target);
+        let promotion = match promotion {
+            Some("R") => 1,
+            Some("N") => 2,
+            Some("B") => 3,
+            Some("Q") => 4,
+            Some(_) => return None,
+            None => 0,
+        };
+
+        let mut candidates = legal_moves.iter().filter(|turn| {
+            let from = turn.to_algebraic();
+            turn.to == to
+                && board.field[turn.from as usize] % 10 == piece
+                && turn.promotion % 10 == promotion
+                && board.get_castling_squares(turn).is_none()
+                && disambiguation.chars().all(|c| from[..2].contains(c))
+        });
+        let turn = candidates.next()?;
+        if candidates.next().is_some() {
+            return None;
+        }
+        Some(turn.clone())
+    }
 }
 
 #[cfg(test)]
@@ -123,6 +174,23 @@ mod tests {
         NotationUtil::get_turn_from_notation("z9z9");
     }
 
+    #[test]
+    fn test_get_turn_from_san() {
+        let service = crate::service::Service::new();
+        let mut board = service.fen.set_fen("r3k2r/1P6/8/8/8/8/8/R3K1NR w KQkq - 0 1");
+        let legal_moves = service.move_gen.generate_valid_moves_list(&mut board);
+        let uci = |san: &str| NotationUtil::get_turn_from_san(san, &board, &legal_moves).map(|turn| turn.to_algebraic());
+        assert_eq!(uci("Nf3"), Some("g1f3".to_string()));
+        assert_eq!(uci("bxa8=Q+"), Some("b7a8q".to_string()));
+        assert_eq!(uci("b8=N"), Some("b7b8n".to_string()));
+        assert_eq!(uci("b8=R"), Some("b7b8r".to_string()));
+        assert_eq!(uci("b8"), None);
+        assert_eq!(uci("O-O-O"), Some("e1c1".to_string()));
+        assert_eq!(uci("Rd1"), Some("a1d1".to_string()));
+        assert_eq!(uci("Nh3"), Some("g1h3".to_string()));
+        assert_eq!(uci("Ke3"), None);
+    }
+
     #[test]
     fn test_get_turn_from_notation() {
         let turn = NotationUtil::get_turn_from_notation("e2e4");
@@ -130,5 +198,14 @@ mod tests {
         assert_eq!(turn.to, 65);
         assert_eq!(turn.promotion, 0);
     }
+
+    #[test]
+    fn test_promotion_to_algebraic() {
+        assert_eq!(NotationUtil::get_turn_from_notation("b7b8n").to_algebraic(), "b7b8n");
+        assert_eq!(NotationUtil::get_turn_from_notation("b2b1n").to_algebraic(), "b2b1n");
+        assert_eq!(NotationUtil::get_turn_from_notation("b7b8q").to_algebraic(), "b7b8q");
+        assert_eq!(NotationUtil::get_turn_from_notation("b7b8r").to_algebraic(), "b7b8r");
+        assert_eq!(NotationUtil::get_turn_from_notation("b2b1b").to_algebraic(), "b2b1b");
+    }
 }
 
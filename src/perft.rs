@@ -0,0 +1,91 @@
+use std::time::Instant;
+
+use crate::model::{Board, GameStatus};
+use crate::service::Service;
+
+
+/// Number of leaf nodes of the legal move tree of `board` to `depth` plies.
+pub fn perft(service: &Service, board: &mut Board, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    // repetitions of the searched lines are no game end here
+    board.game_status = GameStatus::Normal;
+    let moves = service.move_gen.generate_valid_moves_list(board);
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+    let mut nodes = 0;
+    for turn in &moves {
+        let move_information = board.do_move(turn);
+        nodes += perft(service, board, depth - 1);
+        board.undo_move(turn, move_information);
+    }
+    nodes
+}
+
+/// Leaf nodes below every legal move of `board`, the moves in coordinate notation.
+pub fn divide(service: &Service, board: &mut Board, depth: u32) -> Vec<(String, u64)> {
+    board.game_status = GameStatus::Normal;
+    let moves = service.move_gen.generate_valid_moves_list(board);
+    moves.iter().map(|turn| {
+        let move_information = board.do_move(turn);
+        let nodes = perft(service, board, depth.saturating_sub(1));
+        board.undo_move(turn, move_information);
+        (turn.to_algebraic(), nodes)
+    }).collect()
+}
+
+/// Prints the perft of the position to `depth`, with `divide` the nodes below each move.
+pub fn print_perft(fen: Option<&str>, depth: u32, divide_moves: bool) {
+    let service = Service::new();
+    let mut board = match fen {
+        Some(fen) => service.fen.set_fen(fen),
+        None => service.fen.set_init_board(),
+    };
+    let start = Instant::now();
+    let nodes = if divide_moves {
+        let moves = divide(&service, &mut board, depth);
+        for (notation, nodes) in &moves {
+            println!("{}: {}", notation, nodes);
+        }
+        println!();
+        moves.iter().map(|(_, nodes)| nodes).sum()
+    } else {
+        perft(&service, &mut board, depth)
+    };
+    let elapsed = start.elapsed();
+    println!("perft {}: {} nodes in {} ms ({} nps)",
+        depth, nodes, elapsed.as_millis(), (nodes as f64 / elapsed.as_secs_f64().max(0.001)) as u64);
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_perft() {
+        let service = Service::new();
+        let mut board = service.fen.set_init_board();
+        assert_eq!(perft(&service, &mut board, 1), 20);
+        assert_eq!(perft(&service, &mut board, 3), 8902);
+
+        // castling, en passant and pins
+        let mut board = service.fen.set_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        assert_eq!(perft(&service, &mut board, 2), 2039);
+        let moves = divide(&service, &mut board, 1);
+        assert_eq!(moves.len(), 48);
+        assert!(moves.iter().all(|(_, nodes)| *nodes == 1));
+
+        // promotions to all four pieces, positions 4 and 5 of the chessprogramming wiki
+        let mut board = service.fen.set_fen("8/P7/8/8/8/8/8/k6K w - - 0 1");
+        assert_eq!(perft(&service, &mut board, 1), 7);
+        let moves: Vec<String> = divide(&service, &mut board, 1).into_iter().map(|(uci, _)| uci).filter(|uci| uci.len() == 5).collect();
+        assert_eq!(moves.len(), 4, "{:?}", moves);
+        let mut board = service.fen.set_fen("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1");
+        assert_eq!(perft(&service, &mut board, 3), 9467);
+        let mut board = service.fen.set_fen("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8");
+        assert_eq!(perft(&service, &mut board, 3), 62379);
+    }
+}
@@ -1,5 +1,118 @@
-use std::fs::OpenOptions;
-use std::io::Write;
+use chrono::{DateTime, Utc};
+
+use crate::engine_process::EngineCrash;
+use crate::locked_append;
+
+
+/// The tags besides the Seven Tag Roster that a game can have, in the order they are written.
+pub const OPTIONAL_TAGS: [&str; 12] = ["WhiteElo", "BlackElo", "ECO", "Opening", "TimeControl", "Time",
+    "UTCDate", "UTCTime", "PlyCount", "GameDuration", "Termination", "Annotator"];
+
+/// Optional tags written when the configuration does not remove them.
+pub const DEFAULT_TAGS: [&str; 6] = ["ECO", "Opening", "TimeControl", "Time", "PlyCount", "Termination"];
+
+/// Tags Matt-Magie fills in itself, they can not be set to a custom value.
+const RESERVED_TAGS: [&str; 10] = ["Event", "Site", "Date", "Round", "White", "Black", "Result", "Variant", "SetUp", "FEN"];
+
+const DEFAULT_ANNOTATOR: &str = "Matt-Magie";
+/// Stderr lines of a crashed engine in the comment of the game, the JSON record has more.
+const CRASH_COMMENT_LINES: usize = 5;
+
+
+/// Selection of the optional tags and the custom tags with their values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgnTags {
+    optional: Vec<String>,
+    custom: Vec<(String, String)>,
+}
+
+impl Default for PgnTags {
+    fn default() -> Self {
+        PgnTags { optional: DEFAULT_TAGS.iter().map(|name| name.to_string()).collect(), custom: Vec::new() }
+    }
+}
+
+impl PgnTags {
+    /// Parses a `;` separated list: `Name` adds an optional tag, `-Name` removes one of the defaults
+    /// and `Name=Value` sets a custom tag (or the value of the Annotator).
+    pub fn parse(spec: &str) -> Result<PgnTags, String> {
+        let mut tags = PgnTags::default();
+        for item in spec.split(';').map(str::trim).filter(|item| !item.is_empty()) {
+            if let Some((name, value)) = item.split_once('=') {
+                let name = name.trim();
+                if name.is_empty() || name.contains(|c: char| !c.is_ascii_alphanumeric() && c != '_') {
+                    return Err(format!("'{}' is no valid PGN tag name", name));
+                }
+                if RESERVED_TAGS.contains(&name) || (OPTIONAL_TAGS.contains(&name) && name != "Annotator") {
+                    return Err(format!("the PGN tag '{}' is set by Matt-Magie", name));
+                }
+                tags.custom.retain(|(custom, _)| custom != name);
+                tags.custom.push((name.to_string(), value.trim().to_string()));
+                if name == "Annotator" {
+                    tags.add("Annotator");
+                }
+                continue;
+            }
+            let (remove, name) = match item.strip_prefix('-') {
+                Some(name) => (true, name.trim()),
+                None => (false, item),
+            };
+            if !OPTIONAL_TAGS.contains(&name) {
+                return Err(format!("unknown optional PGN tag '{}', known are {}", name, OPTIONAL_TAGS.join(", ")));
+            }
+            if remove {
+                tags.optional.retain(|optional| optional != name);
+            } else {
+                tags.add(name);
+            }
+        }
+        Ok(tags)
+    }
+
+    fn add(&mut self, name: &str) {
+        if !self.includes(name) {
+            self.optional.push(name.to_string());
+        }
+    }
+
+    pub fn includes(&self, name: &str) -> bool {
+        self.optional.iter().any(|optional| optional == name)
+    }
+
+    fn custom_value(&self, name: &str) -> Option<&str> {
+        self.custom.iter().find(|(custom, _)| custom == name).map(|(_, value)| value.as_str())
+    }
+}
+
+/// Coordinate moves of a movetext written by `Pgn::set_moves`, up to the first move whose start square
+/// is not written (a promotion).
+pub fn coordinate_moves(movetext: &str) -> Vec<String> {
+    let mut moves = Vec::new();
+    for token in movetext.split_whitespace().filter(|token| !token.ends_with('.')) {
+        let white = moves.len() % 2 == 0;
+        let played = match token {
+            "O-O" => if white { "e1g1" } else { "e8g8" }.to_string(),
+            "O-O-O" => if white { "e1c1" } else { "e8c8" }.to_string(),
+            _ => token.trim_start_matches(['N', 'B', 'R', 'Q', 'K']).to_string(),
+        };
+        let square = |square: &[u8]| (b'a'..=b'h').contains(&square[0]) && (b'1'..=b'8').contains(&square[1]);
+        if played.len() != 4 || !square(&played.as_bytes()[..2]) || !square(&played.as_bytes()[2..]) {
+            break;
+        }
+        moves.push(played);
+    }
+    moves
+}
+
+/// The TimeControl tag value, seconds per game plus the increment in seconds, e.g. `30+1`.
+pub fn format_time_control(time_ms: i32, inc_ms: i32) -> String {
+    let seconds = |ms: i32| {
+        let text = format!("{:.3}", ms as f64 / 1000.0);
+        text.trim_end_matches('0').trim_end_matches('.').to_string()
+    };
+    format!("{}+{}", seconds(time_ms), seconds(inc_ms))
+}
+
 
 pub struct Pgn {
     pub(crate) event: String,
@@ -15,10 +128,19 @@ pub struct Pgn {
     pub(crate) time: String,
     pub(crate) moves: String,
     pub(crate) path: String,
+    pub(crate) variant: String,
+    pub(crate) fen: String,
+    pub(crate) eco: String,
+    pub(crate) opening: String,
+    pub(crate) elos: [Option<u32>; 2],
+    pub(crate) started: DateTime<Utc>,
+    pub(crate) tags: PgnTags,
+    pub(crate) crash: Option<EngineCrash>,
 }
 
 
 impl Pgn {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(event: String,
         site: String,
         date: String,
@@ -32,19 +154,27 @@ impl Pgn {
             ) -> Pgn {
 
         Pgn {
-            event: event,
-            site: site,
-            date: date,
-            round: round,
-            white: white,
-            black: black,
-            time: time,
-            time_control: time_control,
-            termination: termination,
+            event,
+            site,
+            date,
+            round,
+            white,
+            black,
+            time,
+            time_control,
+            termination,
             result: String::new(),
             ply_count: String::new(),
             moves: String::new(),
-            path: path,
+            path,
+            variant: String::new(),
+            fen: String::new(),
+            eco: String::new(),
+            opening: String::new(),
+            elos: [None, None],
+            started: Utc::now(),
+            tags: PgnTags::default(),
+            crash: None,
         }
     }
 
@@ -87,8 +217,8 @@ impl Pgn {
                 } else {
                     // Handling castling and other moves
                     match mv {
-                        "Ke1g1" | "Ke8g8" => "0-0".to_string(),
-                        "Ke1c1" | "Ke8c8" => "0-0-0".to_string(),
+                        "Ke1g1" | "Ke8g8" => "O-O".to_string(),
+                        "Ke1c1" | "Ke8c8" => "O-O-O".to_string(),
                         _ => mv.to_string(),
                     }
                 }
@@ -112,34 +242,154 @@ impl Pgn {
         self.termination = String::from(termination);
     }
 
-    pub fn save(&self) {
-        let content = format!("[Event \"{}\"]\n[Site \"{}\"]\n[Date \"{}\"]\n[Round \"{}\"]\n[White \"{}\"]\n[Black \"{}\"]\n[Result \"{}\"]\n[Termination \"{}\"]\n[TimeControl \"{}\"]\n[Time \"{}\"]\n{} {}\n\n",
-        self.event,
-        self.site,
-        self.date,
-        self.round,
-        self.white,
-        self.black,
-        self.result,
-        self.termination,
-        self.time_control,
-        self.time,
-        self.moves,        
-        self.result,
-        );
-
-        match OpenOptions::new()
-            .write(true)
-            .append(true)
-            .create(true)
-            .open(&self.path) {
-                Ok(mut file) => {
-                    match file.write_all(content.as_bytes()) {
-                        Ok(_) => (),
-                        Err(e) => println!("Error writing to file: {}", e),
-                    }
-                },
-                Err(e) => println!("Error opening file: {}", e),
+    /// Games not starting from the standard position need the Variant and the start FEN.
+    pub fn set_variant(&mut self, variant: &str, fen: &str) {
+        self.variant = String::from(variant);
+        self.fen = String::from(fen);
+    }
+
+    pub fn set_tags(&mut self, tags: PgnTags) {
+        self.tags = tags;
+    }
+
+    pub fn set_opening(&mut self, eco: &str, opening: &str) {
+        self.eco = eco.to_string();
+        self.opening = opening.to_string();
+    }
+
+    /// The engine that crashed during the game, noted in a comment before the result.
+    pub fn set_crash(&mut self, crash: EngineCrash) {
+        self.crash = Some(crash);
+    }
+
+    pub fn set_elos(&mut self, white_elo: Option<u32>, black_elo: Option<u32>) {
+        self.elos = [white_elo, black_elo];
+    }
+
+    /// Header tags in the order of the PGN standard: the Seven Tag Roster, the selected optional tags,
+    /// the tags of the start position and the custom tags.
+    pub fn header(&self) -> Vec<(String, String)> {
+        let mut header: Vec<(String, String)> = [
+            ("Event", &self.event), ("Site", &self.site), ("Date", &self.date), ("Round", &self.round),
+            ("White", &self.white), ("Black", &self.black), ("Result", &self.result),
+        ].iter().map(|(name, value)| (name.to_string(), value.to_string())).collect();
+
+        let now = Utc::now();
+        let duration = (now - self.started).num_seconds().max(0);
+        let elo = |elo: Option<u32>| elo.map_or("-".to_string(), |elo| elo.to_string());
+        for name in OPTIONAL_TAGS {
+            if !self.tags.includes(name) {
+                continue;
             }
+            let value = match name {
+                "WhiteElo" => elo(self.elos[0]),
+                "BlackElo" => elo(self.elos[1]),
+                "ECO" => self.eco.clone(),
+                "Opening" => self.opening.clone(),
+                "TimeControl" => self.time_control.clone(),
+                "Time" => self.time.clone(),
+                "UTCDate" => self.started.format("%Y.%m.%d").to_string(),
+                "UTCTime" => self.started.format("%H:%M:%S").to_string(),
+                "PlyCount" => self.ply_count.clone(),
+                "GameDuration" => format!("{:02}:{:02}:{:02}", duration / 3600, duration / 60 % 60, duration % 60),
+                "Termination" => self.termination.clone(),
+                "Annotator" => self.tags.custom_value(name).unwrap_or(DEFAULT_ANNOTATOR).to_string(),
+                _ => String::new(),
+            };
+            // classification and counts that are not known yet are left out
+            if !value.is_empty() {
+                header.push((name.to_string(), value));
+            }
+        }
+        if !self.variant.is_empty() {
+            header.push(("Variant".to_string(), self.variant.clone()));
+            header.push(("SetUp".to_string(), "1".to_string()));
+            header.push(("FEN".to_string(), self.fen.clone()));
+        }
+        header.extend(self.tags.custom.iter().filter(|(name, _)| !OPTIONAL_TAGS.contains(&name.as_str())).cloned());
+        header
+    }
+
+    pub fn save(&self, logfile: &str) {
+        let tags: String = self.header().iter()
+            .map(|(name, value)| format!("[{} \"{}\"]\n", name, value.replace('\\', "\\\\").replace('"', "\\\"")))
+            .collect();
+        // braces would end the comment early
+        let comment = self.crash.as_ref()
+            .map(|crash| format!(" {{{}}}", crash.summary(CRASH_COMMENT_LINES).replace(['{', '}'], "")))
+            .unwrap_or_default();
+        let content = format!("{}{}{} {}\n\n", tags, self.moves, comment, self.result);
+
+        if let Err(e) = locked_append::append_pgn(&self.path, &content, logfile) {
+            println!("Error writing to file: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_pgn(tags: &str) -> Pgn {
+        let mut pgn = Pgn::new("Test".to_string(), "local".to_string(), "2026.10.18".to_string(), "3".to_string(),
+            "suprah".to_string(), "stockfish".to_string(), "12:00:00".to_string(), format_time_control(30000, 1000),
+            String::new(), String::new());
+        pgn.set_tags(PgnTags::parse(tags).unwrap());
+        pgn.set_result("1-0".to_string());
+        pgn.set_termination("Checkmate");
+        pgn.set_ply_count("41".to_string());
+        pgn
+    }
+
+    fn names(pgn: &Pgn) -> Vec<String> {
+        pgn.header().into_iter().map(|(name, _)| name).collect()
+    }
+
+    #[test]
+    fn test_header_order() {
+        let pgn = test_pgn("");
+        assert_eq!(names(&pgn), ["Event", "Site", "Date", "Round", "White", "Black", "Result",
+            "TimeControl", "Time", "PlyCount", "Termination"]);
+        assert!(pgn.header().contains(&("TimeControl".to_string(), "30+1".to_string())));
+
+        let mut pgn = test_pgn("Site2=club; -Time; GameDuration; BlackElo; WhiteElo; Annotator=Me \"x\"");
+        pgn.set_elos(Some(2650), None);
+        pgn.set_variant("Chess960", "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9");
+        assert_eq!(names(&pgn), ["Event", "Site", "Date", "Round", "White", "Black", "Result", "WhiteElo", "BlackElo",
+            "TimeControl", "PlyCount", "GameDuration", "Termination", "Annotator", "Variant", "SetUp", "FEN", "Site2"]);
+        let header = pgn.header();
+        assert_eq!(header[7].1, "2650");
+        assert_eq!(header[8].1, "-");
+        assert_eq!(header[11].1, "00:00:00");
+        assert_eq!(header[13].1, "Me \"x\"");
+    }
+
+    #[test]
+    fn test_parse_tags() {
+        assert!(PgnTags::parse("").unwrap().includes("PlyCount"));
+        assert!(!PgnTags::parse("-PlyCount").unwrap().includes("PlyCount"));
+        assert!(PgnTags::parse("Ply").unwrap_err().starts_with("unknown optional PGN tag 'Ply'"));
+        assert_eq!(PgnTags::parse("Result=1-0"), Err("the PGN tag 'Result' is set by Matt-Magie".to_string()));
+        assert_eq!(PgnTags::parse("My Tag=1"), Err("'My Tag' is no valid PGN tag name".to_string()));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_coordinate_moves() {
+        let mut pgn = test_pgn("");
+        pgn.set_moves("1. e2e4 e7e5 2. Ng1f3 Nb8c6 3. Bf1c4 Bf8c5 4. Ke1g1 Ke8g8 5. b7b8=Q".to_string());
+        assert!(pgn.moves.contains("4. O-O O-O 5."));
+        assert_eq!(coordinate_moves(&pgn.moves), ["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "f8c5", "e1g1", "e8g8"]);
+        assert_eq!(coordinate_moves("1. d2d4 d7d5 2. O-O-O"), ["d2d4", "d7d5", "e1c1"]);
+        assert!(coordinate_moves("1-0").is_empty());
+
+        pgn.set_moves("1. a7b8=N Ke8d7 2. a2a1=R".to_string());
+        assert_eq!(pgn.moves, "1. axb8=N Ke8d7 2. a1=R");
+    }
+
+    #[test]
+    fn test_format_time_control() {
+        assert_eq!(format_time_control(30000, 1000), "30+1");
+        assert_eq!(format_time_control(1000, 10), "1+0.01");
+        assert_eq!(format_time_control(90500, 0), "90.5+0");
+    }
+}
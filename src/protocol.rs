@@ -0,0 +1,136 @@
+use crate::cecp_protocol::CecpProtocol;
+use crate::human_protocol::HumanProtocol;
+use crate::uci_option::UciOption;
+use crate::uci_protocol::UciProtocol;
+
+
+/// What an engine line means to the manager, independent of the protocol.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EngineEvent {
+    IdName(String),
+    OptionDeclared(UciOption),
+    HandshakeDone,
+    Ready,
+    BestMove { best_move: String, ponder_move: Option<String> },
+    Resign,
+    Info(SearchInfo),
+}
+
+
+/// Progress of a running search, fields the engine did not report are None.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchInfo {
+    pub depth: Option<u32>,
+    pub score: Option<String>,  // from the engine's view, e.g. "+0.35" or "-M3"
+    pub nps: Option<u64>,
+}
+
+impl SearchInfo {
+    pub fn centipawns(cp: i64) -> String {
+        format!("{}{}.{:02}", if cp < 0 { "-" } else { "+" }, cp.abs() / 100, cp.abs() % 100)
+    }
+
+    pub fn mate(moves: i64) -> String {
+        format!("{}M{}", if moves < 0 { "-" } else { "" }, moves.abs())
+    }
+
+    /// Takes the reported fields of a newer info, the others are kept.
+    pub fn update(&mut self, info: SearchInfo) {
+        self.depth = info.depth.or(self.depth);
+        self.score = info.score.or(self.score.take());
+        self.nps = info.nps.or(self.nps);
+    }
+}
+
+
+/// A move of the game, castling keeps its side for protocols that write it as O-O.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayedMove {
+    pub uci: String,
+    pub castling: Option<&'static str>,  // "O-O" or "O-O-O"
+}
+
+impl PlayedMove {
+    pub fn uci_moves(moves: &[PlayedMove]) -> String {
+        moves.iter().map(|played| played.uci.as_str()).collect::<Vec<&str>>().join(" ")
+    }
+}
+
+
+/// Start position and time control of the engine for a new game.
+#[derive(Debug, Clone)]
+pub struct GameSetup {
+    pub start_fen: Option<String>,  // None is the standard start position
+    pub chess960: bool,
+    pub time_ms: i32,  // own base time
+    pub inc_ms: i32,  // own increment
+    pub ponder: bool,
+}
+
+/// Position and clocks the engine has to search.
+pub struct SearchRequest<'a> {
+    pub moves: &'a [PlayedMove],
+    pub wtime: i32,
+    pub btime: i32,
+    pub winc: i32,
+    pub binc: i32,
+}
+
+impl SearchRequest<'_> {
+    /// Games always start with white to move.
+    pub fn white_to_move(&self) -> bool {
+        self.moves.len().is_multiple_of(2)
+    }
+}
+
+
+/// Translation between the manager and the text protocol of an engine.
+/// Commands are returned as lines, the manager sends and logs them.
+pub trait Protocol: Send {
+    /// Commands right after the start of the process.
+    fn start(&mut self) -> Vec<String>;
+
+    /// Interprets an engine line, the returned commands answer it (e.g. CECP feature acceptance).
+    fn parse(&mut self, line: &str) -> (Vec<EngineEvent>, Vec<String>);
+
+    /// Whether the handshake ended without an answer of the engine (CECP engines that send no
+    /// `feature done=1`), true only once.
+    fn handshake_lapsed(&mut self) -> bool;
+
+    /// Options the manager has to set for the game type, e.g. UCI_Chess960.
+    fn protocol_options(&self, chess960: bool, ponder: bool, declared: &[UciOption]) -> Vec<(String, String)>;
+
+    fn set_option(&self, option: &UciOption, value: &str) -> String;
+
+    fn debug(&self) -> Vec<String>;
+
+    fn new_game(&mut self, setup: &GameSetup) -> Vec<String>;
+
+    /// Readiness check, None if the protocol has none and the engine is ready right away.
+    fn ready_check(&mut self) -> Option<String>;
+
+    fn go(&mut self, request: &SearchRequest) -> Vec<String>;
+
+    /// Search on the opponent's time with the predicted reply, None if the engine ponders by itself.
+    fn ponder(&mut self, request: &SearchRequest, predicted: &str) -> Option<Vec<String>>;
+
+    fn ponderhit(&mut self) -> Vec<String>;
+
+    /// Interrupts a search, the flag tells if the engine answers it with a move.
+    fn stop(&mut self) -> (Vec<String>, bool);
+
+    fn game_over(&mut self, result: &str, termination: &str) -> Vec<String>;
+
+    fn quit(&mut self) -> Vec<String>;
+}
+
+
+/// Protocol by its registry name.
+pub fn from_name(name: &str) -> Option<Box<dyn Protocol>> {
+    match name {
+        "uci" => Some(Box::new(UciProtocol::new())),
+        "xboard" | "cecp" => Some(Box::new(CecpProtocol::new())),
+        "human" => Some(Box::new(HumanProtocol::new())),
+        _ => None,
+    }
+}
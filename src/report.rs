@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+
+use crate::eco::{self, EcoTable};
+use crate::pgn;
+use crate::swiss;
+
+
+/// Iterations of the Bradley-Terry rating fit.
+const RATING_ITERATIONS: usize = 100;
+
+/// Ratings are shifted to this average.
+const AVERAGE_ELO: f64 = 2000.0;
+
+
+/// White, black, result and ECO code of a PGN game.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameResult {
+    pub white: String,
+    pub black: String,
+    pub result: String,
+    pub eco: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EngineScore {
+    pub name: String,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    pub points: f64,
+    pub elo: f64,
+}
+
+impl EngineScore {
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+}
+
+
+/// The games of a PGN file that have White, Black and Result tags. Games without an ECO tag are
+/// classified by their moves, unless they start from a set up position.
+pub fn parse_games(pgn: &str) -> Vec<GameResult> {
+    let mut games = Vec::new();
+    let mut tags: HashMap<String, String> = HashMap::new();
+    let mut movetext = String::new();
+    let mut finish = |tags: &mut HashMap<String, String>, movetext: &mut String| {
+        if let (Some(white), Some(black), Some(result)) = (tags.remove("White"), tags.remove("Black"), tags.remove("Result")) {
+            let eco = tags.remove("ECO").or_else(|| match tags.contains_key("FEN") {
+                true => None,
+                false => EcoTable::bundled().classify(&pgn::coordinate_moves(movetext)).map(|opening| opening.eco.clone()),
+            });
+            games.push(GameResult { white, black, result, eco });
+        }
+        tags.clear();
+        movetext.clear();
+    };
+    for line in pgn.lines().map(str::trim) {
+        let Some(tag) = line.strip_prefix('[').and_then(|tag| tag.strip_suffix(']')) else {
+            movetext.push_str(line);
+            movetext.push(' ');
+            continue;
+        };
+        let Some((name, value)) = tag.split_once(' ') else {
+            continue;
+        };
+        if name == "Event" {
+            finish(&mut tags, &mut movetext);
+        }
+        tags.insert(name.to_string(), value.trim().trim_matches('"').to_string());
+    }
+    finish(&mut tags, &mut movetext);
+    games
+}
+
+/// Scores of all engines with finished games, best rated first. The ratings are fitted with
+/// the Bradley-Terry model, one virtual draw against an average player keeps them finite.
+pub fn scoreboard(games: &[GameResult]) -> Vec<EngineScore> {
+    let mut scores: Vec<EngineScore> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut pairs: HashMap<(usize, usize), u32> = HashMap::new();
+    for game in games {
+        let Some(white_score) = swiss::white_score(&game.result) else {
+            continue;
+        };
+        let mut player = |name: &str| *index.entry(name.to_string()).or_insert_with(|| {
+            scores.push(EngineScore { name: name.to_string(), ..EngineScore::default() });
+            scores.len() - 1
+        });
+        let (white, black) = (player(&game.white), player(&game.black));
+        for (side, score) in [(white, white_score), (black, 1.0 - white_score)] {
+            scores[side].points += score;
+            match score {
+                1.0 => scores[side].wins += 1,
+                0.0 => scores[side].losses += 1,
+                _ => scores[side].draws += 1,
+            }
+        }
+        *pairs.entry((white, black)).or_default() += 1;
+        *pairs.entry((black, white)).or_default() += 1;
+    }
+
+    let mut gamma = vec![1.0; scores.len()];
+    for _ in 0..RATING_ITERATIONS {
+        gamma = (0..scores.len()).map(|i| {
+            let denominator = 2.0 / (gamma[i] + 1.0) + pairs.iter()
+                .filter(|((a, _), _)| *a == i)
+                .fold(0.0, |sum, ((_, b), games)| sum + *games as f64 / (gamma[i] + gamma[*b]));
+            (scores[i].points + 1.0) / denominator
+        }).collect();
+    }
+    for (score, gamma) in scores.iter_mut().zip(&gamma) {
+        score.elo = 400.0 * gamma.log10();
+    }
+    if !scores.is_empty() {
+        let shift = AVERAGE_ELO - scores.iter().fold(0.0, |sum, score| sum + score.elo) / scores.len() as f64;
+        scores.iter_mut().for_each(|score| score.elo += shift);
+    }
+    scores.sort_by(|a, b| b.elo.total_cmp(&a.elo).then(b.points.total_cmp(&a.points)));
+    scores
+}
+
+fn shorten(name: &str, width: usize) -> String {
+    if name.chars().count() > width {
+        format!("{}..", name.chars().take(width - 2).collect::<String>())
+    } else {
+        name.to_string()
+    }
+}
+
+pub fn format_scoreboard(scores: &[EngineScore]) -> Vec<String> {
+    let rule = "=".repeat(73);
+    let mut lines = vec![
+        rule.clone(),
+        format!("{:>55}", "TOURNAMENT SCOREBOARD & ELO EVALUATION"),
+        rule.clone(),
+        format!("{:<4} {:<25} {:<6} {:<13} {:<8} {:<7} {:<5}", "Rank", "Engine Name", "Games", "W/D/L", "Points", "Score%", "Elo"),
+        "-".repeat(73),
+    ];
+    for (rank, score) in scores.iter().enumerate() {
+        let percent = if score.games() > 0 { score.points / score.games() as f64 * 100.0 } else { 0.0 };
+        let line = format!("{:<4} {:<25} {:<6} {:<13} {:<8.1} {:<7.1} {:<5}",
+            rank + 1, shorten(&score.name, 25), score.games(), format!("{}/{}/{}", score.wins, score.draws, score.losses),
+            score.points, percent, score.elo.round());
+        lines.push(line.trim_end().to_string());
+    }
+    lines.push(rule.clone());
+    lines.push("Note: Elo calculates via iterative Bradley-Terry, normalized to 2000 avg.".to_string());
+    lines.push(rule);
+    lines
+}
+
+/// The scoreboard of the games of every ECO family, games without a known opening count as family `?`.
+pub fn eco_breakdown(games: &[GameResult]) -> Vec<(char, Vec<EngineScore>)> {
+    let family = |game: &GameResult| game.eco.as_deref().map_or('?', eco::family_of);
+    eco::FAMILIES.iter().map(|(letter, _)| *letter).chain(['?'])
+        .map(|letter| (letter, games.iter().filter(|game| family(game) == letter).cloned().collect::<Vec<_>>()))
+        .filter(|(_, games)| !games.is_empty())
+        .map(|(letter, games)| (letter, scoreboard(&games)))
+        .collect()
+}
+
+pub fn format_eco_breakdown(breakdown: &[(char, Vec<EngineScore>)]) -> Vec<String> {
+    let rule = "=".repeat(73);
+    let mut lines = vec![
+        rule.clone(),
+        format!("{:>47}", "RESULTS BY ECO FAMILY"),
+        rule.clone(),
+        format!("{:<4} {:<25} {:<6} {:<13} {:<8} {}", "ECO", "Engine Name", "Games", "W/D/L", "Points", "Score%"),
+    ];
+    for (letter, scores) in breakdown {
+        let name = eco::FAMILIES.iter().find(|(family, _)| family == letter).map_or("Unclassified", |(_, name)| name);
+        // every game counts for both engines
+        let games = scores.iter().map(EngineScore::games).sum::<u32>() / 2;
+        lines.push("-".repeat(73));
+        lines.push(format!("{:<4} {} ({} games)", letter, name, games));
+        for score in scores {
+            let percent = score.points / score.games() as f64 * 100.0;
+            let line = format!("{:<4} {:<25} {:<6} {:<13} {:<8.1} {:<7.1}",
+                "", shorten(&score.name, 25), score.games(), format!("{}/{}/{}", score.wins, score.draws, score.losses),
+                score.points, percent);
+            lines.push(line.trim_end().to_string());
+        }
+    }
+    lines.push(rule);
+    lines
+}
+
+/// Prints the scoreboard of a PGN file, with `list_games` the single results first and with `by_eco`
+/// the results per ECO family after it.
+pub fn print_report(pgn_path: &str, list_games: bool, by_eco: bool) -> Result<(), Box<dyn Error>> {
+    let pgn = fs::read_to_string(pgn_path).map_err(|e| format!("MM can not read {}: {}", pgn_path, e))?;
+    let games = parse_games(&pgn);
+    if games.is_empty() {
+        println!("No games found in {}.", pgn_path);
+        return Ok(());
+    }
+    if list_games {
+        for (number, game) in games.iter().enumerate() {
+            println!("Game {:<4} {:<25} vs {:<25}  -> {:<7} {}", number + 1, shorten(&game.white, 25), shorten(&game.black, 25),
+                game.result, game.eco.as_deref().unwrap_or("?"));
+        }
+        println!();
+    }
+    for line in format_scoreboard(&scoreboard(&games)) {
+        println!("{}", line);
+    }
+    if by_eco {
+        println!();
+        for line in format_eco_breakdown(&eco_breakdown(&games)) {
+            println!("{}", line);
+        }
+    }
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scoreboard() {
+        let pgn = "[Event \"T\"]\n[White \"a\"]\n[Black \"b\"]\n[Result \"1-0\"]\n1. e2e4 1-0\n\n\
+            [Event \"T\"]\n[White \"b\"]\n[Black \"a\"]\n[Result \"1/2-1/2\"]\n1. e2e4 e7e5 1/2-1/2\n\n\
+            [Event \"T\"]\n[White \"a\"]\n[Black \"b\"]\n[Result \"*\"]\n1. e2e4 *\n\n";
+        let games = parse_games(pgn);
+        assert_eq!(games.len(), 3);
+        assert_eq!(games[1], GameResult { white: "b".to_string(), black: "a".to_string(), result: "1/2-1/2".to_string(), eco: Some("C20".to_string()) });
+
+        let scores = scoreboard(&games);
+        assert_eq!(scores.iter().map(|score| (score.name.as_str(), score.wins, score.draws, score.losses)).collect::<Vec<_>>(),
+            vec![("a", 1, 1, 0), ("b", 0, 1, 1)]);
+        assert_eq!(scores[0].points, 1.5);
+        assert!(scores[0].elo > 2000.0 && (scores[0].elo + scores[1].elo - 4000.0).abs() < 1e-6);
+        assert_eq!(format_scoreboard(&scores)[5], "1    a                         2      1/1/0         1.5      75.0    2060");
+    }
+
+    #[test]
+    fn test_eco_breakdown() {
+        let pgn = "[Event \"T\"]\n[White \"a\"]\n[Black \"b\"]\n[Result \"1-0\"]\n[ECO \"B90\"]\n1. e2e4 1-0\n\n\
+            [Event \"T\"]\n[White \"b\"]\n[Black \"a\"]\n[Result \"0-1\"]\n1. d2d4 d7d5 2. c2c4 0-1\n\n\
+            [Event \"T\"]\n[White \"a\"]\n[Black \"b\"]\n[Result \"1/2-1/2\"]\n[FEN \"8/8/8/8/8/8/8/K1k5 w - - 0 1\"]\n1. a1a2 1/2-1/2\n\n";
+        let games = parse_games(pgn);
+        assert_eq!(games.iter().map(|game| game.eco.as_deref()).collect::<Vec<_>>(), [Some("B90"), Some("D06"), None]);
+
+        let breakdown = eco_breakdown(&games);
+        assert_eq!(breakdown.iter().map(|(letter, scores)| (*letter, scores.len())).collect::<Vec<_>>(), [('B', 2), ('D', 2), ('?', 2)]);
+        let lines = format_eco_breakdown(&breakdown);
+        assert_eq!(lines[5], "B    Semi-open games (1 games)");
+        assert_eq!(lines[6], "     a                         1      1/0/0         1.0      100.0");
+    }
+}
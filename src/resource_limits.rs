@@ -0,0 +1,230 @@
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::os::fd::AsRawFd;
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process::Command;
+use std::thread;
+use std::time::Duration;
+
+use crate::log;
+
+
+/// Lock files of the claimed cores, shared by all Matt-Magie runs of the machine.
+const CPU_LOCK_DIR: &str = "matt-magie-cpus";
+/// A crashed engine whose address space came this close to its limit ran out of memory.
+const MEMORY_BREACH_SHARE: f64 = 0.9;
+/// Stderr lines (lower case) of engines that could not allocate memory, e.g. of Rust, C++, Go and Python.
+const OUT_OF_MEMORY_PATTERNS: [&str; 5] = ["memory allocation of", "bad_alloc", "out of memory", "cannot allocate memory", "memoryerror"];
+
+
+/// Resources of the engines of a run: own cores, their nice level and a cap of their address space.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResourceLimits {
+    pub cpus_per_engine: usize,  // 0 leaves the engines unpinned
+    pub nice: Option<i32>,
+    pub memory_mb: Option<u64>,
+}
+
+impl ResourceLimits {
+    /// Parses `cpus=1,nice=10,memory=512` (memory in MiB), missing keys set no limit.
+    pub fn from_arg(arg: &str) -> Result<ResourceLimits, String> {
+        let mut limits = ResourceLimits::default();
+        for part in arg.split(',').filter(|part| !part.trim().is_empty()) {
+            let (key, value) = part.split_once('=').ok_or_else(|| format!("limit '{}' is not key=value", part.trim()))?;
+            let value = value.trim();
+            let invalid = || format!("limit '{}' has an invalid value", part.trim());
+            match key.trim() {
+                "cpus" => limits.cpus_per_engine = value.parse().map_err(|_| invalid())?,
+                "nice" => limits.nice = Some(value.parse().ok().filter(|nice| (-20..=19).contains(nice)).ok_or_else(invalid)?),
+                "memory" => limits.memory_mb = Some(value.parse().ok().filter(|mb| *mb > 0).ok_or_else(invalid)?),
+                other => return Err(format!("unknown limit '{}'", other)),
+            }
+        }
+        Ok(limits)
+    }
+
+    /// Limits of the engine `index` (0 or 1) running on the cores of `lease`.
+    pub fn for_engine(&self, index: usize, lease: Option<&CpuLease>) -> ProcessLimits {
+        let cpus = lease.map(|lease| lease.cpus.iter().skip(index * self.cpus_per_engine).take(self.cpus_per_engine).copied().collect());
+        ProcessLimits { cpus: cpus.unwrap_or_default(), nice: self.nice, memory_bytes: self.memory_mb.map(|mb| mb << 20) }
+    }
+}
+
+
+/// Limits of one engine process, applied between fork and exec.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProcessLimits {
+    pub cpus: Vec<usize>,  // empty: all cores of Matt-Magie
+    pub nice: Option<i32>,
+    pub memory_bytes: Option<u64>,
+}
+
+impl ProcessLimits {
+    pub fn apply(&self, command: &mut Command) {
+        if *self == ProcessLimits::default() {
+            return;
+        }
+        // the cpu set is built here, the child may only make async-signal-safe calls
+        // SAFETY: cpu_set_t is a plain bit array, all zeros is the empty set
+        let mut cpu_set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+        for cpu in &self.cpus {
+            // SAFETY: the cores come from available_cpus, so they are below CPU_SETSIZE
+            unsafe { libc::CPU_SET(*cpu, &mut cpu_set) };
+        }
+        let pin = !self.cpus.is_empty();
+        let nice = self.nice;
+        let memory = self.memory_bytes.map(|bytes| libc::rlimit { rlim_cur: bytes, rlim_max: bytes });
+        let checked = |result: libc::c_int| if result == 0 { Ok(()) } else { Err(io::Error::last_os_error()) };
+        // SAFETY: the closure runs in the forked child before exec, it only calls the async-signal-safe
+        // sched_setaffinity, setpriority and setrlimit on copied values and does not allocate (an OS error
+        // is stored without allocation)
+        unsafe {
+            command.pre_exec(move || {
+                if pin {
+                    checked(libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &cpu_set))?;
+                }
+                if let Some(nice) = nice {
+                    checked(libc::setpriority(libc::PRIO_PROCESS, 0, nice))?;
+                }
+                if let Some(memory) = memory {
+                    checked(libc::setrlimit(libc::RLIMIT_AS, &memory))?;
+                }
+                Ok(())
+            });
+        }
+    }
+
+    /// Whether an engine with these limits that crashed with `stderr` after using up to `peak_bytes`
+    /// of address space ran into its memory cap.
+    pub fn is_memory_breach(&self, peak_bytes: u64, stderr: &[String]) -> bool {
+        let Some(limit) = self.memory_bytes else {
+            return false;
+        };
+        peak_bytes as f64 >= limit as f64 * MEMORY_BREACH_SHARE
+            || stderr.iter().any(|line| OUT_OF_MEMORY_PATTERNS.iter().any(|pattern| line.to_ascii_lowercase().contains(pattern)))
+    }
+}
+
+
+/// Peak address space of the process `pid` (`VmPeak`), 0 once it exited.
+pub fn peak_memory(pid: u32) -> u64 {
+    let status = fs::read_to_string(format!("/proc/{}/status", pid)).unwrap_or_default();
+    status.lines()
+        .find_map(|line| line.strip_prefix("VmPeak:"))
+        .and_then(|value| value.trim().trim_end_matches("kB").trim().parse::<u64>().ok())
+        .map_or(0, |kb| kb << 10)
+}
+
+
+/// Cores of one Matt-Magie run, no other run gets them until the lease is dropped.
+#[derive(Debug)]
+pub struct CpuLease {
+    pub cpus: Vec<usize>,
+    _locks: Vec<File>,  // the cores stay claimed as long as their lock files are open
+}
+
+/// Claims `count` cores Matt-Magie may run on, waiting while other runs hold them.
+pub fn claim_cpus(count: usize, logfile: &str) -> Result<CpuLease, String> {
+    let available = available_cpus();
+    if count > available.len() {
+        return Err(format!("MM cpu pinning needs {} cores, only {} are available", count, available.len()));
+    }
+    let dir = std::env::temp_dir().join(CPU_LOCK_DIR);
+    fs::create_dir_all(&dir).map_err(|e| format!("MM can not create {}: {}", dir.display(), e))?;
+    let mut waiting = false;
+    loop {
+        // one run claims at a time, so two runs never hold half of the cores each
+        let claim_lock = open_lock(dir.join("claim.lock")).map_err(|e| format!("MM can not lock the cores: {}", e))?;
+        lock(&claim_lock, libc::LOCK_EX).map_err(|e| format!("MM can not lock the cores: {}", e))?;
+        let mut cpus = Vec::new();
+        let mut locks = Vec::new();
+        for cpu in &available {
+            if cpus.len() == count {
+                break;
+            }
+            if let Ok(file) = open_lock(dir.join(format!("cpu{}.lock", cpu))) && lock(&file, libc::LOCK_EX | libc::LOCK_NB).is_ok() {
+                cpus.push(*cpu);
+                locks.push(file);
+            }
+        }
+        if cpus.len() == count {
+            log::info(&format!("pinned to cores {:?}", cpus), logfile);
+            return Ok(CpuLease { cpus, _locks: locks });
+        }
+        drop(locks);
+        drop(claim_lock);
+        if !waiting {
+            log::info(&format!("waiting for {} free cores", count), logfile);
+            waiting = true;
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+/// The cores Matt-Magie itself may run on.
+pub fn available_cpus() -> Vec<usize> {
+    // SAFETY: cpu_set_t is a plain bit array, all zeros is the empty set
+    let mut cpu_set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    // SAFETY: sched_getaffinity writes at most the given size into the set, which lives on this stack
+    if unsafe { libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut cpu_set) } != 0 {
+        return (0..thread::available_parallelism().map_or(1, |n| n.get())).collect();
+    }
+    // SAFETY: the cores are below CPU_SETSIZE, CPU_ISSET only reads the set
+    (0..libc::CPU_SETSIZE as usize).filter(|cpu| unsafe { libc::CPU_ISSET(*cpu, &cpu_set) }).collect()
+}
+
+fn open_lock(path: PathBuf) -> io::Result<File> {
+    OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path)
+}
+
+fn lock(file: &File, operation: libc::c_int) -> io::Result<()> {
+    // SAFETY: flock only changes the lock of the descriptor, which `file` keeps open during the call
+    if unsafe { libc::flock(file.as_raw_fd(), operation) } == 0 { Ok(()) } else { Err(io::Error::last_os_error()) }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_arg() {
+        assert_eq!(ResourceLimits::from_arg(""), Ok(ResourceLimits::default()));
+        let limits = ResourceLimits::from_arg("cpus=2, nice=10, memory=512").unwrap();
+        assert_eq!(limits, ResourceLimits { cpus_per_engine: 2, nice: Some(10), memory_mb: Some(512) });
+        assert_eq!(ResourceLimits::from_arg("nice=40"), Err("limit 'nice=40' has an invalid value".to_string()));
+        assert_eq!(ResourceLimits::from_arg("swap=1"), Err("unknown limit 'swap'".to_string()));
+
+        let lease = CpuLease { cpus: vec![4, 5, 6, 7], _locks: Vec::new() };
+        let black = limits.for_engine(1, Some(&lease));
+        assert_eq!((black.cpus, black.memory_bytes), (vec![6, 7], Some(512 << 20)));
+        assert!(limits.for_engine(0, None).cpus.is_empty());
+    }
+
+    #[test]
+    fn test_limits_of_child() {
+        let limits = ProcessLimits { cpus: vec![available_cpus()[0]], nice: Some(5), memory_bytes: Some(256 << 20) };
+        let mut command = Command::new("sh");
+        command.arg("-c").arg("grep Cpus_allowed_list /proc/self/status; ulimit -v; cut -d' ' -f19 /proc/self/stat");
+        limits.apply(&mut command);
+        let output = String::from_utf8(command.output().unwrap().stdout).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[0].split_whitespace().last(), Some(available_cpus()[0].to_string().as_str()));
+        assert_eq!(lines[1], (256 << 10).to_string());
+        assert!(lines[2].parse::<i32>().unwrap() >= 5);
+
+        assert!(limits.is_memory_breach(0, &["memory allocation of 1048576 bytes failed".to_string()]));
+        assert!(limits.is_memory_breach(250 << 20, &[]));
+        assert!(!limits.is_memory_breach(10 << 20, &["index out of bounds".to_string()]));
+        assert!(!ProcessLimits::default().is_memory_breach(u64::MAX, &[]));
+    }
+
+    #[test]
+    fn test_claim_cpus() {
+        let count = available_cpus().len();
+        assert!(claim_cpus(count + 1, "/dev/null").unwrap_err().contains("only"));
+        let lease = claim_cpus(1, "/dev/null").unwrap();
+        assert_eq!(lease.cpus.len(), 1);
+    }
+}
@@ -0,0 +1,54 @@
+use std::sync::atomic::{AtomicI32, Ordering};
+
+use crate::log;
+
+
+/// Signal that asked Matt-Magie to stop, 0 while none arrived.
+static REQUESTED: AtomicI32 = AtomicI32::new(0);
+
+extern "C" fn on_signal(signal: libc::c_int) {
+    REQUESTED.store(signal, Ordering::SeqCst);
+}
+
+/// SIGINT and SIGTERM no longer end the process right away, the running game
+/// sees `requested()` and shuts down its engines first.
+pub fn install() {
+    // SAFETY: the handler only stores the signal number in an atomic, which is async signal safe
+    unsafe {
+        libc::signal(libc::SIGINT, on_signal as *const () as libc::sighandler_t);
+        libc::signal(libc::SIGTERM, on_signal as *const () as libc::sighandler_t);
+    }
+}
+
+/// The received SIGINT or SIGTERM, if any.
+pub fn requested() -> Option<i32> {
+    match REQUESTED.load(Ordering::SeqCst) {
+        0 => None,
+        signal => Some(signal),
+    }
+}
+
+/// Ends the process by the signal it received, so a calling shell sees the interruption.
+pub fn exit_by(signal: i32) -> ! {
+    log::flush();
+    // SAFETY: restores the default action and raises the signal for this process only
+    unsafe {
+        libc::signal(signal, libc::SIG_DFL);
+        libc::raise(signal);
+    }
+    std::process::exit(128 + signal);
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requested() {
+        assert_eq!(requested(), None);
+        on_signal(libc::SIGTERM);
+        assert_eq!(requested(), Some(libc::SIGTERM));
+        REQUESTED.store(0, Ordering::SeqCst);
+    }
+}
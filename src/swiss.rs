@@ -0,0 +1,335 @@
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fs;
+
+
+/// Pairings tried before a round without rematches is given up.
+const MAX_PAIRING_STEPS: u32 = 200_000;
+
+
+/// Tie-break of the Swiss standings, applied in the configured order after the points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TieBreak {
+    Buchholz,         // sum of the opponents' points
+    SonnebornBerger,  // sum of the points of beaten opponents and half of those of drawn opponents
+}
+
+impl TieBreak {
+    /// Parses a comma separated list like `buchholz, sonneborn_berger`.
+    pub fn parse_list(arg: &str) -> Result<Vec<TieBreak>, String> {
+        arg.split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| match name {
+                "buchholz" => Ok(TieBreak::Buchholz),
+                "sonneborn_berger" => Ok(TieBreak::SonnebornBerger),
+                other => Err(format!("unknown tie-break '{}'", other)),
+            })
+            .collect()
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            TieBreak::Buchholz => "Buchholz",
+            TieBreak::SonnebornBerger => "SB",
+        }
+    }
+}
+
+
+/// A finished game or a bye of a Swiss round, as mm.sh records them in the tournament state file:
+/// `result<TAB>round<TAB>white<TAB>black<TAB>1-0` and `bye<TAB>round<TAB>engine`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RoundEntry {
+    Game { round: u32, white: String, black: String, white_score: f64 },
+    Bye { round: u32, player: String },
+}
+
+impl RoundEntry {
+    fn round(&self) -> u32 {
+        match self {
+            RoundEntry::Game { round, .. } | RoundEntry::Bye { round, .. } => *round,
+        }
+    }
+}
+
+/// White's points of a PGN result, None for an unfinished game.
+pub fn white_score(result: &str) -> Option<f64> {
+    match result {
+        "1-0" => Some(1.0),
+        "0-1" => Some(0.0),
+        "1/2-1/2" => Some(0.5),
+        _ => None,
+    }
+}
+
+/// The Swiss entries of a state file, other lines and unfinished games are skipped.
+pub fn parse_entries(state: &str) -> Vec<RoundEntry> {
+    state.lines().filter_map(|line| {
+        let fields: Vec<&str> = line.split('\t').collect();
+        match fields.as_slice() {
+            ["result", round, white, black, result] => {
+                let white_score = white_score(result)?;
+                Some(RoundEntry::Game { round: round.parse().ok()?, white: white.to_string(), black: black.to_string(), white_score })
+            }
+            ["bye", round, player] => Some(RoundEntry::Bye { round: round.parse().ok()?, player: player.to_string() }),
+            _ => None,
+        }
+    }).collect()
+}
+
+
+/// What a player did in the rounds so far.
+#[derive(Debug, Default)]
+struct PlayerRecord {
+    points: f64,
+    opponents: Vec<(usize, f64)>,  // opponent and the player's score against it
+    colors: Vec<bool>,  // true for white, in playing order
+    had_bye: bool,
+}
+
+impl PlayerRecord {
+    fn color_difference(&self) -> i32 {
+        self.colors.iter().map(|white| if *white { 1 } else { -1 }).sum()
+    }
+
+    fn has_met(&self, opponent: usize) -> bool {
+        self.opponents.iter().any(|(index, _)| *index == opponent)
+    }
+}
+
+/// Records of the players from all entries before `round`, entries of unknown engines are ignored.
+fn records(players: &[String], entries: &[RoundEntry], round: u32) -> Vec<PlayerRecord> {
+    let mut records: Vec<PlayerRecord> = players.iter().map(|_| PlayerRecord::default()).collect();
+    let index_of = |name: &str| players.iter().position(|player| player == name);
+    for entry in entries.iter().filter(|entry| entry.round() < round) {
+        match entry {
+            RoundEntry::Game { white, black, white_score, .. } => {
+                let (Some(white), Some(black)) = (index_of(white), index_of(black)) else {
+                    continue;
+                };
+                records[white].points += white_score;
+                records[white].opponents.push((black, *white_score));
+                records[white].colors.push(true);
+                records[black].points += 1.0 - white_score;
+                records[black].opponents.push((white, 1.0 - white_score));
+                records[black].colors.push(false);
+            }
+            RoundEntry::Bye { player, .. } => {
+                if let Some(player) = index_of(player) {
+                    records[player].points += 1.0;
+                    records[player].had_bye = true;
+                }
+            }
+        }
+    }
+    records
+}
+
+
+/// Games of a Swiss round as (white, black), with the engine that sits out if the number is odd.
+#[derive(Debug, PartialEq)]
+pub struct Pairing {
+    pub games: Vec<(String, String)>,
+    pub bye: Option<String>,
+}
+
+/// Pairs round `round` from the results of the rounds before: the players are ranked by points
+/// (ties keep the order of `players`) and each is paired with the next ranked player it has not met.
+/// Rematches are only allowed if the round can not be paired without them.
+pub fn pair_round(players: &[String], entries: &[RoundEntry], round: u32) -> Pairing {
+    let records = records(players, entries, round);
+    let mut ranking: Vec<usize> = (0..players.len()).collect();
+    ranking.sort_by(|a, b| records[*b].points.partial_cmp(&records[*a].points).unwrap_or(Ordering::Equal));
+
+    // the lowest ranked player without a bye sits out, everybody had one: the lowest ranked
+    let mut bye = None;
+    if ranking.len() % 2 == 1 {
+        let at = ranking.iter().rposition(|player| !records[*player].had_bye).unwrap_or(ranking.len() - 1);
+        bye = Some(ranking.remove(at));
+    }
+
+    let mut steps = 0;
+    let pairs = pair_players(&ranking, &records, false, &mut steps)
+        .or_else(|| pair_players(&ranking, &records, true, &mut 0))
+        .unwrap_or_default();
+
+    Pairing {
+        games: pairs.iter().map(|(higher, lower)| {
+            let (white, black) = allocate_colors(*higher, *lower, &records, round);
+            (players[white].clone(), players[black].clone())
+        }).collect(),
+        bye: bye.map(|player| players[player].clone()),
+    }
+}
+
+/// Pairs the first player with the best ranked possible opponent and the rest recursively.
+fn pair_players(ranking: &[usize], records: &[PlayerRecord], rematches: bool, steps: &mut u32) -> Option<Vec<(usize, usize)>> {
+    let Some((first, rest)) = ranking.split_first() else {
+        return Some(Vec::new());
+    };
+    for (at, opponent) in rest.iter().enumerate() {
+        *steps += 1;
+        if !rematches && (*steps > MAX_PAIRING_STEPS || records[*first].has_met(*opponent)) {
+            continue;
+        }
+        let mut remaining = rest.to_vec();
+        remaining.remove(at);
+        if let Some(mut pairs) = pair_players(&remaining, records, rematches, steps) {
+            pairs.insert(0, (*first, *opponent));
+            return Some(pairs);
+        }
+    }
+    None
+}
+
+/// White goes to the player that had black more often, then to the one that had black last;
+/// without a difference the higher ranked player gets white in odd rounds.
+fn allocate_colors(higher: usize, lower: usize, records: &[PlayerRecord], round: u32) -> (usize, usize) {
+    let (higher_record, lower_record) = (&records[higher], &records[lower]);
+    let higher_white = match higher_record.color_difference().cmp(&lower_record.color_difference()) {
+        Ordering::Less => true,
+        Ordering::Greater => false,
+        Ordering::Equal => match (higher_record.colors.last(), lower_record.colors.last()) {
+            (Some(false), Some(true)) => true,
+            (Some(true), Some(false)) => false,
+            _ => round % 2 == 1,
+        },
+    };
+    if higher_white { (higher, lower) } else { (lower, higher) }
+}
+
+
+/// A line of the Swiss standings.
+#[derive(Debug, PartialEq)]
+pub struct Standing {
+    pub name: String,
+    pub points: f64,
+    pub games: usize,
+    pub tie_breaks: Vec<f64>,
+}
+
+/// Standings after `rounds` rounds, sorted by points and then by the tie-breaks.
+pub fn standings(players: &[String], entries: &[RoundEntry], rounds: u32, tie_breaks: &[TieBreak]) -> Vec<Standing> {
+    let records = records(players, entries, rounds + 1);
+    let mut standings: Vec<Standing> = players.iter().zip(&records).map(|(name, record)| Standing {
+        name: name.clone(),
+        points: record.points,
+        games: record.opponents.len(),
+        tie_breaks: tie_breaks.iter().map(|tie_break| match tie_break {
+            // folded from 0.0, a sum of no values would print as -0.00
+            TieBreak::Buchholz => record.opponents.iter().fold(0.0, |sum, (opponent, _)| sum + records[*opponent].points),
+            TieBreak::SonnebornBerger => record.opponents.iter().fold(0.0, |sum, (opponent, score)| sum + score * records[*opponent].points),
+        }).collect(),
+    }).collect();
+    standings.sort_by(|a, b| {
+        std::iter::once(b.points.partial_cmp(&a.points))
+            .chain(b.tie_breaks.iter().zip(&a.tie_breaks).map(|(b, a)| b.partial_cmp(a)))
+            .map(|ordering| ordering.unwrap_or(Ordering::Equal))
+            .find(|ordering| *ordering != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    });
+    standings
+}
+
+/// The standings as a table for the terminal.
+pub fn format_standings(standings: &[Standing], tie_breaks: &[TieBreak]) -> Vec<String> {
+    let header: String = tie_breaks.iter().map(|tie_break| format!(" {:>9}", tie_break.label())).collect();
+    let mut lines = vec![format!("{:<4} {:<25} {:>5} {:>6}{}", "Rank", "Engine Name", "Games", "Points", header)];
+    for (rank, standing) in standings.iter().enumerate() {
+        let values: String = standing.tie_breaks.iter().map(|value| format!(" {:>9.2}", value)).collect();
+        lines.push(format!("{:<4} {:<25} {:>5} {:>6.1}{}", rank + 1, standing.name, standing.games, standing.points, values));
+    }
+    lines
+}
+
+
+/// Engines of a comma separated list and the Swiss entries of a state file, a missing file has none.
+fn load(state_path: &str, engines: &str) -> (Vec<String>, Vec<RoundEntry>) {
+    let players = engines.split(',').map(str::trim).filter(|name| !name.is_empty()).map(str::to_string).collect();
+    (players, parse_entries(&fs::read_to_string(state_path).unwrap_or_default()))
+}
+
+/// Prints the games of a round as `game<TAB>white<TAB>black` lines and the bye as `bye<TAB>engine`.
+pub fn print_pairing(state_path: &str, round: &str, engines: &str) -> Result<(), Box<dyn Error>> {
+    let round: u32 = round.parse().map_err(|_| format!("MM swiss round must be a number, found '{}'", round))?;
+    let (players, entries) = load(state_path, engines);
+    let pairing = pair_round(&players, &entries, round);
+    for (white, black) in pairing.games {
+        println!("game\t{}\t{}", white, black);
+    }
+    if let Some(bye) = pairing.bye {
+        println!("bye\t{}", bye);
+    }
+    Ok(())
+}
+
+/// Prints the standings after `rounds` rounds.
+pub fn print_standings(state_path: &str, rounds: &str, engines: &str, tie_breaks: &str) -> Result<(), Box<dyn Error>> {
+    let rounds: u32 = rounds.parse().map_err(|_| format!("MM swiss rounds must be a number, found '{}'", rounds))?;
+    let tie_breaks = TieBreak::parse_list(tie_breaks)?;
+    let (players, entries) = load(state_path, engines);
+    for line in format_standings(&standings(&players, &entries, rounds, &tie_breaks), &tie_breaks) {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn players(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn game(round: u32, white: &str, black: &str, white_score: f64) -> RoundEntry {
+        RoundEntry::Game { round, white: white.to_string(), black: black.to_string(), white_score }
+    }
+
+    #[test]
+    fn test_parse_entries() {
+        let state = "schedule engines=a,b,c\nseed 1\ndone 1\nresult\t1\ta\tb\t0-1\nbye\t1\tc\nresult\t2\tc\ta\t*\n";
+        assert_eq!(parse_entries(state), vec![game(1, "a", "b", 0.0), RoundEntry::Bye { round: 1, player: "c".to_string() }]);
+        assert_eq!(TieBreak::parse_list("buchholz, sonneborn_berger"), Ok(vec![TieBreak::Buchholz, TieBreak::SonnebornBerger]));
+        assert!(TieBreak::parse_list("median").is_err());
+    }
+
+    #[test]
+    fn test_pair_round() {
+        let players = players(&["a", "b", "c", "d", "e"]);
+        let first = pair_round(&players, &[], 1);
+        assert_eq!(first.games, vec![("a".to_string(), "b".to_string()), ("c".to_string(), "d".to_string())]);
+        assert_eq!(first.bye, Some("e".to_string()));
+
+        // b and d won, e had the bye: e and b lead, a may not meet b again
+        let entries = vec![game(1, "a", "b", 0.0), game(1, "c", "d", 0.0), RoundEntry::Bye { round: 1, player: "e".to_string() }];
+        let second = pair_round(&players, &entries, 2);
+        assert_eq!(second.bye, Some("c".to_string()));
+        assert_eq!(second.games, vec![("d".to_string(), "b".to_string()), ("e".to_string(), "a".to_string())]);
+    }
+
+    #[test]
+    fn test_pair_round_allows_rematch_when_needed() {
+        let players = players(&["a", "b"]);
+        let entries = vec![game(1, "a", "b", 1.0)];
+        assert_eq!(pair_round(&players, &entries, 2).games, vec![("b".to_string(), "a".to_string())]);
+    }
+
+    #[test]
+    fn test_standings() {
+        let players = players(&["a", "b", "c", "d"]);
+        let entries = vec![
+            game(1, "a", "b", 1.0), game(1, "c", "d", 0.5),
+            game(2, "d", "a", 0.0), game(2, "b", "c", 1.0),
+        ];
+        let tie_breaks = [TieBreak::Buchholz, TieBreak::SonnebornBerger];
+        let standings = standings(&players, &entries, 2, &tie_breaks);
+        let order: Vec<&str> = standings.iter().map(|standing| standing.name.as_str()).collect();
+        assert_eq!(order, vec!["a", "b", "d", "c"]);
+        assert_eq!(standings[0].tie_breaks, vec![1.5, 1.5]);
+        assert_eq!(standings[3], Standing { name: "c".to_string(), points: 0.5, games: 2, tie_breaks: vec![1.5, 0.25] });
+        assert_eq!(format_standings(&standings, &tie_breaks)[1], "1    a                             2    2.0      1.50      1.50");
+    }
+}
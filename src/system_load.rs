@@ -0,0 +1,110 @@
+use std::error::Error;
+use std::fs;
+
+use crate::engine_config::EngineRegistry;
+use crate::resource_limits;
+
+
+/// Load and free memory of the machine, read from /proc.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SystemLoad {
+    pub load_per_core: f64,  // 1 minute load average divided by the cores
+    pub free_memory_percent: f64,  // MemAvailable of MemTotal
+}
+
+impl SystemLoad {
+    pub fn read() -> Result<SystemLoad, String> {
+        let loadavg = fs::read_to_string("/proc/loadavg").map_err(|e| format!("MM can not read /proc/loadavg: {}", e))?;
+        let meminfo = fs::read_to_string("/proc/meminfo").map_err(|e| format!("MM can not read /proc/meminfo: {}", e))?;
+        SystemLoad::parse(&loadavg, &meminfo, resource_limits::available_cpus().len())
+    }
+
+    pub fn parse(loadavg: &str, meminfo: &str, cores: usize) -> Result<SystemLoad, String> {
+        let load: f64 = loadavg.split_whitespace().next().and_then(|load| load.parse().ok()).ok_or("MM can not parse /proc/loadavg")?;
+        let kb = |key: &str| meminfo.lines()
+            .find_map(|line| line.strip_prefix(key))
+            .and_then(|value| value.trim().trim_end_matches("kB").trim().parse::<f64>().ok());
+        let (Some(total), Some(available)) = (kb("MemTotal:"), kb("MemAvailable:")) else {
+            return Err("MM can not parse /proc/meminfo".to_string());
+        };
+        Ok(SystemLoad { load_per_core: load / cores.max(1) as f64, free_memory_percent: 100.0 * available / total.max(1.0) })
+    }
+
+    /// Why no new game should start with this load, a limit of 0 is not checked.
+    pub fn overload(&self, max_load_per_core: f64, min_free_memory_percent: f64) -> Option<String> {
+        if max_load_per_core > 0.0 && self.load_per_core > max_load_per_core {
+            return Some(format!("load {:.2} per core is above {}", self.load_per_core, max_load_per_core));
+        }
+        if min_free_memory_percent > 0.0 && self.free_memory_percent < min_free_memory_percent {
+            return Some(format!("free memory {:.0}% is below {}%", self.free_memory_percent, min_free_memory_percent));
+        }
+        None
+    }
+}
+
+
+/// Parallel games on `cores` cores when every engine uses `threads_per_engine` threads, at least one.
+pub fn auto_concurrency(cores: usize, threads_per_engine: usize) -> usize {
+    (cores / (2 * threads_per_engine.max(1))).max(1)
+}
+
+/// The `Threads` value of comma-separated engine options, the last one counts like for the engine.
+pub fn threads_of(options: &str) -> Option<usize> {
+    options.split(',')
+        .filter_map(crate::parse_option)
+        .filter(|(name, _)| name.eq_ignore_ascii_case("Threads"))
+        .filter_map(|(_, value)| value.parse().ok())
+        .next_back()
+}
+
+/// `--auto-concurrency` of mm.sh: prints the parallel games, the cores and the threads per engine, tab
+/// separated. The options of an engine are added to those of the registry like for a game.
+pub fn print_auto_concurrency(registry: &EngineRegistry, cpus_per_engine: usize, engines: &[(String, String)]) {
+    let threads = engines.iter()
+        .map(|(engine, options)| {
+            let configured = registry.resolve(engine).options;
+            threads_of(&configured).into_iter().chain(threads_of(options)).next_back().unwrap_or(1)
+        })
+        .max()
+        .unwrap_or(1)
+        .max(cpus_per_engine);
+    let cores = resource_limits::available_cpus().len();
+    println!("{}\t{}\t{}", auto_concurrency(cores, threads), cores, threads);
+}
+
+/// `--load-check` of mm.sh: prints why the machine is too busy for another game and fails, or succeeds silently.
+pub fn check_load(max_load_per_core: f64, min_free_memory_percent: f64) -> Result<bool, Box<dyn Error>> {
+    match SystemLoad::read()?.overload(max_load_per_core, min_free_memory_percent) {
+        Some(reason) => {
+            println!("{}", reason);
+            Ok(false)
+        }
+        None => Ok(true),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auto_concurrency() {
+        assert_eq!(auto_concurrency(16, 1), 8);
+        assert_eq!(auto_concurrency(16, 3), 2);
+        assert_eq!(auto_concurrency(2, 4), 1);
+        assert_eq!(threads_of("Hash=128, Threads=2, threads=4"), Some(4));
+        assert_eq!(threads_of("Hash=128"), None);
+    }
+
+    #[test]
+    fn test_overload() {
+        let meminfo = "MemTotal:       16000000 kB\nMemFree:         1000000 kB\nMemAvailable:    4000000 kB\n";
+        let load = SystemLoad::parse("6.00 4.50 3.00 2/345 6789\n", meminfo, 4).unwrap();
+        assert_eq!(load, SystemLoad { load_per_core: 1.5, free_memory_percent: 25.0 });
+        assert_eq!(load.overload(1.25, 10.0), Some("load 1.50 per core is above 1.25".to_string()));
+        assert_eq!(load.overload(2.0, 30.0), Some("free memory 25% is below 30%".to_string()));
+        assert_eq!(load.overload(0.0, 0.0), None);
+        assert!(SystemLoad::parse("6.00", "MemTotal: 1 kB\n", 4).is_err());
+    }
+}
@@ -0,0 +1,167 @@
+/// Option type as declared by the engine in `option name <name> type <type> ...`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OptionType {
+    Check,
+    Spin { min: i64, max: i64 },
+    Combo(Vec<String>),
+    Button,
+    String,
+}
+
+/// An option advertised by the engine before `uciok`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UciOption {
+    pub name: String,
+    pub option_type: OptionType,
+    pub default: String,
+}
+
+impl UciOption {
+    /// Parses an `option name ... type ... [default ...] [min ...] [max ...] [var ...]*` line.
+    /// Names and values may contain spaces, so the tokens are collected up to the next keyword.
+    pub fn parse(line: &str) -> Option<UciOption> {
+        let mut name: Vec<&str> = Vec::new();
+        let mut option_type: Vec<&str> = Vec::new();
+        let mut default: Vec<&str> = Vec::new();
+        let mut min: Vec<&str> = Vec::new();
+        let mut max: Vec<&str> = Vec::new();
+        let mut vars: Vec<Vec<&str>> = Vec::new();
+
+        let mut tokens = line.split_whitespace();
+        if tokens.next() != Some("option") {
+            return None;
+        }
+        let mut current: Option<&str> = None;
+        for token in tokens {
+            match token {
+                "name" | "type" | "default" | "min" | "max" | "var" if current != Some("name") || token == "type" => {
+                    if token == "var" {
+                        vars.push(Vec::new());
+                    }
+                    current = Some(token);
+                }
+                _ => match current {
+                    Some("name") => name.push(token),
+                    Some("type") => option_type.push(token),
+                    Some("default") => default.push(token),
+                    Some("min") => min.push(token),
+                    Some("max") => max.push(token),
+                    Some("var") => vars.last_mut()?.push(token),
+                    _ => return None,
+                },
+            }
+        }
+
+        let option_type = match option_type.join(" ").as_str() {
+            "check" => OptionType::Check,
+            "spin" => OptionType::Spin {
+                min: min.join(" ").parse().ok()?,
+                max: max.join(" ").parse().ok()?,
+            },
+            "combo" => OptionType::Combo(vars.iter().map(|var| var.join(" ")).collect()),
+            "button" => OptionType::Button,
+            "string" => OptionType::String,
+            _ => return None,
+        };
+        if name.is_empty() {
+            return None;
+        }
+        Some(UciOption { name: name.join(" "), option_type, default: default.join(" ") })
+    }
+
+    /// Checks a configured value against the declaration, the returned value is the one to send.
+    pub fn check_value(&self, value: &str) -> Result<String, String> {
+        match &self.option_type {
+            OptionType::Check => match value.to_lowercase().as_str() {
+                "true" | "false" => Ok(value.to_lowercase()),
+                _ => Err(format!("option '{}' expects true or false, found '{}'", self.name, value)),
+            },
+            OptionType::Spin { min, max } => match value.parse::<i64>() {
+                Ok(number) if number >= *min && number <= *max => Ok(number.to_string()),
+                Ok(_) => Err(format!("option '{}' value {} is out of range {}..{}", self.name, value, min, max)),
+                Err(_) => Err(format!("option '{}' expects an integer, found '{}'", self.name, value)),
+            },
+            OptionType::Combo(vars) => vars.iter()
+                .find(|var| var.eq_ignore_ascii_case(value))
+                .cloned()
+                .ok_or_else(|| format!("option '{}' expects one of [{}], found '{}'", self.name, vars.join(", "), value)),
+            OptionType::Button | OptionType::String => Ok(value.to_string()),
+        }
+    }
+}
+
+/// Looks up an advertised option, option names are case insensitive in UCI.
+pub fn find_option<'a>(options: &'a [UciOption], name: &str) -> Option<&'a UciOption> {
+    options.iter().find(|option| option.name.eq_ignore_ascii_case(name))
+}
+
+/// Validates a configured option against the advertised ones, returns the option and the value to send.
+pub fn checked_option<'a>(options: &'a [UciOption], name: &str, value: &str) -> Result<(&'a UciOption, String), String> {
+    let option = find_option(options, name).ok_or_else(|| format!("engine does not know option '{}'", name))?;
+    let value = option.check_value(value)?;
+    Ok((option, value))
+}
+
+/// Effective option set: the advertised defaults overwritten by the options that were sent.
+pub fn effective_options(options: &[UciOption], sent: &[(String, String)]) -> String {
+    options.iter()
+        .filter(|option| option.option_type != OptionType::Button)
+        .map(|option| {
+            let value = sent.iter().rev()
+                .find(|(name, _)| name.eq_ignore_ascii_case(&option.name))
+                .map(|(_, value)| value.as_str())
+                .unwrap_or(&option.default);
+            format!("{}={}", option.name, value)
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> Vec<UciOption> {
+        [
+            "option name Hash type spin default 16 min 1 max 33554432",
+            "option name Ponder type check default false",
+            "option name Style type combo default Normal var Solid var Normal var Risky",
+            "option name Clear Hash type button",
+            "option name SyzygyPath type string default <empty>",
+        ].iter().map(|line| UciOption::parse(line).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_parse_option_line() {
+        let options = options();
+        assert_eq!(options[0], UciOption {
+            name: "Hash".to_string(),
+            option_type: OptionType::Spin { min: 1, max: 33554432 },
+            default: "16".to_string(),
+        });
+        assert_eq!(options[2].option_type, OptionType::Combo(vec!["Solid".to_string(), "Normal".to_string(), "Risky".to_string()]));
+        assert_eq!(options[3].name, "Clear Hash");
+        assert_eq!(UciOption::parse("option name Broken type spin default 1"), None);
+        assert_eq!(UciOption::parse("id name Suprah"), None);
+    }
+
+    #[test]
+    fn test_checked_option() {
+        let options = options();
+        let value = |name: &str, value: &str| checked_option(&options, name, value).map(|(option, value)| (option.name.clone(), value));
+        assert_eq!(value("hash", "128"), Ok(("Hash".to_string(), "128".to_string())));
+        assert!(value("Hash", "0").is_err());
+        assert!(value("Hash", "big").is_err());
+        assert!(value("Hsah", "128").is_err());
+        assert_eq!(value("Ponder", "TRUE"), Ok(("Ponder".to_string(), "true".to_string())));
+        assert_eq!(value("style", "risky"), Ok(("Style".to_string(), "Risky".to_string())));
+        assert!(value("Style", "Wild").is_err());
+    }
+
+    #[test]
+    fn test_effective_options() {
+        let sent = vec![("hash".to_string(), "128".to_string())];
+        assert_eq!(effective_options(&options(), &sent), "Hash=128, Ponder=false, Style=Normal, SyzygyPath=<empty>");
+    }
+}
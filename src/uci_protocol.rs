@@ -0,0 +1,187 @@
+use crate::protocol::{EngineEvent, GameSetup, PlayedMove, Protocol, SearchInfo, SearchRequest};
+use crate::uci_option::{self, OptionType, UciOption};
+
+
+/// Universal Chess Interface: the engine gets the whole position before every search.
+pub struct UciProtocol {
+    start_fen: Option<String>,
+}
+
+impl UciProtocol {
+    pub fn new() -> Self {
+        UciProtocol { start_fen: None }
+    }
+
+    fn position(&self, moves: &str) -> String {
+        let position = match &self.start_fen {
+            None => "position startpos".to_string(),
+            Some(fen) => format!("position fen {}", fen),
+        };
+        if moves.is_empty() {
+            position
+        } else {
+            format!("{} moves {}", position, moves)
+        }
+    }
+
+    fn go(request: &SearchRequest) -> String {
+        format!("go wtime {} winc {} btime {} binc {}", request.wtime, request.winc, request.btime, request.binc)
+    }
+}
+
+impl Protocol for UciProtocol {
+    fn start(&mut self) -> Vec<String> {
+        vec!["uci".to_string()]
+    }
+
+    fn parse(&mut self, line: &str) -> (Vec<EngineEvent>, Vec<String>) {
+        let event = match line {
+            "uciok" => Some(EngineEvent::HandshakeDone),
+            "readyok" => Some(EngineEvent::Ready),
+            _ if line.starts_with("option ") => UciOption::parse(line).map(EngineEvent::OptionDeclared),
+            _ if line.starts_with("id name ") => Some(EngineEvent::IdName(line[8..].to_string())),
+            _ if line.starts_with("info ") => parse_info(line).map(EngineEvent::Info),
+            _ if line.starts_with("bestmove") => {
+                let mut tokens = line.split_whitespace().skip(1);
+                let best_move = tokens.next().unwrap_or_default().to_string();
+                let ponder_move = match tokens.next() {
+                    Some("ponder") => tokens.next().map(str::to_string),
+                    _ => None,
+                };
+                Some(EngineEvent::BestMove { best_move, ponder_move })
+            }
+            _ => None,
+        };
+        (event.into_iter().collect(), Vec::new())
+    }
+
+    fn handshake_lapsed(&mut self) -> bool {
+        false
+    }
+
+    fn protocol_options(&self, chess960: bool, ponder: bool, declared: &[UciOption]) -> Vec<(String, String)> {
+        let mut options = Vec::new();
+        if chess960 {
+            options.push(("UCI_Chess960".to_string(), "true".to_string()));
+        }
+        if ponder && uci_option::find_option(declared, "Ponder").is_some() {
+            options.push(("Ponder".to_string(), "true".to_string()));
+        }
+        options
+    }
+
+    fn set_option(&self, option: &UciOption, value: &str) -> String {
+        if option.option_type == OptionType::Button {
+            format!("setoption name {}", option.name)
+        } else {
+            format!("setoption name {} value {}", option.name, value)
+        }
+    }
+
+    fn debug(&self) -> Vec<String> {
+        vec!["debug on".to_string()]
+    }
+
+    fn new_game(&mut self, setup: &GameSetup) -> Vec<String> {
+        self.start_fen = setup.start_fen.clone();
+        vec!["ucinewgame".to_string()]
+    }
+
+    fn ready_check(&mut self) -> Option<String> {
+        Some("isready".to_string())
+    }
+
+    fn go(&mut self, request: &SearchRequest) -> Vec<String> {
+        vec![self.position(&PlayedMove::uci_moves(request.moves)), UciProtocol::go(request)]
+    }
+
+    fn ponder(&mut self, request: &SearchRequest, predicted: &str) -> Option<Vec<String>> {
+        let moves = format!("{} {}", PlayedMove::uci_moves(request.moves), predicted);
+        Some(vec![self.position(moves.trim()), UciProtocol::go(request).replacen("go", "go ponder", 1)])
+    }
+
+    fn ponderhit(&mut self) -> Vec<String> {
+        vec!["ponderhit".to_string()]
+    }
+
+    fn stop(&mut self) -> (Vec<String>, bool) {
+        (vec!["stop".to_string()], true)
+    }
+
+    fn game_over(&mut self, _result: &str, _termination: &str) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn quit(&mut self) -> Vec<String> {
+        vec!["quit".to_string()]
+    }
+}
+
+
+/// Depth, score and nps of an `info` line, None if it has none of them (e.g. `info string`).
+fn parse_info(line: &str) -> Option<SearchInfo> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    let value = |key: &str| tokens.iter().position(|token| *token == key).and_then(|at| tokens.get(at + 1));
+    if tokens.get(1) == Some(&"string") {
+        return None;
+    }
+    let score = match (value("cp"), value("mate")) {
+        (Some(cp), _) => cp.parse().ok().map(SearchInfo::centipawns),
+        (_, Some(mate)) => mate.parse().ok().map(SearchInfo::mate),
+        _ => None,
+    };
+    let info = SearchInfo {
+        depth: value("depth").and_then(|depth| depth.parse().ok()),
+        score,
+        nps: value("nps").and_then(|nps| nps.parse().ok()),
+    };
+    (info != SearchInfo::default()).then_some(info)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn played(moves: &[&str]) -> Vec<PlayedMove> {
+        moves.iter().map(|uci| PlayedMove { uci: uci.to_string(), castling: None }).collect()
+    }
+
+    #[test]
+    fn test_parse_bestmove() {
+        let mut uci = UciProtocol::new();
+        assert_eq!(uci.parse("bestmove e2e4").0, vec![EngineEvent::BestMove { best_move: "e2e4".to_string(), ponder_move: None }]);
+        assert_eq!(uci.parse("bestmove e7e8q ponder e8d8").0,
+            vec![EngineEvent::BestMove { best_move: "e7e8q".to_string(), ponder_move: Some("e8d8".to_string()) }]);
+        assert_eq!(uci.parse("bestmove g1f3 ponder").0, vec![EngineEvent::BestMove { best_move: "g1f3".to_string(), ponder_move: None }]);
+        assert_eq!(uci.parse("info string using 4 threads").0, vec![]);
+        assert_eq!(uci.parse("id name Suprah 0.9").0, vec![EngineEvent::IdName("Suprah 0.9".to_string())]);
+    }
+
+    #[test]
+    fn test_parse_info() {
+        assert_eq!(parse_info("info depth 12 seldepth 18 score cp -35 nodes 1000 nps 250000 pv e2e4"),
+            Some(SearchInfo { depth: Some(12), score: Some("-0.35".to_string()), nps: Some(250000) }));
+        assert_eq!(parse_info("info depth 30 score mate -3"), Some(SearchInfo { depth: Some(30), score: Some("-M3".to_string()), nps: None }));
+        assert_eq!(parse_info("info currmove e2e4 currmovenumber 1"), None);
+    }
+
+    #[test]
+    fn test_position_command() {
+        let mut uci = UciProtocol::new();
+        let setup = GameSetup { start_fen: None, chess960: false, time_ms: 1000, inc_ms: 10, ponder: false };
+        uci.new_game(&setup);
+        let request = SearchRequest { moves: &[], wtime: 1000, btime: 900, winc: 10, binc: 20 };
+        assert_eq!(uci.go(&request), vec!["position startpos", "go wtime 1000 winc 10 btime 900 binc 20"]);
+
+        let fen = "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1".to_string();
+        uci.new_game(&GameSetup { start_fen: Some(fen), chess960: true, ..setup });
+        let moves = played(&["e2e4"]);
+        let request = SearchRequest { moves: &moves, wtime: 1000, btime: 900, winc: 10, binc: 20 };
+        assert_eq!(uci.go(&request)[0], "position fen bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1 moves e2e4");
+        assert_eq!(uci.ponder(&request, "e7e5").unwrap(), vec![
+            "position fen bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1 moves e2e4 e7e5",
+            "go ponder wtime 1000 winc 10 btime 900 binc 20",
+        ]);
+    }
+}
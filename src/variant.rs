@@ -0,0 +1,94 @@
+use rand::{RngExt, rngs::StdRng, SeedableRng};
+
+use crate::model::Board;
+use crate::service::Service;
+
+
+/// Start position rules of a game.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Variant {
+    Standard,
+    Chess960(u32),
+}
+
+impl Variant {
+    /// Parses the variant argument: `standard`, `chess960` (random position),
+    /// `chess960:<index>` or `chess960:seed:<seed>`.
+    pub fn from_arg(arg: &str) -> Option<Variant> {
+        let parts: Vec<&str> = arg.trim().split(':').collect();
+        match parts.as_slice() {
+            [""] | ["standard"] => Some(Variant::Standard),
+            ["chess960"] => Some(Variant::Chess960(rand::random_range(0..960))),
+            ["chess960", "seed", seed] => {
+                let mut rng = StdRng::seed_from_u64(seed.parse::<u64>().ok()?);
+                Some(Variant::Chess960(rng.random_range(0..960)))
+            }
+            ["chess960", index] => {
+                let index = index.parse::<u32>().ok()?;
+                if index < 960 { Some(Variant::Chess960(index)) } else { None }
+            }
+            _ => None,
+        }
+    }
+
+    /// Variant of the n-th pairing of a match, a seeded Chess960 argument gets a new start position
+    /// per pairing (seed + n) like the tournament scheduler does.
+    pub fn from_pairing_arg(arg: &str, pairing: u64) -> Option<Variant> {
+        match arg.trim().strip_prefix("chess960:seed:") {
+            Some(seed) => Variant::from_arg(&format!("chess960:seed:{}", seed.parse::<u64>().ok()?.wrapping_add(pairing))),
+            None => Variant::from_arg(arg),
+        }
+    }
+
+    pub fn is_chess960(&self) -> bool {
+        matches!(self, Variant::Chess960(_))
+    }
+
+    pub fn get_start_board(&self, service: &Service) -> Board {
+        match self {
+            Variant::Standard => service.fen.set_init_board(),
+            Variant::Chess960(index) => service.fen.set_chess960_board(*index),
+        }
+    }
+
+    /// Start position sent to the engines, chess960 games have to transmit theirs.
+    pub fn get_start_fen(&self, service: &Service) -> Option<String> {
+        match self {
+            Variant::Standard => None,
+            Variant::Chess960(index) => Some(service.fen.get_chess960_fen(*index)),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_arg() {
+        assert_eq!(Variant::from_arg(""), Some(Variant::Standard));
+        assert_eq!(Variant::from_arg("standard"), Some(Variant::Standard));
+        assert_eq!(Variant::from_arg("chess960:518"), Some(Variant::Chess960(518)));
+        assert_eq!(Variant::from_arg("chess960:960"), None);
+        assert_eq!(Variant::from_arg("chess960:seed:42"), Variant::from_arg("chess960:seed:42"));
+        assert!(Variant::from_arg("chess960").unwrap().is_chess960());
+        assert_eq!(Variant::from_arg("crazyhouse"), None);
+    }
+
+    #[test]
+    fn test_from_pairing_arg() {
+        assert_eq!(Variant::from_pairing_arg("chess960:seed:40", 2), Variant::from_arg("chess960:seed:42"));
+        assert_eq!(Variant::from_pairing_arg("chess960:7", 3), Some(Variant::Chess960(7)));
+        assert_eq!(Variant::from_pairing_arg("standard", 1), Some(Variant::Standard));
+        assert_eq!(Variant::from_pairing_arg("chess960:seed:x", 1), None);
+    }
+
+    #[test]
+    fn test_start_fen() {
+        let service = Service::new();
+        assert_eq!(Variant::Standard.get_start_fen(&service), None);
+        assert_eq!(Variant::Chess960(0).get_start_fen(&service),
+            Some("bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1".to_string()));
+    }
+}
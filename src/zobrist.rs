@@ -38,10 +38,10 @@ impl ZobristTable {
         fig_map.insert(24, 10);
         fig_map.insert(25, 11);
 
-        for i in 0..BOARD_SIZE {
+        for square in table.iter_mut() {
             for j in FIG {
-                let fig_index = fig_map.get(&(j as usize)).unwrap();
-                table[i][*fig_index] = rng.random();
+                let fig_index = fig_map.get(&j).unwrap();
+                square[*fig_index] = rng.random();
             }
         }
         let white_to_move = rng.random();
@@ -106,11 +106,103 @@ def compute_ratings_and_scores(games, iterations=100):
             
     return stats, ratings
 
+def resolve_challenger(name, engine_counts, challengers=()):
+    """Resolves a challenger name to the one used in the PGN file, None if no single name matches.
+    Names given exactly for the other challengers are left to them."""
+    if name in engine_counts or not engine_counts:
+        return name
+    candidates = [eng for eng in engine_counts if eng not in challengers]
+
+    # Same name apart from case and punctuation, e.g. "Suprah 0.9.3" for "suprah-0.9.3"
+    def clean(text):
+        return re.sub(r'[^a-z0-9]', '', text.lower())
+    matches = [eng for eng in candidates if clean(eng) == clean(name)]
+    if not matches:
+        # Same version number e.g. "0.9.3" from "suprah-0.9.3"
+        version_match = re.search(r'\d+\.\d+\.\d+', name)
+        if version_match:
+            matches = [eng for eng in candidates if version_match.group(0) in eng]
+    return matches[0] if len(matches) == 1 else None
+
+def head_to_head(games, challenger):
+    """Wins, draws and losses of the challenger against each of its opponents."""
+    h2h = defaultdict(lambda: {'wins': 0, 'draws': 0, 'losses': 0})
+    for game in games:
+        w = game['White'].strip()
+        b = game['Black'].strip()
+        res = game['Result'].strip()
+        if res not in ("1-0", "0-1", "1/2-1/2"):
+            continue
+
+        if w == challenger:
+            opp = b
+            if res == "1-0":
+                h2h[opp]['wins'] += 1
+            elif res == "0-1":
+                h2h[opp]['losses'] += 1
+            else:
+                h2h[opp]['draws'] += 1
+        elif b == challenger:
+            opp = w
+            if res == "0-1":
+                h2h[opp]['wins'] += 1
+            elif res == "1-0":
+                h2h[opp]['losses'] += 1
+            else:
+                h2h[opp]['draws'] += 1
+    return h2h
+
+def score_percent(st):
+    games_played = st['wins'] + st['draws'] + st['losses']
+    return (st['wins'] + 0.5 * st['draws']) / games_played * 100.0 if games_played > 0 else 0.0
+
+def print_gauntlet_summary(challenger, h2h):
+    print("=" * 70)
+    print(" " * 19 + "GAUNTLET HEAD-TO-HEAD SUMMARY")
+    print("=" * 70)
+    print(f"Challenger: {challenger}")
+    print("-" * 70)
+
+    for opp, st in sorted(h2h.items()):
+        w_d_l = f"{st['wins']}/{st['draws']}/{st['losses']}"
+        opp_disp = (opp[:30] + "..") if len(opp) > 32 else opp
+        print(f"vs {opp_disp:<32} {w_d_l:<9}")
+
+    print("-" * 70)
+    total_wins = sum(st['wins'] for st in h2h.values())
+    total_draws = sum(st['draws'] for st in h2h.values())
+    total_losses = sum(st['losses'] for st in h2h.values())
+    total_wdl = f"{total_wins}/{total_draws}/{total_losses}"
+    print(f"{'TOTAL (W/D/L):':<35} {total_wdl:<9}")
+    print("=" * 70)
+
+def print_challenger_comparison(challengers, results):
+    """Score percentage of every challenger against the opponents all of them played."""
+    shared = set.intersection(*(set(results[challenger]) for challenger in challengers)) - set(challengers)
+    columns = [(c[:10] + "..") if len(c) > 12 else c for c in challengers]
+    width = 26 + 14 * len(challengers)
+
+    print("=" * width)
+    print("SHARED-OPPONENT COMPARISON (Score %)".center(width).rstrip())
+    print("=" * width)
+    print(f"{'Opponent':<26}" + "".join(f"{column:>14}" for column in columns))
+    print("-" * width)
+    for opp in sorted(shared):
+        opp_disp = (opp[:22] + "..") if len(opp) > 24 else opp
+        print(f"{opp_disp:<26}" + "".join(f"{score_percent(results[c][opp]):>14.1f}" for c in challengers))
+    print("-" * width)
+    totals = []
+    for challenger in challengers:
+        total = {key: sum(results[challenger][opp][key] for opp in shared) for key in ('wins', 'draws', 'losses')}
+        totals.append(score_percent(total))
+    print(f"{'TOTAL':<26}" + "".join(f"{total:>14.1f}" for total in totals))
+    print("=" * width)
+
 def main():
     parser = argparse.ArgumentParser(description="Matt-Magie PGN Summary & Statistics Generator")
     parser.add_argument("pgn_file", help="Path to the PGN file to analyze")
     parser.add_argument("-g", "--games", action="store_true", help="Display the list of individual game results")
-    parser.add_argument("--gauntlet", help="Name of the challenger engine in Gauntlet mode")
+    parser.add_argument("--gauntlet", help="Name of the challenger engine in Gauntlet mode, comma-separated for several challengers")
     parser.add_argument("-i", "--iterations", type=int, default=100, help="Number of iterations for iterative Elo calculation (default: 100)")
     
     if len(sys.argv) < 2:
@@ -148,88 +240,23 @@ def main():
         print()
         
     if gauntlet_challenger:
-        # Resolve challenger name to what's in the PGN file
         engine_counts = defaultdict(int)
         for game in games:
             engine_counts[game['White'].strip()] += 1
             engine_counts[game['Black'].strip()] += 1
-            
-        detected_challenger = max(engine_counts, key=engine_counts.get) if engine_counts else gauntlet_challenger
-        
-        # Check if the passed name exists exactly
-        if gauntlet_challenger.strip() not in engine_counts:
-            # Try fuzzy matching (substring or common numbers like version digits)
-            matched = None
-            # Extract digits/version numbers e.g. "0.9.3" from "suprah-0.9.3"
-            version_match = re.search(r'\d+\.\d+\.\d+', gauntlet_challenger)
-            version_str = version_match.group(0) if version_match else None
-            
-            for eng in engine_counts:
-                if version_str and version_str in eng:
-                    matched = eng
-                    break
-                # Try simple clean alphanumeric match
-                eng_clean = re.sub(r'[^a-zA-Z0-9]', '', eng.lower())
-                target_clean = re.sub(r'[^a-zA-Z0-9]', '', gauntlet_challenger.lower())
-                if target_clean in eng_clean or eng_clean in target_clean:
-                    matched = eng
-                    break
-            
-            if matched:
-                gauntlet_challenger = matched
-            else:
-                gauntlet_challenger = detected_challenger
-                
-        print("=" * 70)
-        print(" " * 19 + "GAUNTLET HEAD-TO-HEAD SUMMARY")
-        print("=" * 70)
-        print(f"Challenger: {gauntlet_challenger}")
-        print("-" * 70)
-        
-        total_wins = 0
-        total_draws = 0
-        total_losses = 0
-        h2h = defaultdict(lambda: {'wins': 0, 'draws': 0, 'losses': 0})
-        
-        for game in games:
-            w = game['White'].strip()
-            b = game['Black'].strip()
-            res = game['Result'].strip()
-            if res not in ("1-0", "0-1", "1/2-1/2"):
-                continue
-                
-            if w == gauntlet_challenger:
-                opp = b
-                if res == "1-0":
-                    h2h[opp]['wins'] += 1
-                    total_wins += 1
-                elif res == "0-1":
-                    h2h[opp]['losses'] += 1
-                    total_losses += 1
-                else:
-                    h2h[opp]['draws'] += 1
-                    total_draws += 1
-            elif b == gauntlet_challenger:
-                opp = w
-                if res == "0-1":
-                    h2h[opp]['wins'] += 1
-                    total_wins += 1
-                elif res == "1-0":
-                    h2h[opp]['losses'] += 1
-                    total_losses += 1
-                else:
-                    h2h[opp]['draws'] += 1
-                    total_draws += 1
-                    
-        for opp, st in sorted(h2h.items()):
-            w_d_l = f"{st['wins']}/{st['draws']}/{st['losses']}"
-            opp_disp = (opp[:30] + "..") if len(opp) > 32 else opp
-            print(f"vs {opp_disp:<32} {w_d_l:<9}")
-            
-        print("-" * 70)
-        total_wdl = f"{total_wins}/{total_draws}/{total_losses}"
-        print(f"{'TOTAL (W/D/L):':<35} {total_wdl:<9}")
-        print("=" * 70)
+
+        names = [name.strip() for name in gauntlet_challenger.split(',') if name.strip()]
+        challengers = [resolve_challenger(name, engine_counts, names) for name in names]
+        for name, challenger in zip(names, challengers):
+            if challenger is None:
+                print(f"Error: challenger '{name}' matches no single engine of {pgn_file} ({', '.join(sorted(engine_counts))})")
+                sys.exit(1)
+        results = {challenger: head_to_head(games, challenger) for challenger in challengers}
+        for challenger in challengers:
+            print_gauntlet_summary(challenger, results[challenger])
+        if len(challengers) > 1:
+            print()
+            print_challenger_comparison(challengers, results)
         return
 
     stats, ratings = compute_ratings_and_scores(games, iterations=args.iterations)